@@ -1,16 +1,16 @@
 #[allow(unused_imports)]
 use cli_log::*;
 use std::{
-    env::temp_dir, fs::File, io::{
+    fs::File, io::{
         BufRead,
         Result,
-    }, sync::mpsc::{self, Sender}
+    }, sync::mpsc::{self, Sender}, time::{Duration, Instant}
 };
 
 use cli_clipboard::{ClipboardContext, ClipboardProvider};
 
 use crossterm::event::{
-    KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEvent, MouseEventKind
+    KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind
 };
 use ratatui::{
     layout::{
@@ -18,16 +18,18 @@ use ratatui::{
         Flex,
         Layout,
         Margin,
+        Position,
         Rect,
     }, style::
         Stylize, symbols::{
         self,
         scrollbar::{DOUBLE_HORIZONTAL, DOUBLE_VERTICAL},
-    }, text::Line, widgets::{
+    }, text::{Line, Span}, widgets::{
         Block,
         BorderType,
         Borders,
         Clear,
+        Gauge,
         ListState,
         Paragraph,
         Scrollbar,
@@ -36,10 +38,13 @@ use ratatui::{
 };
 use unicode_segmentation::UnicodeSegmentation;
 use crate::{
-    dataset::{DatasetRequest, DatasetResponse}, layerlist::TatLayerList, navparagraph::TatNavigableParagraph, numberinput::{TatNumberInput, TatNumberInputResult}, table::TableRects, types::{TatNavHorizontal, TatNavVertical}
+    columnpicker::{TatColumnPicker, TatColumnPickerResult}, commandline::{TatCommand, TatCommandLine, TatCommandLineResult, TatCompletionNames}, compare::{TatCompareView, TatCompareViewResult}, copymenu::{TatCopyMenu, TatCopyMenuResult}, dataset::{DatasetRequest, DatasetResponse}, export::TatExportProgress, exportdialog::{TatExportDialog, TatExportDialogResult}, layerlist::TatLayerList, map::TatMap, navparagraph::TatNavigableParagraph, recordview::{TatRecordView, TatRecordViewResult}, search::{TatSearchInput, TatSearchInputResult, TatSearchQuery}, textinput::{TatInputHistory, TatInputPurpose}, keys::{TatAction, TatKeyContext}, serialize::TatFeatureFormat, session::TatSession, table::TableRects, types::{TatNavHorizontal, TatNavVertical}, valuetree::{TatValueTree, TatValueTreeResult}
 };
 use crate::table::TatTable;
 
+/// How far apart two clicks on the same cell may be to count as a double-click
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
 const BORDER_LAYER_INFO: symbols::border::Set = symbols::border::Set {
     top_left: symbols::line::ROUNDED.horizontal_down,
     top_right: symbols::line::NORMAL.horizontal_down,
@@ -54,6 +59,13 @@ const BORDER_PREVIEW_TABLE: symbols::border::Set = symbols::border::Set {
     ..symbols::border::ROUNDED
 };
 
+/// What is done with the mark named by the next key press
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TatMarkCommand {
+    Set,
+    Jump,
+}
+
 /// Specificies the available menus of the program
 #[derive(PartialEq, Debug)]
 enum TatMenu {
@@ -69,6 +81,18 @@ enum TatMainMenuSectionFocus {
     PreviewTable,
 }
 
+/// The scrollbars which can be dragged with the mouse
+#[derive(PartialEq, Debug, Clone, Copy)]
+enum TatScrollbar {
+    LayerList,
+    LayerInfoVertical,
+    LayerInfoHorizontal,
+    TableVertical,
+    TableHorizontal,
+    PopupVertical,
+    PopupHorizontal,
+}
+
 /// Custom event enum which also wraps Crossterm events
 #[derive(Debug)]
 pub enum TatEvent {
@@ -85,12 +109,39 @@ pub struct TatApp {
     modal_popup: Option<TatNavigableParagraph>,
     table: TatTable,
     layerlist: TatLayerList,
+    map: TatMap,
+    show_map: bool,
     focused_section: TatMainMenuSectionFocus,
     clip: Option<ClipboardContext>,
     table_area: Rect,
-    number_input: Option<TatNumberInput>,
-    clipboard_feedback: Option<String>,
+    /// The whole area rendered into last time, used for finding what was clicked
+    frame_area: Rect,
+    /// When and where the left mouse button was last clicked, for detecting double-clicks
+    last_click: Option<(Instant, u16, u16)>,
+    dragged_scrollbar: Option<TatScrollbar>,
+    command_line: Option<TatCommandLine>,
+    /// The accepted commands, searches and export paths
+    input_history: TatInputHistory,
+    /// Set while waiting for the row of the FID given with :goto-fid
+    finding_fid: bool,
+    search_input: Option<TatSearchInput>,
+    /// Set after the key which sets or jumps to a mark, until the name of the mark is pressed
+    pending_mark: Option<TatMarkCommand>,
+    column_picker: Option<TatColumnPicker>,
+    record_view: Option<TatRecordView>,
+    compare_view: Option<TatCompareView>,
+    /// Shows a list or JSON value as a tree instead of the value pop-up
+    value_tree: Option<TatValueTree>,
+    export_dialog: Option<TatExportDialog>,
+    copy_menu: Option<TatCopyMenu>,
+    export_progress: Option<TatExportProgress>,
+    feedback: Option<String>,
     dataset_info_text: String,
+    /// Restored when the layers have been read and stored when the program is closed
+    session: Option<TatSession>,
+    /// The number of filters and sort orders of the session which are still being applied. The
+    /// views of the layers are restored once all have been applied.
+    pending_session_updates: usize,
     ds_request_tx: Sender<DatasetRequest>,
 }
 
@@ -115,26 +166,55 @@ impl TatApp {
             modal_popup: None,
             layerlist: TatLayerList::new(dataset_request_tx.clone()),
             table: TatTable::new(dataset_request_tx.clone()),
+            map: TatMap::new(dataset_request_tx.clone()),
+            show_map: false,
             focused_section: TatMainMenuSectionFocus::LayerList,
             clip,
             table_area: Rect::default(),
-            number_input: None,
-            clipboard_feedback: None,
+            frame_area: Rect::default(),
+            last_click: None,
+            dragged_scrollbar: None,
+            command_line: None,
+            input_history: TatInputHistory::default(),
+            finding_fid: false,
+            search_input: None,
+            pending_mark: None,
+            column_picker: None,
+            record_view: None,
+            compare_view: None,
+            value_tree: None,
+            export_dialog: None,
+            copy_menu: None,
+            export_progress: None,
+            feedback: None,
             dataset_info_text: String::default(),
+            session: None,
+            pending_session_updates: 0,
             ds_request_tx: dataset_request_tx,
         }
     }
 
+    /// Sets the session of the dataset, which is restored once the layers have been read
+    pub fn set_session(&mut self, session: TatSession) {
+        self.session = Some(session);
+    }
+
     /// Main execution loop of the program. The state of the program is rendered along with key and
     /// mouse events being handled
     pub fn run(&mut self, terminal: &mut DefaultTerminal, rx: mpsc::Receiver<TatEvent>) -> Result<()> {
         while !self.quit {
-            self.table_area = Rect::new(
-                0,
-                0,
-                terminal.size()?.width,
-                terminal.size()?.height,
+            // the table is laid out before handling the event so that e.g. opening the table reads
+            // the attributes for the columns which fit beside the map
+            let (table_area, _) = TatApp::table_view_areas(
+                Rect::new(
+                    0,
+                    0,
+                    terminal.size()?.width,
+                    terminal.size()?.height,
+                ),
+                self.show_map,
             );
+            self.table_area = table_area;
 
             // TODO: don't unwrap yada yada
             match rx.recv().unwrap() {
@@ -166,6 +246,7 @@ impl TatApp {
             },
             DatasetResponse::LayerSchemas(tat_layer_schemas) => {
                 self.table.set_layer_schemas(tat_layer_schemas);
+                self.restore_session();
             },
             DatasetResponse::AttributeView(view) => {
                 self.table.set_attribute_view(view);
@@ -173,8 +254,103 @@ impl TatApp {
             },
             DatasetResponse::AttributeViewUpdated => {
             },
+            DatasetResponse::Record(record) => {
+                if let Some(view) = self.record_view.as_mut()
+                    && let Some(record) = record
+                {
+                    view.set_record(record);
+                }
+            },
+            DatasetResponse::Comparison(comparison) => {
+                match comparison {
+                    Some(comparison) => {
+                        if let Some(schema) = self.table.layer_schemas().get(comparison.layer_index) {
+                            self.compare_view = Some(TatCompareView::new(comparison, schema));
+                        }
+                    },
+                    None => self.set_feedback("ERROR! Could not read the compared features".to_string()),
+                }
+            },
+            DatasetResponse::FidFound(fid, row) => {
+                if !self.finding_fid {
+                    return;
+                }
+
+                self.finding_fid = false;
+
+                match row {
+                    Some(row) => {
+                        self.table.record_jump();
+                        self.table.nav_v(TatNavVertical::Specific(row as i64));
+                    },
+                    None => self.set_feedback(format!("No feature with FID {fid} in the layer")),
+                }
+            },
+            DatasetResponse::LayerUpdated(layer_index, result) => {
+                match result {
+                    Ok(schema) => {
+                        self.table.update_layer_schema(*schema);
+                        self.map.reload_layer(layer_index);
+                        // the feature counts in the layer information have changed
+                        self.ds_request_tx.send(DatasetRequest::LayerInfos).unwrap();
+                    },
+                    Err(e) => self.set_feedback(format!("ERROR! Could not update the layer: {}", e)),
+                }
+
+                self.session_update_finished();
+            },
+            DatasetResponse::FidsFound(layer_index, rows) => {
+                let name = self.table.layer_schemas().get(layer_index).map(|schema| schema.name().to_string());
+
+                if let Some(session) = self.session.as_mut()
+                    && let Some(layer) = session.layers.iter_mut().find(|layer| Some(&layer.name) == name.as_ref())
+                {
+                    layer.marked_rows = rows.into_iter().collect();
+                }
+
+                self.session_update_finished();
+            },
             DatasetResponse::LayersBuilt => {
             },
+            DatasetResponse::DatasetReport(_) => {
+                // only requested by the info command
+            },
+            DatasetResponse::LayerGeometries(geometries) => {
+                self.map.set_geometries(geometries);
+            },
+            DatasetResponse::MapFeature(layer_index, feature) => {
+                self.map.set_highlighted_feature(layer_index, feature);
+            },
+            DatasetResponse::ExportProgress(progress) => {
+                self.export_progress = Some(progress);
+            },
+            DatasetResponse::ExportFinished(result) => {
+                self.export_progress = None;
+
+                match result {
+                    Ok(count) => self.set_feedback(format!("Export finished, {} feature(s) written!", count)),
+                    Err(e) => self.set_feedback(format!("ERROR! Could not export: {}", e)),
+                }
+            },
+            DatasetResponse::FeaturesSerialized(format, count, result) => {
+                match result {
+                    Ok(text) => self.copy_features_to_clipboard(text, format, count),
+                    Err(e) => self.set_feedback(format!("ERROR! Could not serialize features: {}", e)),
+                }
+            },
+            DatasetResponse::SearchFinished(result) => {
+                match result {
+                    Ok(results) => {
+                        let text = results.query.text.clone();
+                        self.table.set_search_results(results);
+
+                        if !self.table.jump_to_match(false) {
+                            self.set_feedback(format!("No matches for \"{}\"", text));
+                        }
+                    },
+                    Err(e) => self.set_feedback(format!("ERROR! Could not search: {}", e)),
+                }
+            },
             DatasetResponse::InvalidDataset => {
                 // should never happen
                 panic!()
@@ -190,14 +366,24 @@ impl TatApp {
 
     /// Renders the current menu and any other active pop-ups or dialogs
     pub fn render(&mut self, frame: &mut Frame) {
+        self.frame_area = frame.area();
+
         match self.current_menu {
             TatMenu::MainMenu => self.render_main_menu(frame.area(), frame),
             TatMenu::TableView => self.render_table_view(frame),
         }
 
+        self.render_record_view(frame);
+        self.render_compare_view(frame);
         self.render_popup(frame);
-        self.render_number_input(frame);
-        self.render_clipboard_feedback(frame);
+        self.render_value_tree(frame);
+        self.render_command_line(frame);
+        self.render_search_input(frame);
+        self.render_column_picker(frame);
+        self.render_export_dialog(frame);
+        self.render_copy_menu(frame);
+        self.render_export_progress(frame);
+        self.render_feedback(frame);
     }
 
     /// Opens the table view menu
@@ -209,7 +395,7 @@ impl TatApp {
 
     /// Closes the table view menu
     pub fn close_table(&mut self) {
-        self.table.reset();
+        self.table.cancel_visual();
         self.current_menu = TatMenu::MainMenu;
     }
 
@@ -218,15 +404,15 @@ impl TatApp {
         self.table.set_layer_index(idx);
     }
 
-    /// Renders the clipboard feedback message (if any)
-    fn render_clipboard_feedback(&mut self, frame: &mut Frame) {
-        if let Some(feedback) = self.clipboard_feedback.as_mut() {
-            let cleared_area = TatApp::number_input_area(frame.area(), 50);
+    /// Renders the feedback message (if any), e.g. after copying to the clipboard
+    fn render_feedback(&mut self, frame: &mut Frame) {
+        if let Some(feedback) = self.feedback.as_mut() {
+            let cleared_area = TatApp::dialog_area(frame.area(), 50);
             let block_area = cleared_area.inner(Margin { horizontal: 1, vertical: 1 });
 
             let block = Block::default()
                         .borders(Borders::ALL)
-                        .border_style(crate::shared::palette::DEFAULT.highlighted_style())
+                        .border_style(crate::shared::palette::current().highlighted_style())
                         .border_type(BorderType::Rounded)
                         .title_bottom(Line::raw( " <press any key to continue> ").centered());
 
@@ -234,28 +420,86 @@ impl TatApp {
 
             frame.render_widget(Clear, cleared_area);
             frame.render_widget(block, block_area);
-            frame.render_widget(feedback.as_str(), text_area);
+            frame.render_widget(Span::styled(feedback.as_str(), crate::shared::palette::current().default_style()), text_area);
+        }
+    }
+
+    /// Renders the export dialog (if any)
+    fn render_export_dialog(&mut self, frame: &mut Frame) {
+        if let Some(dialog) = self.export_dialog.as_ref() {
+            dialog.render(frame, TatExportDialog::area(frame.area(), 60));
+        }
+    }
+
+    /// Renders the menu for copying whole features (if any)
+    fn render_copy_menu(&mut self, frame: &mut Frame) {
+        if let Some(menu) = self.copy_menu.as_ref() {
+            menu.render(frame, frame.area());
         }
     }
 
-    /// Renders the number input dialog (if any)
-    fn render_number_input(&mut self, frame: &mut Frame) {
-        if let Some(number_input) = self.number_input.as_mut() {
-            let cleared_area = TatApp::number_input_area(frame.area(), 50);
+    /// Renders the progress bar of an ongoing export (if any)
+    fn render_export_progress(&mut self, frame: &mut Frame) {
+        if let Some(progress) = self.export_progress {
+            let cleared_area = TatApp::dialog_area(frame.area(), 50);
             let block_area = cleared_area.inner(Margin { horizontal: 1, vertical: 1 });
 
-            let block = Block::default()
-                        .title(Line::raw(" Jump To Feature ").bold().underlined().centered())
+            let gauge = Gauge::default()
+                .block(
+                    Block::default()
+                        .title(Line::raw(" Exporting ").bold().underlined().centered())
                         .borders(Borders::ALL)
-                        .border_style(crate::shared::palette::DEFAULT.highlighted_style())
+                        .border_style(crate::shared::palette::current().highlighted_style())
                         .border_type(BorderType::Rounded)
-                        .title_bottom(Line::raw( " <press Enter to jump, q to cancel> ").centered());
-
-            let input_area = block_area.inner(Margin { horizontal: 1, vertical: 1 });
+                )
+                .gauge_style(crate::shared::palette::current().selected_style())
+                .ratio(progress.ratio())
+                .label(format!("{} / {}", progress.exported, progress.total));
 
             frame.render_widget(Clear, cleared_area);
-            frame.render_widget(block, block_area);
-            number_input.render(frame, input_area);
+            frame.render_widget(gauge, block_area);
+        }
+    }
+
+    /// Renders the command line (if any)
+    fn render_command_line(&mut self, frame: &mut Frame) {
+        if let Some(command_line) = self.command_line.as_ref() {
+            command_line.render(frame, TatApp::dialog_area(frame.area(), 60));
+        }
+    }
+
+    /// Renders the record view (if any)
+    fn render_record_view(&mut self, frame: &mut Frame) {
+        if let Some(view) = self.record_view.as_mut() {
+            view.render(frame, TatApp::popup_area(frame.area(), 80, 80));
+        }
+    }
+
+    /// Renders the comparison of two features (if any)
+    fn render_compare_view(&mut self, frame: &mut Frame) {
+        if let Some(view) = self.compare_view.as_mut() {
+            view.render(frame, TatApp::popup_area(frame.area(), 90, 80));
+        }
+    }
+
+    /// Renders the tree of a list or JSON value (if any)
+    fn render_value_tree(&mut self, frame: &mut Frame) {
+        if let Some(tree) = self.value_tree.as_mut() {
+            tree.render(frame, TatApp::popup_area(frame.area(), 70, 70));
+        }
+    }
+
+    /// Renders the search dialog (if any)
+    fn render_search_input(&mut self, frame: &mut Frame) {
+        if let Some(search_input) = self.search_input.as_ref() {
+            search_input.render(frame, TatApp::dialog_area(frame.area(), 50));
+        }
+    }
+
+    /// Renders the column picker (if any)
+    fn render_column_picker(&mut self, frame: &mut Frame) {
+        if let Some(picker) = self.column_picker.as_ref() {
+            picker.render(frame, frame.area());
         }
     }
 
@@ -289,13 +533,14 @@ impl TatApp {
 
 
             let block = popup.paragraph()
+                .style(crate::shared::palette::current().default_style())
                 .block(
                     Block::default()
                         .title(Line::raw(title).bold().underlined().centered())
                         .borders(Borders::ALL)
-                        .border_style(crate::shared::palette::DEFAULT.highlighted_style())
+                        .border_style(crate::shared::palette::current().highlighted_style())
                         .border_type(BorderType::Rounded)
-                        .title_bottom(Line::raw(crate::shared::POPUP_HINT).centered())
+                        .title_bottom(Line::raw(crate::shared::popup_hint()).centered())
                 );
 
             frame.render_widget(Clear, cleared_area);
@@ -305,7 +550,7 @@ impl TatApp {
                 let scrollbar = Scrollbar::default()
                     .orientation(ScrollbarOrientation::VerticalRight)
                     .begin_symbol(Some(DOUBLE_VERTICAL.begin))
-                    .style(crate::shared::palette::DEFAULT.highlighted_style())
+                    .style(crate::shared::palette::current().highlighted_style())
                     .end_symbol(Some(DOUBLE_VERTICAL.end));
 
                 let scrollbar_area = popup_area.inner(Margin { horizontal: 1, vertical: 1 });
@@ -323,7 +568,7 @@ impl TatApp {
                 let scrollbar = Scrollbar::default()
                     .orientation(ScrollbarOrientation::HorizontalBottom)
                     .begin_symbol(Some(DOUBLE_HORIZONTAL.begin))
-                    .style(crate::shared::palette::DEFAULT.highlighted_style())
+                    .style(crate::shared::palette::current().highlighted_style())
                     .end_symbol(Some(DOUBLE_HORIZONTAL.end));
 
                 let scrollbar_area = popup_area.inner(Margin { horizontal: 2, vertical: 1 });
@@ -341,36 +586,172 @@ impl TatApp {
 
     /// Attempts to copy a value to the system keyboard
     fn copy_table_value_to_clipboard(&mut self) {
+        self.copy_value_to_clipboard(self.table.selected_value());
+    }
+
+    /// Attempts to copy a value to the system clipboard
+    fn copy_value_to_clipboard(&mut self, value: Option<String>) {
         if let Some(clip) = self.clip.as_mut() {
-            if let Some(text_to_copy) = self.table.selected_value() {
+            if let Some(text_to_copy) = value {
                 match clip.set_contents(text_to_copy.to_string()) {
                     Ok(()) => {
                         let postscript = " copied to clipboard!";
                         let max_len = 50;
                         if text_to_copy.chars().count() < max_len {
-                            self.set_clipboard_feedback(format!("\"{text_to_copy}\"{postscript}"));
+                            self.set_feedback(format!("\"{text_to_copy}\"{postscript}"));
                         } else {
                             let graph = text_to_copy.graphemes(true);
                             let substring: String = graph.into_iter().take(max_len).collect();
 
-                            self.set_clipboard_feedback(format!("\"{}…\"{}", &substring, postscript));
+                            self.set_feedback(format!("\"{}…\"{}", &substring, postscript));
                         }
                         return;
                     }
                     Err(e) => {
-                        self.clipboard_feedback = Some(format!("ERROR! Could not copy to clipboard: {}", e.to_string()));
+                        self.feedback = Some(format!("ERROR! Could not copy to clipboard: {}", e.to_string()));
                     }
                 }
             } else {
-                self.clipboard_feedback = Some(format!("NULL value NOT copied to clipboard!"));
+                self.feedback = Some(format!("NULL value NOT copied to clipboard!"));
             }
         } else {
-            self.clipboard_feedback = Some("ERROR! Could not copy to clipboard: clipboard context does not exist!".to_string());
+            self.feedback = Some("ERROR! Could not copy to clipboard: clipboard context does not exist!".to_string());
+        }
+    }
+
+    /// Requests the selected features (or the current feature if none are selected) to be
+    /// serialized, they are copied to the clipboard once the dataset responds
+    fn request_features_copy(&mut self, format: TatFeatureFormat) {
+        if let Some(schema) = self.table.layer_schema() {
+            self.ds_request_tx.send(
+                DatasetRequest::SerializeFeatures(
+                    schema.index(),
+                    self.table.selected_rows_or_current(),
+                    format,
+                )
+            ).unwrap();
+        }
+    }
+
+    /// Requests the current layer to be searched, the table jumps to the first match once the
+    /// dataset responds
+    fn request_search(&mut self, query: TatSearchQuery) {
+        if let Some(schema) = self.table.layer_schema() {
+            self.ds_request_tx.send(
+                DatasetRequest::Search(
+                    schema.index(),
+                    query,
+                )
+            ).unwrap();
+        }
+    }
+
+    /// Attempts to copy serialized features to the system clipboard
+    fn copy_features_to_clipboard(&mut self, text: String, format: TatFeatureFormat, count: usize) {
+        let clip = match self.clip.as_mut() {
+            Some(clip) => clip,
+            None => {
+                self.set_feedback("ERROR! Could not copy to clipboard: clipboard context does not exist!".to_string());
+                return;
+            },
+        };
+
+        match clip.set_contents(text) {
+            Ok(()) => {
+                let features = if count == 1 { "Feature".to_string() } else { format!("{} features", count) };
+                self.set_feedback(format!("{} copied to clipboard as {}!", features, format));
+            },
+            Err(e) => self.set_feedback(format!("ERROR! Could not copy to clipboard: {}", e)),
+        }
+    }
+
+    /// Restores the filters and the sort orders of the layers from the session (if any), finds
+    /// the rows of the marked features and then restores the views and the selected layer
+    fn restore_session(&mut self) {
+        let Some(session) = self.session.as_ref() else {
+            return;
+        };
+
+        for layer in &session.layers {
+            let Some(schema) = self.table.layer_schemas().iter().find(|schema| schema.name() == layer.name) else {
+                continue;
+            };
+
+            if let Some(filter) = layer.filter.as_ref() {
+                self.ds_request_tx.send(DatasetRequest::SetFilter(schema.index(), Some(filter.clone()))).unwrap();
+                self.pending_session_updates += 1;
+            }
+
+            // the sorted field may have been removed since
+            if let Some(order) = layer.sort.as_ref()
+                && schema.attribute_fields().iter().any(|field| field.name() == order.field)
+            {
+                self.ds_request_tx.send(DatasetRequest::Sort(schema.index(), Some(order.clone()))).unwrap();
+                self.pending_session_updates += 1;
+            }
+
+            // the rows are found after the layer has been filtered and sorted
+            if !layer.marked_fids.is_empty() {
+                self.ds_request_tx.send(DatasetRequest::FindFids(schema.index(), layer.marked_fids.iter().copied().collect())).unwrap();
+                self.pending_session_updates += 1;
+            }
+        }
+
+        // updating a layer resets its view, so the views are restored once the layers are updated
+        if self.pending_session_updates == 0 {
+            self.restore_session_views();
         }
     }
 
+    /// Restores the views of the layers and the selected layer from the session (if any)
+    fn restore_session_views(&mut self) {
+        let Some(session) = self.session.as_ref() else {
+            return;
+        };
+
+        self.table.restore_layer_sessions(&session.layers);
+
+        let layer_index = session.layer.as_ref().and_then(|name| {
+            self.table.layer_schemas().iter().position(|schema| schema.name() == name)
+        });
+
+        if let Some(layer_index) = layer_index {
+            self.layerlist.nav(TatNavVertical::Specific(layer_index as i64));
+            self.sync_table_layer();
+        }
+    }
+
+    /// Marks the filter, sort order or marked rows of the session as restored. The views are
+    /// restored once all of them have been.
+    fn session_update_finished(&mut self) {
+        if self.pending_session_updates == 0 {
+            return;
+        }
+
+        self.pending_session_updates -= 1;
+
+        if self.pending_session_updates == 0 {
+            self.restore_session_views();
+        }
+    }
+
+    /// Stores the views of the layers and the selected layer in the session (if any). The session
+    /// is saved by the dataset, which knows the FIDs of the marked rows.
+    fn save_session(&mut self) {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+
+        session.layer = self.table.layer_schema().map(|schema| schema.name().to_string());
+        session.layers = self.table.layer_sessions();
+
+        self.ds_request_tx.send(DatasetRequest::SaveSession(session.clone())).unwrap();
+    }
+
     /// Terminates the program
     fn close(&mut self) {
+        self.save_session();
+
         self.ds_request_tx.send(
             DatasetRequest::Terminate,
         ).unwrap();
@@ -378,9 +759,9 @@ impl TatApp {
         self.quit = true;
     }
 
-    /// Activates the clipboard feedback, which is shown in a small pop-up
-    fn set_clipboard_feedback(&mut self, text: String) {
-        self.clipboard_feedback = Some(text);
+    /// Activates the feedback message, which is shown in a small pop-up
+    fn set_feedback(&mut self, text: String) {
+        self.feedback = Some(text);
     }
 
     /// Handles incoming mouse events and delegates to other widgets
@@ -388,129 +769,708 @@ impl TatApp {
         match event.kind {
             MouseEventKind::ScrollUp => self.delegate_nav_v(TatNavVertical::MouseScrollUp),
             MouseEventKind::ScrollDown => self.delegate_nav_v(TatNavVertical::MouseScrollDown),
+            MouseEventKind::Down(button) => self.handle_click(event.column, event.row, button),
+            MouseEventKind::Drag(MouseButton::Left) => {
+                if let Some(scrollbar) = self.dragged_scrollbar {
+                    self.drag_scrollbar(scrollbar, event.column, event.row);
+                }
+            },
+            MouseEventKind::Up(MouseButton::Left) => self.dragged_scrollbar = None,
             _ => (),
         }
     }
 
+    /// Handles a mouse click. A click selects what was clicked and focuses its section, a
+    /// double-click opens a layer or shows a value and a right-click copies a value. Clicking a
+    /// scrollbar starts dragging it.
+    fn handle_click(&mut self, column: u16, row: u16, button: MouseButton) {
+        // like any key, a click dismisses the feedback message
+        if self.feedback.is_some() {
+            self.feedback = None;
+            return;
+        }
+
+        // the dialogs are only used with the keyboard
+        if self.dialog_open() {
+            return;
+        }
+
+        let double_click = button == MouseButton::Left && self.last_click.is_some_and(|(time, last_column, last_row)| {
+            last_column == column && last_row == row && time.elapsed() <= DOUBLE_CLICK_INTERVAL
+        });
+
+        self.last_click = if button == MouseButton::Left && !double_click {
+            Some((Instant::now(), column, row))
+        } else {
+            None
+        };
+
+        let position = Position { x: column, y: row };
+
+        if let Some((scrollbar, _)) = self.scrollbar_areas().into_iter().find(|(_, area)| area.contains(position)) {
+            if button == MouseButton::Left {
+                self.dragged_scrollbar = Some(scrollbar);
+                self.drag_scrollbar(scrollbar, column, row);
+            }
+
+            return;
+        }
+
+        // only the scrollbars of a pop-up can be clicked
+        if self.modal_popup.is_some() {
+            return;
+        }
+
+        match self.current_menu {
+            TatMenu::TableView => {
+                if !self.table.click(column, row, false) {
+                    return;
+                }
+
+                match button {
+                    MouseButton::Left if double_click => self.show_full_value_popup(),
+                    MouseButton::Right => self.copy_table_value_to_clipboard(),
+                    _ => (),
+                }
+            },
+            TatMenu::MainMenu => {
+                let (list_area, info_area, preview_table_area) = self.main_menu_section_areas();
+
+                if list_area.contains(position) {
+                    self.focused_section = TatMainMenuSectionFocus::LayerList;
+
+                    // the first row is the border
+                    if row > list_area.y && self.layerlist.select_visible_row((row - list_area.y - 1) as usize) {
+                        self.sync_table_layer();
+
+                        if double_click {
+                            self.open_table();
+                        }
+                    }
+                } else if info_area.contains(position) {
+                    self.focused_section = TatMainMenuSectionFocus::LayerInfo;
+                } else if preview_table_area.contains(position) {
+                    self.focused_section = TatMainMenuSectionFocus::PreviewTable;
+
+                    let clicked = self.table.click(column, row, true);
+
+                    match button {
+                        MouseButton::Left if double_click => self.open_table(),
+                        MouseButton::Right if clicked => self.copy_table_value_to_clipboard(),
+                        _ => (),
+                    }
+                }
+            },
+        }
+    }
+
+    /// Scrolls the content of the scrollbar to match the position of the mouse
+    fn drag_scrollbar(&mut self, scrollbar: TatScrollbar, column: u16, row: u16) {
+        let Some((_, area)) = self.scrollbar_areas().into_iter().find(|(shown, _)| *shown == scrollbar) else {
+            // e.g. the pop-up has been closed
+            self.dragged_scrollbar = None;
+            return;
+        };
+
+        let vertical = TatNavVertical::Fraction(TatApp::scrollbar_fraction(area.y, area.height, row));
+        let horizontal = TatNavHorizontal::Fraction(TatApp::scrollbar_fraction(area.x, area.width, column));
+
+        match scrollbar {
+            TatScrollbar::LayerList => {
+                self.layerlist.nav(vertical);
+                self.sync_table_layer();
+            },
+            TatScrollbar::LayerInfoVertical => {
+                if let Some(info) = self.layerlist.current_layer_info_paragraph() {
+                    info.nav_v(vertical);
+                }
+            },
+            TatScrollbar::LayerInfoHorizontal => {
+                if let Some(info) = self.layerlist.current_layer_info_paragraph() {
+                    info.nav_h(horizontal);
+                }
+            },
+            TatScrollbar::TableVertical => self.table.nav_v(vertical),
+            TatScrollbar::TableHorizontal => self.table.nav_h(horizontal),
+            TatScrollbar::PopupVertical => {
+                if let Some(popup) = self.modal_popup.as_mut() {
+                    popup.nav_v(vertical);
+                }
+            },
+            TatScrollbar::PopupHorizontal => {
+                if let Some(popup) = self.modal_popup.as_mut() {
+                    popup.nav_h(horizontal);
+                }
+            },
+        }
+    }
+
+    /// Returns the scrollbars which are currently shown and their areas. Only the scrollbars of
+    /// the pop-up are returned if one is open.
+    fn scrollbar_areas(&mut self) -> Vec<(TatScrollbar, Rect)> {
+        let mut areas = vec![];
+
+        // see render_popup(), render_layer_info() and TatLayerList::render() for the areas
+        if let Some(popup) = self.modal_popup.as_ref() {
+            let popup_area = TatApp::popup_area(self.frame_area, 70, 70).inner(Margin { horizontal: 1, vertical: 1 });
+            let (_, has_h_scrollbar, _, has_v_scrollbar) = TatApp::text_area_dimensions(
+                &popup_area,
+                popup.max_line_len() as i64,
+                popup.total_lines() as i64,
+            );
+
+            if has_v_scrollbar && let Some(area) = popup_area.inner(Margin { horizontal: 1, vertical: 1 }).columns().next_back() {
+                areas.push((TatScrollbar::PopupVertical, area));
+            }
+
+            if has_h_scrollbar && let Some(area) = popup_area.inner(Margin { horizontal: 2, vertical: 1 }).rows().next_back() {
+                areas.push((TatScrollbar::PopupHorizontal, area));
+            }
+
+            return areas;
+        }
+
+        match self.current_menu {
+            TatMenu::TableView => {
+                let (v_scroll_area, h_scroll_area) = self.table.scrollbar_areas();
+
+                areas.push((TatScrollbar::TableVertical, v_scroll_area));
+                areas.push((TatScrollbar::TableHorizontal, h_scroll_area));
+            },
+            TatMenu::MainMenu => {
+                let (list_area, info_area, _) = self.main_menu_section_areas();
+
+                if self.layerlist.layer_count() > self.layerlist.available_rows()
+                    && let Some(area) = list_area.inner(Margin { horizontal: 0, vertical: 1 }).columns().next_back()
+                {
+                    areas.push((TatScrollbar::LayerList, area));
+                }
+
+                if let Some(info) = self.layerlist.current_layer_info_paragraph() {
+                    let (_, has_h_scrollbar, _, has_v_scrollbar) = TatApp::text_area_dimensions(
+                        &info_area,
+                        info.max_line_len() as i64,
+                        info.total_lines() as i64,
+                    );
+                    let scrollbar_area = info_area.inner(Margin { horizontal: 1, vertical: 1 });
+
+                    if has_v_scrollbar && let Some(area) = scrollbar_area.columns().next_back() {
+                        areas.push((TatScrollbar::LayerInfoVertical, area));
+                    }
+
+                    if has_h_scrollbar && let Some(area) = scrollbar_area.rows().next_back() {
+                        areas.push((TatScrollbar::LayerInfoHorizontal, area));
+                    }
+                }
+            },
+        }
+
+        areas
+    }
+
+    /// Returns whether a dialog which takes all input is open
+    fn dialog_open(&self) -> bool {
+        self.command_line.is_some()
+            || self.search_input.is_some()
+            || self.column_picker.is_some()
+            || self.record_view.is_some()
+            || self.compare_view.is_some()
+            || self.value_tree.is_some()
+            || self.export_dialog.is_some()
+            || self.copy_menu.is_some()
+            || self.export_progress.is_some()
+    }
+
     /// Handles incoming key events and delegates to other widgets
     fn handle_key(&mut self, key: KeyEvent) {
         let ctrl_down: bool = key.modifiers.contains(KeyModifiers::CONTROL);
         let in_layer_list: bool = matches!(self.current_menu, TatMenu::MainMenu) && matches!(self.focused_section, TatMainMenuSectionFocus::LayerList);
         let in_table: bool = matches!(self.current_menu, TatMenu::TableView);
-        let in_main_menu: bool = matches!(self.current_menu, TatMenu::MainMenu);
         let in_preview_table: bool = matches!(self.focused_section, TatMainMenuSectionFocus::PreviewTable);
         let popup_open: bool = self.modal_popup.is_some();
 
+        // the export has to finish before anything else can be done
+        if self.export_progress.is_some() {
+            return;
+        }
+
+        if let Some(dialog) = self.export_dialog.as_mut() {
+            match dialog.key_press(key.code, ctrl_down) {
+                TatExportDialogResult::Close => self.export_dialog = None,
+                TatExportDialogResult::Accept(request) => {
+                    self.export_dialog = None;
+                    self.input_history.add(TatInputPurpose::ExportPath, &request.path);
+                    self.export_progress = Some(TatExportProgress::default());
+                    self.ds_request_tx.send(DatasetRequest::Export(request)).unwrap();
+                },
+                _ => (),
+            }
+
+            return;
+        }
+
+        if let Some(menu) = self.copy_menu.as_mut() {
+            match menu.key_press(key) {
+                TatCopyMenuResult::Close => self.copy_menu = None,
+                TatCopyMenuResult::Accept(format) => {
+                    self.copy_menu = None;
+                    self.request_features_copy(format);
+                },
+                _ => (),
+            }
+
+            return;
+        }
+
+        if let Some(tree) = self.value_tree.as_mut() {
+            match tree.key_press(key) {
+                TatValueTreeResult::Close => self.value_tree = None,
+                TatValueTreeResult::CopyValue(value) => self.copy_value_to_clipboard(Some(value)),
+                _ => (),
+            }
+
+            return;
+        }
+
         if in_table {
-            if let Some(number_input) = & mut self.number_input {
-                let res = number_input.key_press(key.code, ctrl_down);
-
-                match res {
-                    TatNumberInputResult::Close => self.number_input = None,
-                    TatNumberInputResult::Accept(num) => {
-                        self.table.nav_v(TatNavVertical::Specific(num));
-                        self.number_input = None;
-                    }
+            if let Some(command_line) = self.command_line.as_mut() {
+                match command_line.key_press(key.code, ctrl_down) {
+                    TatCommandLineResult::Close => self.command_line = None,
+                    TatCommandLineResult::Accept(text) => {
+                        self.input_history.add(TatInputPurpose::Command, &text);
+
+                        // the command line stays open to show why the command could not be run
+                        let result = text.parse().and_then(|command| self.run_command(command));
+                        match result {
+                            Ok(()) => self.command_line = None,
+                            Err(e) => {
+                                if let Some(command_line) = self.command_line.as_mut() {
+                                    command_line.set_error(e);
+                                }
+                            },
+                        }
+                    },
+                    _ => (),
+                }
+
+                return;
+            }
+
+            if let Some(search_input) = self.search_input.as_mut() {
+                match search_input.key_press(key.code, ctrl_down) {
+                    TatSearchInputResult::Close => self.search_input = None,
+                    TatSearchInputResult::Accept(query) => {
+                        self.search_input = None;
+                        self.input_history.add(TatInputPurpose::Search, &query.text);
+                        self.request_search(query);
+                    },
                     _ => (),
                 }
 
                 return;
             }
+
+            if let Some(view) = self.compare_view.as_mut() {
+                if view.key_press(key) == TatCompareViewResult::Close {
+                    self.compare_view = None;
+                }
+
+                return;
+            }
+
+            if !popup_open && let Some(view) = self.record_view.as_mut() {
+                match view.key_press(key) {
+                    TatRecordViewResult::Close => self.record_view = None,
+                    TatRecordViewResult::PreviousFeature => {
+                        self.table.nav_v(TatNavVertical::UpOne);
+                        self.request_record();
+                    },
+                    TatRecordViewResult::NextFeature => {
+                        self.table.nav_v(TatNavVertical::DownOne);
+                        self.request_record();
+                    },
+                    TatRecordViewResult::ShowValue => {
+                        let row = view.record().map_or(self.table.current_row(), |record| record.row);
+                        let name = view.selected_field_name().unwrap_or("UNKNOWN COLUMN").to_string();
+                        let value = view.selected_value();
+                        let nested = view.selected_field_nested();
+
+                        self.show_value_popup(row, &name, value, nested);
+                    },
+                    TatRecordViewResult::CopyValue => {
+                        let value = view.selected_value();
+
+                        self.copy_value_to_clipboard(value);
+                    },
+                    _ => (),
+                }
+
+                return;
+            }
+
+            if let Some(picker) = self.column_picker.as_mut() {
+                match picker.key_press(key.code, ctrl_down) {
+                    TatColumnPickerResult::Close => self.column_picker = None,
+                    TatColumnPickerResult::Accept(column) => {
+                        self.column_picker = None;
+                        self.table.nav_h(TatNavHorizontal::Specific(column));
+                    },
+                    _ => (),
+                }
+
+                return;
+            }
+
+            if let Some(command) = self.pending_mark.take() {
+                // any other key cancels
+                if let KeyCode::Char(name @ 'a'..='z') = key.code {
+                    match command {
+                        TatMarkCommand::Set => self.table.set_mark(name),
+                        TatMarkCommand::Jump => {
+                            if !self.table.jump_to_mark(name) {
+                                self.set_feedback(format!("Mark '{}' is not set in this layer", name));
+                            }
+                        },
+                    }
+                }
+
+                return;
+            }
         }
 
 
-        match key.code {
-            KeyCode::Char('q') if ctrl_down => self.close(),
-            KeyCode::Char('q') | KeyCode::Esc => self.previous_menu(),
-            KeyCode::Char('g') => self.delegate_nav_v(TatNavVertical::First),
-            KeyCode::Char('G') => self.delegate_nav_v(TatNavVertical::Last),
-            KeyCode::Char('k') | KeyCode::Up => self.delegate_nav_v(TatNavVertical::UpOne),
-            KeyCode::Char('j') | KeyCode::Down => self.delegate_nav_v(TatNavVertical::DownOne),
-            KeyCode::Char('d') if ctrl_down => self.delegate_nav_v(TatNavVertical::DownHalfParagraph),
-            KeyCode::Char('u') if ctrl_down => self.delegate_nav_v(TatNavVertical::UpHalfParagraph),
-            KeyCode::Char('f') if ctrl_down => self.delegate_nav_v(TatNavVertical::DownParagraph),
-            KeyCode::Char('b') if ctrl_down => self.delegate_nav_v(TatNavVertical::UpParagraph),
-            KeyCode::PageDown => self.delegate_nav_v(TatNavVertical::DownParagraph),
-            KeyCode::PageUp => self.delegate_nav_v(TatNavVertical::UpParagraph),
-            KeyCode::Char('h') | KeyCode::Left => self.delegate_nav_h(TatNavHorizontal::LeftOne),
-            KeyCode::Char('l') | KeyCode::Right => self.delegate_nav_h(TatNavHorizontal::RightOne),
-            KeyCode::Char('0') | KeyCode::Home => self.delegate_nav_h(TatNavHorizontal::Home),
-            KeyCode::Char('$') | KeyCode::End => self.delegate_nav_h(TatNavHorizontal::End),
-            KeyCode::Char('c') if ctrl_down && in_table => {
+        let context = if in_table { TatKeyContext::Table } else { TatKeyContext::MainMenu };
+        let action = crate::config::config().keys.action(&key, context)
+            // Esc always leaves the current menu, whatever the bindings are
+            .or((key.code == KeyCode::Esc).then_some(TatAction::PreviousMenu));
+
+        match action {
+            Some(TatAction::Quit) => self.close(),
+            Some(TatAction::PreviousMenu) => self.previous_menu(),
+            Some(TatAction::ScrollTop) => {
+                if in_table && !popup_open {
+                    self.table.record_jump();
+                }
+                self.delegate_nav_v(TatNavVertical::First);
+            },
+            Some(TatAction::ScrollBottom) => {
+                if in_table && !popup_open {
+                    self.table.record_jump();
+                }
+                self.delegate_nav_v(TatNavVertical::Last);
+            },
+            Some(TatAction::Up) => self.delegate_nav_v(TatNavVertical::UpOne),
+            Some(TatAction::Down) => self.delegate_nav_v(TatNavVertical::DownOne),
+            Some(TatAction::HalfPageDown) => self.delegate_nav_v(TatNavVertical::DownHalfParagraph),
+            Some(TatAction::HalfPageUp) => self.delegate_nav_v(TatNavVertical::UpHalfParagraph),
+            Some(TatAction::PageDown) => self.delegate_nav_v(TatNavVertical::DownParagraph),
+            Some(TatAction::PageUp) => self.delegate_nav_v(TatNavVertical::UpParagraph),
+            Some(TatAction::Left) => self.delegate_nav_h(TatNavHorizontal::LeftOne),
+            Some(TatAction::Right) => self.delegate_nav_h(TatNavHorizontal::RightOne),
+            Some(TatAction::FirstColumn) => self.delegate_nav_h(TatNavHorizontal::Home),
+            Some(TatAction::LastColumn) => self.delegate_nav_h(TatNavHorizontal::End),
+            Some(TatAction::CopyValue) => {
                 self.copy_table_value_to_clipboard();
+
                 return;
             },
-            KeyCode::Char('y') if in_table => {
-                self.copy_table_value_to_clipboard();
+            Some(TatAction::CopyFeatures) if !popup_open => {
+                self.copy_menu = Some(TatCopyMenu::new());
 
                 return;
             },
-            KeyCode::Char('L') =>  {
-                if !popup_open {
-                    self.show_gdal_log();
+            Some(TatAction::JumpToColumn) if !popup_open => self.open_column_picker(),
+            Some(TatAction::ShowRecord) if !popup_open => self.open_record_view(),
+            Some(TatAction::Compare) if !popup_open => {
+                let requested = self.request_comparison();
+
+                if !requested {
+                    self.set_feedback("Mark one feature and move to another one (or mark two features) to compare them".to_string());
+
+                    return;
                 }
             },
-            KeyCode::Char('D') =>  {
-                if !popup_open {
-                    self.show_debug_log();
+            Some(TatAction::HideColumn) if !popup_open => self.table.hide_current_column(),
+            Some(TatAction::ShowColumns) if !popup_open => self.table.show_all_columns(),
+            Some(TatAction::ResetColumns) if !popup_open => self.table.reset_columns(),
+            Some(TatAction::MoveColumnLeft) if !popup_open => self.table.move_current_column(true),
+            Some(TatAction::MoveColumnRight) if !popup_open => self.table.move_current_column(false),
+            Some(TatAction::FitColumns) if !popup_open => self.table.toggle_fit_to_content(),
+            Some(TatAction::ToggleFids) if !popup_open => self.table.toggle_show_fids(),
+            Some(TatAction::NarrowColumn) if !popup_open => self.table.resize_current_column(false),
+            Some(TatAction::WidenColumn) if !popup_open => self.table.resize_current_column(true),
+            Some(TatAction::PinColumn) if !popup_open => {
+                let pinned = self.table.toggle_pin_current_column();
+
+                if !pinned {
+                    self.set_feedback("Cannot pin more columns, no room left for scrolling".to_string());
+
+                    return;
                 }
             },
-            KeyCode::Char('?') =>  {
-                if !popup_open {
-                    self.show_help();
-                }
+            Some(TatAction::Search) if !popup_open => self.search_input = Some(TatSearchInput::new(self.input_history.entries(TatInputPurpose::Search))),
+            Some(TatAction::NextMatch) if !popup_open => {
+                self.table.jump_to_match(false);
             },
-            KeyCode::Enter => {
-                match self.current_menu {
-                    TatMenu::MainMenu => {
-                        if !in_table && !popup_open && (in_preview_table || in_layer_list && self.layerlist.layer_index().is_some()) {
-                            self.open_table();
-                        }
-                    },
-                    TatMenu::TableView => {
-                        self.show_full_value_popup();
-                    },
-                }
+            Some(TatAction::PreviousMatch) if !popup_open => {
+                self.table.jump_to_match(true);
+            },
+            Some(TatAction::SetMark) if !popup_open => self.pending_mark = Some(TatMarkCommand::Set),
+            Some(TatAction::JumpToMark) if !popup_open => self.pending_mark = Some(TatMarkCommand::Jump),
+            Some(TatAction::JumpBack) if !popup_open => {
+                self.table.jump_in_list(false);
             },
-            KeyCode::Tab => {
-                if in_main_menu && !popup_open {
-                    self.cycle_section_selection(false);
+            Some(TatAction::JumpForward) if !popup_open => {
+                self.table.jump_in_list(true);
+            },
+            Some(TatAction::ToggleMark) if !popup_open => self.table.toggle_mark(),
+            Some(TatAction::ToggleVisual) if !popup_open => self.table.toggle_visual(),
+            Some(TatAction::ClearSelection) if !popup_open => self.table.clear_selection(),
+            Some(TatAction::GdalLog) if !popup_open => self.show_gdal_log(),
+            Some(TatAction::ToggleMap) if !popup_open => self.toggle_map(),
+            Some(TatAction::CrsDetails) if !popup_open => self.show_crs_popup(),
+            Some(TatAction::Export) if !popup_open => self.open_export_dialog(),
+            Some(TatAction::DebugLog) if !popup_open => self.show_debug_log(),
+            Some(TatAction::Help) if !popup_open => self.show_help(),
+            Some(TatAction::OpenTable) => {
+                if !popup_open && (in_preview_table || in_layer_list && self.layerlist.layer_index().is_some()) {
+                    self.open_table();
                 }
             },
-            KeyCode::BackTab => {
-                if in_main_menu && !popup_open {
-                    self.cycle_section_selection(true);
+            Some(TatAction::ShowValue) => self.show_full_value_popup(),
+            Some(TatAction::NextSection) if !popup_open => self.cycle_section_selection(false),
+            Some(TatAction::PreviousSection) if !popup_open => self.cycle_section_selection(true),
+            Some(TatAction::CommandLine) if !popup_open => self.open_command_line(),
+            _ => {},
+        }
+
+        if self.feedback.is_some() {
+            self.feedback = None;
+        }
+    }
+
+    /// Shows or hides the map panel
+    fn toggle_map(&mut self) {
+        self.show_map = !self.show_map;
+
+        if self.current_menu == TatMenu::TableView {
+            (self.table_area, _) = TatApp::table_view_areas(self.frame_area, self.show_map);
+            self.table.set_rects(self.current_table_rects(false));
+            self.table.on_visible_attributes_changed();
+        }
+    }
+
+    /// Opens the record view for the current feature
+    fn open_record_view(&mut self) {
+        if let Some(schema) = self.table.layer_schema() {
+            self.record_view = Some(TatRecordView::new(schema));
+            self.request_record();
+        }
+    }
+
+    /// Requests the comparison of the marked feature and the current one, or of two marked
+    /// features. Returns false if there is nothing to compare.
+    fn request_comparison(&mut self) -> bool {
+        let schema = match self.table.layer_schema() {
+            Some(schema) => schema,
+            None => return false,
+        };
+
+        // only one or two selected rows can be compared
+        if self.table.selected_count() > 2 {
+            return false;
+        }
+
+        let current = self.table.current_row();
+        let rows = match self.table.selected_rows().as_slice() {
+            [marked] if *marked != current => (*marked, current),
+            [first, second] => (*first, *second),
+            _ => return false,
+        };
+
+        self.ds_request_tx.send(DatasetRequest::Compare(schema.index(), rows.0, rows.1)).unwrap();
+
+        true
+    }
+
+    /// Requests all values of the current feature for the record view
+    fn request_record(&mut self) {
+        if let Some(schema) = self.table.layer_schema() {
+            self.ds_request_tx.send(
+                DatasetRequest::Record(
+                    schema.index(),
+                    self.table.current_row(),
+                )
+            ).unwrap();
+        }
+    }
+
+    /// Opens the dialog for jumping to a shown column of the selected layer
+    fn open_column_picker(&mut self) {
+        if let Some(schema) = self.table.layer_schema() {
+            let columns = self.table.columns().iter()
+                .filter_map(|i| Some((*i, schema.field_name_by_id(*i as i32)?.to_string())))
+                .collect();
+
+            self.column_picker = Some(TatColumnPicker::new(columns));
+        }
+    }
+
+    /// Opens the command line, with the names of the layers and the current layer's columns for
+    /// completion
+    fn open_command_line(&mut self) {
+        let mut names = TatCompletionNames {
+            layers: self.table.layer_schemas().iter().map(|schema| schema.name().to_string()).collect(),
+            ..TatCompletionNames::default()
+        };
+
+        if let Some(schema) = self.table.layer_schema() {
+            names.columns = (0..schema.field_count())
+                .filter_map(|i| Some(schema.field_name_by_id(i as i32)?.to_string()))
+                .collect();
+            names.fields = schema.attribute_fields().iter().map(|field| field.name().to_string()).collect();
+        }
+
+        self.command_line = Some(TatCommandLine::new(self.input_history.entries(TatInputPurpose::Command), names));
+    }
+
+    /// Runs a command given in the command line. Returns an error if the command cannot be run,
+    /// errors from the dataset are shown as feedback once it has responded.
+    fn run_command(&mut self, command: TatCommand) -> std::result::Result<(), String> {
+        let Some(schema) = self.table.layer_schema() else {
+            return Ok(());
+        };
+
+        let layer_index = schema.index();
+
+        match command {
+            TatCommand::GotoRow(row) => {
+                self.table.record_jump();
+                self.table.nav_v(TatNavVertical::Specific(row.clamp(1, i64::MAX as u64) as i64));
+            },
+            TatCommand::GotoFid(fid) => {
+                self.finding_fid = true;
+                self.ds_request_tx.send(DatasetRequest::FindFid(layer_index, fid)).unwrap();
+            },
+            TatCommand::Filter(filter) => {
+                self.ds_request_tx.send(DatasetRequest::SetFilter(layer_index, filter)).unwrap();
+            },
+            TatCommand::Sort(order) => {
+                if let Some(order) = order.as_ref()
+                    && !schema.attribute_fields().iter().any(|field| field.name() == order.field)
+                {
+                    return Err(format!("No field named \"{}\"", order.field));
                 }
+
+                self.ds_request_tx.send(DatasetRequest::Sort(layer_index, order)).unwrap();
             },
-            KeyCode::Char(':') =>  {
-                if in_table {
-                    self.number_input = Some(
-                        TatNumberInput::new(),
-                    )
+            TatCommand::Hide(name) => {
+                let column = (0..schema.field_count())
+                    .find(|i| schema.field_name_by_id(*i as i32) == Some(name.as_str()))
+                    .ok_or(format!("No column named \"{}\"", name))?;
+
+                if !self.table.hide_column(column) {
+                    return Err(format!("Column \"{}\" is already hidden or the only one shown", name));
                 }
             },
-            _ => {},
+            TatCommand::Export => self.open_export_dialog(),
+            TatCommand::Layer(name) => {
+                let position = self.table.layer_schemas().iter().position(|schema| schema.name() == name)
+                    .ok_or(format!("No layer named \"{}\"", name))?;
+
+                self.layerlist.nav(TatNavVertical::Specific(position as i64));
+                self.sync_table_layer();
+            },
+            TatCommand::SetGeometryFormat(format) => {
+                self.ds_request_tx.send(DatasetRequest::SetGeometryFormat(format)).unwrap();
+                self.table.on_visible_attributes_changed();
+            },
         }
 
-        if self.clipboard_feedback.is_some() {
-            self.clipboard_feedback = None;
+        Ok(())
+    }
+
+    /// Opens the dialog for exporting the selected layer
+    fn open_export_dialog(&mut self) {
+        if let Some(schema) = self.table.layer_schema() {
+            self.export_dialog = Some(
+                TatExportDialog::new(
+                    schema.index(),
+                    schema.name(),
+                    self.table.layer_schemas().len(),
+                    self.table.selected_rows(),
+                    self.table.columns().to_vec(),
+                    self.input_history.entries(TatInputPurpose::ExportPath),
+                )
+            );
         }
     }
 
+    /// Opens a pop-up which displays the CRS details of the selected layer and its geometry fields
+    fn show_crs_popup(&mut self) {
+        let schema = match self.table.layer_schema() {
+            Some(schema) => schema,
+            None => return,
+        };
+
+        let heading = |title: String, underline: char| {
+            format!("{}\n{}\n", title, underline.to_string().repeat(title.chars().count()))
+        };
+
+        let mut text = heading(format!("Layer \"{}\"", schema.name()), '=');
+        match schema.crs() {
+            Some(crs) => text.push_str(&crs.details_text()),
+            None => text.push_str("No CRS\n"),
+        }
+
+        for field in schema.geom_fields() {
+            text.push('\n');
+            text.push_str(&heading(format!("Geometry field \"{}\"", field.name()), '-'));
+            match field.crs() {
+                Some(crs) => text.push_str(&crs.details_text()),
+                None => text.push_str("No CRS\n"),
+            }
+        }
+
+        self.modal_popup = Some(
+            TatNavigableParagraph::new(
+                text,
+            ).with_title(format!(" CRS - {} ", schema.name()))
+        );
+    }
+
     /// Opens a pop-up which displays the full value of the selected cell in the table
     fn show_full_value_popup(&mut self) {
-        let value = if let Some(_value) = self.table.selected_value() {
-            _value
-        } else {
-            crate::shared::MISSING_VALUE.to_string()
-        };
+        let column_name = self.table.current_column_name().unwrap_or("UNKNOWN COLUMN").to_string();
+
+        self.show_value_popup(
+            self.table.current_row(),
+            &column_name,
+            self.table.selected_value(),
+            self.table.current_column_nested(),
+        );
+    }
 
+    /// Opens a pop-up which displays a value of a feature. Lists and JSON arrays and objects are
+    /// shown as an expandable tree.
+    fn show_value_popup(&mut self, row: u64, column_name: &str, value: Option<String>, nested: bool) {
         let title = format!(
                 " Feature {} - Value of \"{}\" ",
-                self.table.current_row(),
-                self.table.current_column_name().unwrap_or("UNKNOWN COLUMN"),
+                row,
+                column_name,
             );
 
+        if nested && let Some(tree) = value.as_deref().and_then(|value| TatValueTree::new(title.clone(), value)) {
+            self.value_tree = Some(tree);
+            return;
+        }
+
+        let value = if let Some(_value) = value {
+            _value
+        } else {
+            crate::shared::MISSING_VALUE.to_string()
+        };
+
         self.modal_popup = Some(
             TatNavigableParagraph::new(
                 format!(
@@ -547,7 +1507,7 @@ impl TatApp {
 
     /// Opens the GDAL log in a pop-up in which any direct GDAL output is written
     fn show_gdal_log(&mut self) {
-        let file = match File::open(format!("{}/tat_gdal.log", temp_dir().display())) {
+        let file = match File::open(&crate::config::config().gdal_log) {
             Ok(file) => file,
             Err(e) => {
                 error!("Could not open file: {}", e.to_string());
@@ -572,10 +1532,11 @@ impl TatApp {
 
     /// Shows the help pop-up dialog according to the current menu
     fn show_help(&mut self) {
-        let help_text = match self.current_menu {
-            TatMenu::TableView => crate::shared::HELP_TEXT_TABLE,
-            TatMenu::MainMenu => crate::shared::HELP_TEXT_MAINMENU,
-        }.to_string();
+        let context = match self.current_menu {
+            TatMenu::TableView => TatKeyContext::Table,
+            TatMenu::MainMenu => TatKeyContext::MainMenu,
+        };
+        let help_text = crate::config::config().keys.help_text(context);
 
         self.modal_popup = Some(
             TatNavigableParagraph::new(
@@ -611,15 +1572,13 @@ impl TatApp {
             return;
         }
 
-        if self.clipboard_feedback.is_some() {
+        if self.feedback.is_some() {
             return;
         }
 
         match self.current_menu {
-            TatMenu::TableView => {
-                self.table.reset();
-                self.current_menu = TatMenu::MainMenu;
-            },
+            TatMenu::TableView if self.table.visual_active() => self.table.cancel_visual(),
+            TatMenu::TableView => self.current_menu = TatMenu::MainMenu,
             TatMenu::MainMenu => self.close(),
         }
     }
@@ -636,11 +1595,7 @@ impl TatApp {
                 match self.focused_section {
                     TatMainMenuSectionFocus::LayerList => {
                         self.layerlist.nav(conf);
-
-                        if let Some(lyr_i) = self.layerlist.layer_index() {
-                            self.table.set_layer_index(lyr_i);
-                        }
-                        self.table.reset();
+                        self.sync_table_layer();
                     },
                     TatMainMenuSectionFocus::LayerInfo => {
                         if let Some(para) = self.layerlist.current_layer_info_paragraph() {
@@ -656,6 +1611,13 @@ impl TatApp {
         }
     }
 
+    /// Shows the layer selected in the layer list in the table
+    fn sync_table_layer(&mut self) {
+        if let Some(lyr_i) = self.layerlist.layer_index() {
+            self.table.set_layer_index(lyr_i);
+        }
+    }
+
     /// Delegates a horizontal navigation event to the active widget
     fn delegate_nav_h(&mut self, conf: TatNavHorizontal) {
         if let Some(pop) = &mut self.modal_popup {
@@ -688,6 +1650,18 @@ impl TatApp {
         TatApp::render_title(header_area, frame);
         self.render_dataset_info(dataset_area, frame);
         self.layerlist.render(list_area, frame, matches!(self.focused_section, TatMainMenuSectionFocus::LayerList) && self.modal_popup.is_none());
+        let info_area = if self.show_map {
+            let [info_area, map_area] = Layout::vertical([
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+            ]).areas(info_area);
+
+            self.render_map(map_area, frame, None);
+            info_area
+        } else {
+            info_area
+        };
+
         self.render_layer_info(info_area, frame,  matches!(self.focused_section, TatMainMenuSectionFocus::LayerInfo));
 
         self.table_area = preview_table_area;
@@ -697,12 +1671,12 @@ impl TatApp {
             .title(
                 Line::raw(
                     " Preview Table ",
-                ).bold().underlined().left_aligned().fg(
-                    if matches!(self.focused_section, TatMainMenuSectionFocus::PreviewTable) {crate::shared::palette::DEFAULT.highlighted_fg} else {crate::shared::palette::DEFAULT.default_fg}
+                ).bold().underlined().left_aligned().patch_style(
+                    if matches!(self.focused_section, TatMainMenuSectionFocus::PreviewTable) {crate::shared::palette::current().highlighted_style()} else {crate::shared::palette::current().default_style()}
                     ),
                 )
-            .border_style(if matches!(self.focused_section, TatMainMenuSectionFocus::PreviewTable) {crate::shared::palette::DEFAULT.highlighted_style()} else {crate::shared::palette::DEFAULT.default_style()})
-            .title_bottom(Line::raw(" <Enter> to open full table ").centered())
+            .border_style(if matches!(self.focused_section, TatMainMenuSectionFocus::PreviewTable) {crate::shared::palette::current().highlighted_style()} else {crate::shared::palette::current().default_style()})
+            .title_bottom(Line::raw(format!(" <{}> to open full table ", crate::config::config().keys.hint_key(TatAction::OpenTable))).centered())
             .border_set(BORDER_PREVIEW_TABLE)
             .borders(Borders::BOTTOM | Borders::RIGHT | Borders::TOP);
 
@@ -717,7 +1691,7 @@ impl TatApp {
             Paragraph::new(crate::shared::TITLE_PROGRAM)
                 .bold()
                 .centered()
-                .fg(crate::shared::palette::DEFAULT.default_fg),
+                .fg(crate::shared::palette::current().default_fg),
             area,
         );
     }
@@ -725,15 +1699,15 @@ impl TatApp {
     /// Renders the dataset information
     fn render_dataset_info(&mut self, area: Rect, frame: &mut Frame) {
         let block = Block::new()
-            .fg(crate::shared::palette::DEFAULT.default_fg)
+            .fg(crate::shared::palette::current().default_fg)
             .title_top(Line::raw(crate::shared::TITLE_DATASET_INFO).underlined().bold())
             .borders(Borders::TOP | Borders::LEFT | Borders::RIGHT)
             .border_set(symbols::border::ROUNDED)
-            .title_top(Line::raw(crate::shared::SHOW_HELP).centered());
+            .title_top(Line::raw(crate::shared::show_help_hint()).centered());
 
         frame.render_widget(
             Paragraph::new(self.dataset_info_text.as_str())
-                .fg(crate::shared::palette::DEFAULT.default_fg)
+                .fg(crate::shared::palette::current().default_fg)
                 .block(block),
             area
         );
@@ -742,23 +1716,40 @@ impl TatApp {
 
     /// Returns the table view Menu
     fn render_table_view(&mut self, frame: &mut Frame) {
+        let (table_area, map_area) = TatApp::table_view_areas(frame.area(), self.show_map);
+        self.table_area = table_area;
+
+        if let Some(map_area) = map_area {
+            self.render_map(map_area, frame, Some(self.table.current_row()));
+        }
+
         self.table.set_rects(self.current_table_rects(false));
 
         self.table.render(frame);
     }
 
+    /// Renders the map panel for the selected layer, highlighting the feature on the given row
+    fn render_map(&mut self, area: Rect, frame: &mut Frame, highlighted_row: Option<u64>) {
+        if let Some(layer_index) = self.layerlist.layer_index() {
+            self.map.set_layer_index(layer_index);
+        }
+
+        self.map.set_highlighted_row(highlighted_row);
+        self.map.render(area, frame);
+    }
+
     /// Renders the layer information section
     fn render_layer_info(&mut self, area: Rect, frame: &mut Frame, selected: bool) {
         let border_style = if selected && self.modal_popup.is_none() {
-            crate::shared::palette::DEFAULT.highlighted_style()
+            crate::shared::palette::current().highlighted_style()
         } else {
-            crate::shared::palette::DEFAULT.default_style()
+            crate::shared::palette::current().default_style()
         };
 
 
         let block = Block::bordered()
             .title(Line::raw(crate::shared::TITLE_LAYER_INFO).bold().underlined())
-            .fg(crate::shared::palette::DEFAULT.default_fg)
+            .fg(crate::shared::palette::current().default_fg)
             .border_set(BORDER_LAYER_INFO)
             .border_style(border_style);
 
@@ -804,7 +1795,7 @@ impl TatApp {
                 let scrollbar = Scrollbar::default()
                     .orientation(ScrollbarOrientation::HorizontalBottom)
                     .begin_symbol(Some(DOUBLE_HORIZONTAL.begin))
-                    .style(crate::shared::palette::DEFAULT.highlighted_style())
+                    .style(crate::shared::palette::current().highlighted_style())
                     .end_symbol(Some(DOUBLE_HORIZONTAL.end));
 
                 let scrollbar_area = area.inner(Margin { horizontal: 1, vertical: 1 });
@@ -862,6 +1853,40 @@ impl TatApp {
     }
 
 
+    /// Returns the areas of the layer list, the layer information and the preview table based on
+    /// the last rendered frame
+    fn main_menu_section_areas(&self) -> (Rect, Rect, Rect) {
+        let (_, _, list_area, info_area, preview_table_area) = TatApp::main_menu_areas(&self.frame_area);
+
+        // the map takes the bottom half of the layer information section
+        let info_area = if self.show_map {
+            let [info_area, _] = Layout::vertical([
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+            ]).areas(info_area);
+
+            info_area
+        } else {
+            info_area
+        };
+
+        (list_area, info_area, preview_table_area)
+    }
+
+    /// Returns the areas of the table and the map (if shown) in the table view
+    fn table_view_areas(area: Rect, show_map: bool) -> (Rect, Option<Rect>) {
+        if !show_map {
+            return (area, None);
+        }
+
+        let [table_area, map_area] = Layout::horizontal([
+            Constraint::Fill(3),
+            Constraint::Fill(1),
+        ]).areas(area);
+
+        (table_area, Some(map_area))
+    }
+
     /// Returns the rects for each section in the main menu
     fn main_menu_areas(area: &Rect) -> (Rect, Rect, Rect, Rect, Rect) {
         let [header_area, dataset_area, layer_area] = Layout::vertical([
@@ -881,8 +1906,9 @@ impl TatApp {
         (header_area, dataset_area, list_area, info_area, preview_table_area)
     }
 
-    /// Returns a flat rect in the center of the screen
-    fn number_input_area(area: Rect, percent_x: u16) -> Rect {
+    /// Returns a flat rect in the center of the screen for the command line, the search input,
+    /// the feedback messages and the export progress
+    fn dialog_area(area: Rect, percent_x: u16) -> Rect {
         let vertical = Layout::vertical([Constraint::Length(5)]).flex(Flex::Center);
         let horizontal = Layout::horizontal([Constraint::Percentage(percent_x)]).flex(Flex::Center);
         let [area] = vertical.areas(area);
@@ -901,6 +1927,20 @@ impl TatApp {
         area
     }
 
+    /// Returns the position of the mouse on a scrollbar as a fraction between the start (0.0)
+    /// and the end (1.0). The arrows at both ends are not part of the track.
+    fn scrollbar_fraction(start: u16, length: u16, position: u16) -> f64 {
+        let track_length = length.saturating_sub(2);
+
+        if track_length <= 1 {
+            return 0.0;
+        }
+
+        let offset = position.saturating_sub(start + 1);
+
+        (offset as f64 / (track_length - 1) as f64).clamp(0.0, 1.0)
+    }
+
     /// Returns visible columns and rows of a bordered text area which
     /// may or may not have horizontal and/or vertical scrollbars.
     fn text_area_dimensions(rect: &Rect, max_cols: i64, max_rows: i64) -> (usize, bool, usize, bool) {
@@ -947,12 +1987,12 @@ impl TatApp {
 
 #[cfg(test)]
 mod test {
-    use std::{fs::{remove_file, OpenOptions}, path::Path};
+    use std::{collections::{BTreeSet, HashMap}, fs::{remove_file, OpenOptions}, path::Path};
 
     #[allow(unused)]
     use super::*;
 
-    use crate::fixtures::{basic_app, table_rects, TatTestStructure, TatTestUtils};
+    use crate::{fixtures::{basic_app, table_rects, TatTestStructure, TatTestUtils}, session::TatLayerSession, types::TatSortOrder};
 
     use crossterm::event::KeyEventState;
     use rstest::*;
@@ -966,8 +2006,106 @@ mod test {
         assert_eq!(t.modal_popup, None);
         assert_eq!(t.focused_section, TatMainMenuSectionFocus::LayerList);
         assert!(t.table_area.is_empty());
-        assert_eq!(t.number_input, None);
-        assert_eq!(t.clipboard_feedback, None);
+        assert_eq!(t.command_line, None);
+        assert_eq!(t.feedback, None);
+        test.terminate();
+    }
+
+    #[rstest]
+    fn test_restore_session(basic_app: (TatTestStructure, TatApp), table_rects: TableRects) {
+        let (test, mut t) = basic_app;
+        t.table.set_rects(table_rects);
+
+        let layer = TatLayerSession {
+            name: "nogeom".to_string(),
+            top_row: 20,
+            first_column: 0,
+            selected: (2, 1),
+            columns: vec![3, 0, 1, 2],
+            pinned: 0,
+            column_widths: HashMap::new(),
+            marked_rows: BTreeSet::from([21, 1000]),
+            marked_fids: BTreeSet::new(),
+            filter: None,
+            sort: None,
+        };
+
+        let mut session = TatSession::new("test.gpkg", None);
+        session.layer = Some("nogeom".to_string());
+        session.layers = vec![
+            layer.clone(),
+            // the layer has no such field so the view is ignored
+            TatLayerSession { name: "point".to_string(), columns: vec![100], ..layer.clone() },
+        ];
+
+        t.set_session(session);
+        t.restore_session();
+
+        assert_eq!(t.layerlist.layer_index(), Some(4));
+        assert_eq!(t.table.current_row(), 22);
+        assert_eq!(t.table.columns(), &[3, 0, 1, 2]);
+        assert_eq!(t.table.selected_rows(), vec![21]);
+
+        t.set_layer_index(0);
+        assert_eq!(t.table.columns().len() as u64, t.table.layer_schema().unwrap().field_count());
+
+        test.terminate();
+    }
+
+    #[rstest]
+    fn test_restore_session_filter_sort_and_marks(basic_app: (TatTestStructure, TatApp)) {
+        let (test, mut t) = basic_app;
+        let feature_count = t.table.layer_schemas()[4].feature_count();
+
+        let mut session = TatSession::new("test.gpkg", None);
+        session.layer = Some("nogeom".to_string());
+        session.layers = vec![
+            TatLayerSession {
+                name: "nogeom".to_string(),
+                top_row: 1,
+                first_column: 0,
+                selected: (0, 0),
+                columns: vec![0, 1, 2],
+                pinned: 0,
+                column_widths: HashMap::new(),
+                marked_rows: BTreeSet::new(),
+                // the feature with FID 1 is filtered out
+                marked_fids: BTreeSet::from([1, 6]),
+                filter: Some("i32_field > 900".to_string()),
+                sort: Some(TatSortOrder { field: "i32_field".to_string(), descending: true }),
+            },
+        ];
+
+        t.set_session(session);
+        t.restore_session();
+
+        // the view is restored only once the layer has been filtered and sorted and the rows of
+        // the marked features have been found
+        assert_eq!(t.pending_session_updates, 3);
+        assert_ne!(t.layerlist.layer_index(), Some(4));
+
+        while t.pending_session_updates > 0 {
+            if let TatEvent::Dataset(response) = test.tatevent_rx.recv().unwrap()
+                && matches!(response, DatasetResponse::LayerUpdated(..) | DatasetResponse::FidsFound(..))
+            {
+                t.handle_dataset(response);
+            }
+        }
+
+        assert_eq!(t.layerlist.layer_index(), Some(4));
+        assert_eq!(t.table.columns(), &[0, 1, 2]);
+        // the feature with FID 6 has the second largest value
+        assert_eq!(t.table.selected_rows(), vec![2]);
+
+        let schema = t.table.layer_schema().unwrap();
+        assert!(schema.feature_count() < feature_count);
+        assert_eq!(schema.attribute_filter(), Some("i32_field > 900"));
+        assert_eq!(schema.sort_order().map(|order| order.descending), Some(true));
+
+        let layers = t.table.layer_sessions();
+        assert_eq!(layers[0].filter.as_deref(), Some("i32_field > 900"));
+        assert_eq!(layers[0].sort.as_ref().map(|order| order.field.as_str()), Some("i32_field"));
+
         test.terminate();
     }
 
@@ -1025,6 +2163,76 @@ mod test {
         test.terminate();
     }
 
+    #[rstest]
+    fn test_handle_click(basic_app: (TatTestStructure, TatApp)) {
+        let (test, mut t) = basic_app;
+        let mut terminal = Terminal::new(TestBackend::new(100, 40)).unwrap();
+
+        let click = |t: &mut TatApp, button: MouseButton, column: u16, row: u16| {
+            t.handle_mouse(MouseEvent { kind: MouseEventKind::Down(button), column, row, modifiers: KeyModifiers::NONE });
+            t.handle_mouse(MouseEvent { kind: MouseEventKind::Up(button), column, row, modifiers: KeyModifiers::NONE });
+        };
+
+        t.set_table_area(Rect::new(0, 0, 100, 40));
+        terminal.draw(|frame| {t.render(frame)}).unwrap();
+
+        // the second layer in the list
+        click(&mut t, MouseButton::Left, 2, 8);
+        assert_eq!(t.layerlist.layer_index(), Some(1));
+        assert_eq!(t.focused_section, TatMainMenuSectionFocus::LayerList);
+
+        click(&mut t, MouseButton::Left, 30, 10);
+        assert_eq!(t.focused_section, TatMainMenuSectionFocus::LayerInfo);
+
+        click(&mut t, MouseButton::Left, 60, 10);
+        assert_eq!(t.focused_section, TatMainMenuSectionFocus::PreviewTable);
+
+        // double-clicking the first layer opens it
+        click(&mut t, MouseButton::Left, 2, 7);
+        assert_eq!(t.current_menu, TatMenu::MainMenu);
+        click(&mut t, MouseButton::Left, 2, 7);
+        assert_eq!(t.current_menu, TatMenu::TableView);
+        assert_eq!(t.layerlist.layer_index(), Some(0));
+
+        t.set_table_area(Rect::new(0, 0, 100, 40));
+        terminal.draw(|frame| {t.render(frame)}).unwrap();
+        TatTestUtils::refresh_table_attribute_view(&mut t.table, &test.tatevent_rx);
+
+        // the second feature, the header is on the third row
+        click(&mut t, MouseButton::Left, 12, 4);
+        assert_eq!(t.table.current_row(), 2);
+        assert!(t.modal_popup.is_none());
+
+        click(&mut t, MouseButton::Left, 12, 4);
+        assert!(t.modal_popup.is_some());
+
+        // clicks outside of the pop-up do nothing
+        click(&mut t, MouseButton::Left, 12, 3);
+        assert_eq!(t.table.current_row(), 2);
+        t.close_popup();
+
+        click(&mut t, MouseButton::Right, 12, 3);
+        assert_eq!(t.table.current_row(), 1);
+        assert!(t.feedback.is_some());
+
+        // the first click only dismisses the feedback
+        click(&mut t, MouseButton::Left, 12, 4);
+        assert!(t.feedback.is_none());
+        assert_eq!(t.table.current_row(), 1);
+
+        test.terminate();
+    }
+
+    #[test]
+    fn test_scrollbar_fraction() {
+        assert_eq!(TatApp::scrollbar_fraction(10, 12, 10), 0.0);
+        assert_eq!(TatApp::scrollbar_fraction(10, 12, 11), 0.0);
+        assert_eq!(TatApp::scrollbar_fraction(10, 12, 16), 0.5555555555555556);
+        assert_eq!(TatApp::scrollbar_fraction(10, 12, 20), 1.0);
+        assert_eq!(TatApp::scrollbar_fraction(10, 12, 30), 1.0);
+        assert_eq!(TatApp::scrollbar_fraction(10, 2, 10), 0.0);
+    }
+
     #[rstest]
     fn test_show_full_value_popup(basic_app: (TatTestStructure, TatApp)) {
         let (test, mut t) = basic_app;
@@ -1047,13 +2255,13 @@ mod test {
             let (test, mut t) = basic_app;
 
 
-            let path = format!("{}/tat_gdal.log", temp_dir().display());
+            let path = crate::config::config().gdal_log.clone();
 
             if Path::new(&path).exists() {
                 remove_file(&path).unwrap();
             }
 
-            File::create(format!("{}/tat_gdal.log", temp_dir().display())).unwrap();
+            File::create(&path).unwrap();
 
             match OpenOptions::new().write(true).open(path.clone()) {
                 Ok(mut file) => {
@@ -1094,6 +2302,368 @@ mod test {
         test.terminate();
     }
 
+    #[rstest]
+    fn test_toggle_map(basic_app: (TatTestStructure, TatApp)) {
+        let (test, mut t) = basic_app;
+        assert!(!t.show_map);
+
+        t.handle_key(KeyEvent { code: KeyCode::Char('M'), modifiers: KeyModifiers::NONE, kind: KeyEventKind::Press, state: KeyEventState::NONE });
+        assert!(t.show_map);
+
+        t.show_help();
+        t.handle_key(KeyEvent { code: KeyCode::Char('M'), modifiers: KeyModifiers::NONE, kind: KeyEventKind::Press, state: KeyEventState::NONE });
+        assert!(t.show_map);
+
+        t.close_popup();
+        t.handle_key(KeyEvent { code: KeyCode::Char('M'), modifiers: KeyModifiers::NONE, kind: KeyEventKind::Press, state: KeyEventState::NONE });
+        assert!(!t.show_map);
+
+        // the table is narrowed right away, not only when it is rendered
+        t.frame_area = Rect::new(0, 0, 100, 40);
+        t.table_area = t.frame_area;
+        t.current_menu = TatMenu::TableView;
+        t.toggle_map();
+        assert_eq!(t.table_area, Rect::new(0, 0, 75, 40));
+
+        t.toggle_map();
+        assert_eq!(t.table_area, Rect::new(0, 0, 100, 40));
+
+        test.terminate();
+    }
+
+    #[rstest]
+    fn test_show_crs_popup(basic_app: (TatTestStructure, TatApp)) {
+        let (test, mut t) = basic_app;
+        t.show_crs_popup();
+
+        let popup = t.modal_popup.as_ref().unwrap();
+        assert_eq!(popup.title().unwrap().as_str(), " CRS - point ");
+        assert!(popup.text().starts_with("Layer \"point\"\n=============\n- Name: WGS 84 / Pseudo-Mercator\n- Identifier: EPSG:3857\n- Type: Projected\n"));
+        assert!(popup.text().contains("\nGeometry field \"geom\"\n----------------------\n- Name: WGS 84 / Pseudo-Mercator\n"));
+
+        test.terminate();
+    }
+
+    #[rstest]
+    fn test_export_dialog(basic_app: (TatTestStructure, TatApp)) {
+        let (test, mut t) = basic_app;
+
+        t.handle_key(KeyEvent { code: KeyCode::Char('E'), modifiers: KeyModifiers::NONE, kind: KeyEventKind::Press, state: KeyEventState::NONE });
+        let request = t.export_dialog.as_ref().unwrap().request(false);
+        assert_eq!(request.layers, vec![0]);
+        assert_eq!(request.path, "point.gpkg");
+
+        // keys should go to the dialog instead of e.g. closing the program
+        t.handle_key(KeyEvent { code: KeyCode::Char('q'), modifiers: KeyModifiers::NONE, kind: KeyEventKind::Press, state: KeyEventState::NONE });
+        assert!(!t.quit);
+        assert_eq!(t.export_dialog.as_ref().unwrap().request(false).path, "point.gpkgq");
+
+        t.handle_key(KeyEvent { code: KeyCode::Esc, modifiers: KeyModifiers::NONE, kind: KeyEventKind::Press, state: KeyEventState::NONE });
+        assert!(t.export_dialog.is_none());
+        assert!(!t.quit);
+
+        test.terminate();
+    }
+
+    #[rstest]
+    fn test_copy_feature(basic_app: (TatTestStructure, TatApp)) {
+        let (test, mut t) = basic_app;
+        t.current_menu = TatMenu::TableView;
+
+        t.handle_key(KeyEvent { code: KeyCode::Char('Y'), modifiers: KeyModifiers::NONE, kind: KeyEventKind::Press, state: KeyEventState::NONE });
+        assert!(t.copy_menu.is_some());
+
+        t.handle_key(KeyEvent { code: KeyCode::Char('c'), modifiers: KeyModifiers::NONE, kind: KeyEventKind::Press, state: KeyEventState::NONE });
+        assert!(t.copy_menu.is_none());
+
+        match test.tatevent_rx.recv().unwrap() {
+            TatEvent::Dataset(DatasetResponse::FeaturesSerialized(format, count, result)) => {
+                assert_eq!(format, TatFeatureFormat::Csv);
+                assert_eq!(count, 1);
+                assert_eq!(result, Ok("geom,field\nPOINT (0 0),".to_string()));
+            },
+            _ => panic!(),
+        }
+
+        let key = |code: KeyCode| KeyEvent { code, modifiers: KeyModifiers::NONE, kind: KeyEventKind::Press, state: KeyEventState::NONE };
+        t.handle_key(key(KeyCode::Char('v')));
+        t.handle_key(key(KeyCode::Char('j')));
+        t.handle_key(key(KeyCode::Char('Y')));
+        t.handle_key(key(KeyCode::Char('j')));
+
+        match test.tatevent_rx.recv().unwrap() {
+            TatEvent::Dataset(DatasetResponse::FeaturesSerialized(format, count, result)) => {
+                assert_eq!(format, TatFeatureFormat::Json);
+                assert_eq!(count, 2);
+                assert!(result.unwrap().starts_with('['));
+            },
+            _ => panic!(),
+        }
+
+        t.previous_menu();
+        assert!(!t.table.visual_active());
+        assert_eq!(t.current_menu, TatMenu::TableView);
+
+        test.terminate();
+    }
+
+    #[rstest]
+    fn test_search(basic_app: (TatTestStructure, TatApp)) {
+        let (test, mut t) = basic_app;
+        t.current_menu = TatMenu::TableView;
+
+        let key = |code: KeyCode| KeyEvent { code, modifiers: KeyModifiers::NONE, kind: KeyEventKind::Press, state: KeyEventState::NONE };
+        t.handle_key(key(KeyCode::Char('/')));
+        assert!(t.search_input.is_some());
+
+        t.handle_key(key(KeyCode::Char('n')));
+        t.handle_key(key(KeyCode::Enter));
+        assert!(t.search_input.is_none());
+
+        match test.tatevent_rx.recv().unwrap() {
+            TatEvent::Dataset(DatasetResponse::SearchFinished(result)) => {
+                let results = result.unwrap();
+                assert_eq!(results.query.text, "n");
+                assert_eq!(results.layer_index, 0);
+            },
+            _ => panic!(),
+        }
+
+        test.terminate();
+    }
+
+    #[rstest]
+    fn test_show_nested_value(basic_app: (TatTestStructure, TatApp), table_rects: TableRects) {
+        let (test, mut t) = basic_app;
+        t.table.set_rects(table_rects);
+        t.current_menu = TatMenu::TableView;
+        let key = |code: KeyCode| KeyEvent { code, modifiers: KeyModifiers::NONE, kind: KeyEventKind::Press, state: KeyEventState::NONE };
+
+        TatTestUtils::set_layer_index_and_update(4, &mut t.table, &test.tatevent_rx);
+        t.table.nav_h(TatNavHorizontal::End);
+        TatTestUtils::refresh_table_attribute_view(&t.table, &test.tatevent_rx);
+        assert!(t.table.current_column_nested());
+
+        t.show_full_value_popup();
+        assert!(t.modal_popup.is_none());
+        assert_eq!(t.value_tree.as_ref().unwrap().title(), " Feature 1 - Value of \"json_field\" ");
+
+        t.handle_key(key(KeyCode::Char('e')));
+        t.handle_key(key(KeyCode::Char('q')));
+        assert!(t.value_tree.is_none());
+
+        // other values are shown as text
+        t.table.nav_h(TatNavHorizontal::Home);
+        TatTestUtils::refresh_table_attribute_view(&t.table, &test.tatevent_rx);
+        assert!(!t.table.current_column_nested());
+        t.show_full_value_popup();
+        assert!(t.value_tree.is_none());
+        assert!(t.modal_popup.is_some());
+
+        test.terminate();
+    }
+
+    #[rstest]
+    fn test_command_line(basic_app: (TatTestStructure, TatApp), table_rects: TableRects) {
+        let (test, mut t) = basic_app;
+        t.table.set_rects(table_rects);
+        t.current_menu = TatMenu::TableView;
+
+        let key = |code: KeyCode| KeyEvent { code, modifiers: KeyModifiers::NONE, kind: KeyEventKind::Press, state: KeyEventState::NONE };
+        let run = |t: &mut TatApp, command: &str| {
+            t.handle_key(key(KeyCode::Char(':')));
+            for ch in command.chars() {
+                t.handle_key(key(KeyCode::Char(ch)));
+            }
+            t.handle_key(key(KeyCode::Enter));
+        };
+        let wait_for = |t: &mut TatApp, expected: fn(&DatasetResponse) -> bool| {
+            loop {
+                if let TatEvent::Dataset(response) = test.tatevent_rx.recv().unwrap()
+                    && expected(&response)
+                {
+                    t.handle_dataset(response);
+                    break;
+                }
+            }
+        };
+        let i32_value = |row: u64| -> i64 {
+            test.ds_request_tx.send(DatasetRequest::Record(4, row)).unwrap();
+
+            loop {
+                if let TatEvent::Dataset(DatasetResponse::Record(Some(record))) = test.tatevent_rx.recv().unwrap() {
+                    break record.values[1].as_ref().unwrap().parse().unwrap();
+                }
+            }
+        };
+
+        run(&mut t, "layer nogeom");
+        assert!(t.command_line.is_none());
+        assert_eq!(t.layerlist.layer_index(), Some(4));
+        let feature_count = t.table.layer_schema().unwrap().feature_count();
+
+        run(&mut t, "3");
+        assert_eq!(t.table.current_row(), 3);
+
+        run(&mut t, "goto-fid 999999");
+        wait_for(&mut t, |response| matches!(response, DatasetResponse::FidFound(..)));
+        assert_eq!(t.feedback, Some("No feature with FID 999999 in the layer".to_string()));
+
+        // an invalid command keeps the command line open
+        run(&mut t, "sort no_such_field");
+        assert!(t.command_line.is_some());
+        t.handle_key(key(KeyCode::Esc));
+        assert!(t.command_line.is_none());
+
+        run(&mut t, "filter i32_field > 900");
+        wait_for(&mut t, |response| matches!(response, DatasetResponse::LayerUpdated(..)));
+        let filtered = t.table.layer_schema().unwrap().feature_count();
+        assert!(filtered > 0 && filtered < feature_count);
+        assert_eq!(t.table.current_row(), 1);
+
+        run(&mut t, "sort i32_field desc");
+        wait_for(&mut t, |response| matches!(response, DatasetResponse::LayerUpdated(..)));
+        let values: Vec<i64> = (1..=filtered).map(i32_value).collect();
+        assert!(values.iter().all(|value| *value > 900));
+        assert!(values.windows(2).all(|pair| pair[0] >= pair[1]));
+
+        // the sort order is kept when the filter is cleared
+        run(&mut t, "filter");
+        wait_for(&mut t, |response| matches!(response, DatasetResponse::LayerUpdated(..)));
+        assert_eq!(t.table.layer_schema().unwrap().feature_count(), feature_count);
+        assert!(i32_value(1) >= i32_value(2));
+
+        run(&mut t, "filter no_such_field = 1");
+        wait_for(&mut t, |response| matches!(response, DatasetResponse::LayerUpdated(..)));
+        assert!(t.feedback.as_ref().unwrap().starts_with("ERROR! Could not update the layer"));
+        assert_eq!(t.table.layer_schema().unwrap().feature_count(), feature_count);
+
+        run(&mut t, "hide text_field");
+        assert!(!t.table.columns().contains(&0));
+        assert_eq!(t.input_history.entries(TatInputPurpose::Command).last().map(|command| command.as_str()), Some("hide text_field"));
+
+        test.terminate();
+    }
+
+    #[rstest]
+    fn test_empty_filter(basic_app: (TatTestStructure, TatApp), table_rects: TableRects) {
+        let (test, mut t) = basic_app;
+        t.table.set_rects(table_rects);
+        t.current_menu = TatMenu::TableView;
+
+        let key = |code: KeyCode| KeyEvent { code, modifiers: KeyModifiers::NONE, kind: KeyEventKind::Press, state: KeyEventState::NONE };
+
+        let run = |t: &mut TatApp, command: &str| {
+            t.handle_key(key(KeyCode::Char(':')));
+            for ch in command.chars() {
+                t.handle_key(key(KeyCode::Char(ch)));
+            }
+            t.handle_key(key(KeyCode::Enter));
+        };
+
+        run(&mut t, "layer nogeom");
+        run(&mut t, "filter i32_field > 100000");
+
+        loop {
+            if let TatEvent::Dataset(response) = test.tatevent_rx.recv().unwrap()
+                && matches!(response, DatasetResponse::LayerUpdated(..))
+            {
+                t.handle_dataset(response);
+                break;
+            }
+        }
+        TatTestUtils::wait_attribute_view_update(&test.tatevent_rx);
+        assert_eq!(t.table.layer_schema().unwrap().feature_count(), 0);
+        assert_eq!(t.table.selected_value(), None);
+
+        // neither showing nor copying the value of a missing feature panics
+        t.handle_key(key(KeyCode::Enter));
+        t.handle_key(key(KeyCode::Char('y')));
+
+        test.terminate();
+    }
+
+    #[rstest]
+    fn test_record_view(basic_app: (TatTestStructure, TatApp)) {
+        let (test, mut t) = basic_app;
+
+        t.set_table_area(Rect::new(0, 0, 100, 40));
+        t.table.set_rects(t.current_table_rects(true));
+        t.open_table();
+
+        let key = |code: KeyCode| KeyEvent { code, modifiers: KeyModifiers::NONE, kind: KeyEventKind::Press, state: KeyEventState::NONE };
+        let receive_record = |t: &mut TatApp| {
+            loop {
+                if let TatEvent::Dataset(response) = test.tatevent_rx.recv().unwrap()
+                    && let DatasetResponse::Record(..) = response
+                {
+                    t.handle_dataset(response);
+                    break;
+                }
+            }
+        };
+
+        t.handle_key(key(KeyCode::Char('R')));
+        assert!(t.record_view.is_some());
+        receive_record(&mut t);
+        assert_eq!(t.record_view.as_ref().unwrap().record().unwrap().row, 1);
+
+        t.handle_key(key(KeyCode::Char('l')));
+        receive_record(&mut t);
+        assert_eq!(t.table.current_row(), 2);
+        assert_eq!(t.record_view.as_ref().unwrap().record().unwrap().row, 2);
+
+        t.handle_key(key(KeyCode::Enter));
+        assert!(t.modal_popup.is_some());
+        t.handle_key(key(KeyCode::Char('q')));
+        assert!(t.modal_popup.is_none());
+        assert!(t.record_view.is_some());
+
+        t.handle_key(key(KeyCode::Char('q')));
+        assert!(t.record_view.is_none());
+        assert_eq!(t.current_menu, TatMenu::TableView);
+
+        test.terminate();
+    }
+
+    #[rstest]
+    fn test_compare_features(basic_app: (TatTestStructure, TatApp)) {
+        let (test, mut t) = basic_app;
+
+        t.set_table_area(Rect::new(0, 0, 100, 40));
+        t.table.set_rects(t.current_table_rects(true));
+        t.open_table();
+
+        let key = |code: KeyCode| KeyEvent { code, modifiers: KeyModifiers::NONE, kind: KeyEventKind::Press, state: KeyEventState::NONE };
+
+        t.handle_key(key(KeyCode::Char('X')));
+        assert!(t.feedback.is_some());
+        t.handle_key(key(KeyCode::Char('x')));
+        assert!(t.feedback.is_none());
+
+        t.handle_key(key(KeyCode::Char(' ')));
+        t.handle_key(key(KeyCode::Char('j')));
+        t.handle_key(key(KeyCode::Char('X')));
+        assert!(t.feedback.is_none());
+
+        loop {
+            if let TatEvent::Dataset(response) = test.tatevent_rx.recv().unwrap()
+                && let DatasetResponse::Comparison(..) = response
+            {
+                t.handle_dataset(response);
+                break;
+            }
+        }
+
+        assert_eq!(t.compare_view.as_ref().unwrap().comparison().rows, (1, 2));
+
+        t.handle_key(key(KeyCode::Char('q')));
+        assert!(t.compare_view.is_none());
+        assert_eq!(t.current_menu, TatMenu::TableView);
+
+        test.terminate();
+    }
+
     #[rstest]
     fn test_previous_menu(basic_app: (TatTestStructure, TatApp)) {
         let (test, mut t) = basic_app;
@@ -1288,7 +2858,7 @@ mod test {
     }
 
     #[rstest]
-    fn test_number_input_area() {
+    fn test_dialog_area() {
         let tat_area = Rect {
             x: 0,
             y: 0,
@@ -1296,9 +2866,9 @@ mod test {
             height: 300,
         };
 
-        let ni_area = TatApp::number_input_area(tat_area, 30);
+        let area = TatApp::dialog_area(tat_area, 30);
         assert_eq!(
-            ni_area,
+            area,
             Rect {
                 x: 140,
                 y: 148,
@@ -1489,7 +3059,7 @@ mod test {
     }
 
     #[rstest]
-    fn test_render_open_jump_to_line(basic_app: (TatTestStructure, TatApp)) {
+    fn test_render_open_command_line(basic_app: (TatTestStructure, TatApp)) {
         let (test, mut t) = basic_app;
         let mut terminal = Terminal::new(TestBackend::new(100, 40)).unwrap();
 
@@ -1500,7 +3070,7 @@ mod test {
 
         t.handle_key(KeyEvent { code: KeyCode::Char(':'), modifiers: KeyModifiers::NONE, kind: KeyEventKind::Press, state: KeyEventState::NONE });
         terminal.draw(|frame| {t.render(frame)}).unwrap();
-        assert_snapshot!("open_jump_to_line", terminal.backend());
+        assert_snapshot!("open_command_line", terminal.backend());
 
         test.terminate();
     }
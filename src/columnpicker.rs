@@ -0,0 +1,242 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin, Rect},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListState},
+    Frame,
+};
+
+use crate::textinput::{TatTextInput, TatTextInputResult};
+
+/// Result of handling a key press in the column picker
+#[derive(Debug, PartialEq)]
+pub enum TatColumnPickerResult {
+    RejectedKey,
+    AcceptedKey,
+    Close,
+    /// The index of the chosen column (including the geometry fields)
+    Accept(u64),
+}
+
+/// A column which matches the current filter
+#[derive(Debug, PartialEq)]
+struct TatColumnCandidate {
+    /// Index in the listed columns
+    index: usize,
+    score: i64,
+    /// Character positions of the matched characters in the name
+    positions: Vec<usize>,
+}
+
+/// Dialog for jumping to a column by typing (part of) its name
+#[derive(Debug, PartialEq)]
+pub struct TatColumnPicker {
+    input: TatTextInput,
+    /// The column indices and names
+    columns: Vec<(u64, String)>,
+    candidates: Vec<TatColumnCandidate>,
+    selected: usize,
+}
+
+impl TatColumnPicker {
+    /// Constructs a new picker for the given columns. Initially all columns are listed.
+    pub fn new(columns: Vec<(u64, String)>) -> Self {
+        let mut picker = Self {
+            input: TatTextInput::new(),
+            columns,
+            candidates: vec![],
+            selected: 0,
+        };
+
+        picker.filter();
+
+        picker
+    }
+
+    /// Handles the incoming key code. Typing filters the columns, Up and Down move the selection
+    /// and Enter jumps to the selected column.
+    pub fn key_press(&mut self, key: KeyCode, ctrl_down: bool) -> TatColumnPickerResult {
+        match key {
+            KeyCode::Up => {
+                self.selected = self.selected.saturating_sub(1);
+
+                TatColumnPickerResult::AcceptedKey
+            },
+            KeyCode::Down => {
+                self.selected = (self.selected + 1).min(self.candidates.len().saturating_sub(1));
+
+                TatColumnPickerResult::AcceptedKey
+            },
+            _ => {
+                match self.input.key_press(key, ctrl_down) {
+                    TatTextInputResult::Close => TatColumnPickerResult::Close,
+                    TatTextInputResult::Accept(_) => {
+                        match self.candidates.get(self.selected) {
+                            Some(candidate) => TatColumnPickerResult::Accept(self.columns[candidate.index].0),
+                            None => TatColumnPickerResult::Close,
+                        }
+                    },
+                    TatTextInputResult::AcceptedKey => {
+                        self.filter();
+
+                        TatColumnPickerResult::AcceptedKey
+                    },
+                    TatTextInputResult::RejectedKey => TatColumnPickerResult::RejectedKey,
+                }
+            },
+        }
+    }
+
+    /// Renders the picker in the center of the given area
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let vertical = Layout::vertical([Constraint::Percentage(60)]).flex(Flex::Center);
+        let horizontal = Layout::horizontal([Constraint::Length(50)]).flex(Flex::Center);
+        let [cleared_area] = vertical.areas(area);
+        let [cleared_area] = horizontal.areas(cleared_area);
+        let block_area = cleared_area.inner(Margin { horizontal: 1, vertical: 1 });
+
+        let block = Block::default()
+            .title(Line::raw(" Jump To Column ").bold().underlined().centered())
+            .borders(Borders::ALL)
+            .border_style(crate::shared::palette::current().highlighted_style())
+            .border_type(BorderType::Rounded)
+            .title_bottom(Line::raw(format!(" {}/{} columns, Esc to cancel ", self.candidates.len(), self.columns.len())).centered());
+
+        let inner = block_area.inner(Margin { horizontal: 1, vertical: 1 });
+        let [input_area, list_area] = Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).areas(inner);
+        let [prompt_area, text_area] = Layout::horizontal([Constraint::Length(2), Constraint::Fill(1)]).areas(input_area);
+
+        let items: Vec<Line> = self.candidates.iter().map(|candidate| {
+            let spans: Vec<Span> = self.columns[candidate.index].1.chars().enumerate().map(|(i, ch)| {
+                if candidate.positions.contains(&i) {
+                    Span::raw(ch.to_string()).bold().underlined()
+                } else {
+                    Span::raw(ch.to_string())
+                }
+            }).collect();
+
+            Line::from(spans)
+        }).collect();
+
+        let list = List::new(items)
+            .fg(crate::shared::palette::current().default_fg)
+            .highlight_style(crate::shared::palette::current().selected_style());
+
+        let mut state = ListState::default().with_selected(Some(self.selected));
+
+        frame.render_widget(Clear, cleared_area);
+        frame.render_widget(block, block_area);
+        frame.render_widget(Span::styled(">", crate::shared::palette::current().highlighted_style()), prompt_area);
+        self.input.render(frame, text_area, true);
+        frame.render_stateful_widget(list, list_area, &mut state);
+    }
+
+    /// Updates the candidates based on the current text, best matches first
+    fn filter(&mut self) {
+        let pattern = self.input.text();
+
+        self.candidates = self.columns.iter().enumerate().filter_map(|(index, (_, name))| {
+            let (score, positions) = fuzzy_match(pattern, name)?;
+
+            Some(
+                TatColumnCandidate {
+                    index,
+                    score,
+                    positions,
+                }
+            )
+        }).collect();
+
+        // sorting is stable so equally good matches stay in the display order
+        self.candidates.sort_by_key(|candidate| -candidate.score);
+        self.selected = 0;
+    }
+}
+
+/// Matches the characters of the pattern in order against the candidate, ignoring case. Returns
+/// the score of the match (higher is better) and the positions of the matched characters, or None
+/// if the candidate does not match. Consecutive matches and matches at the start of words are
+/// preferred.
+pub fn fuzzy_match(pattern: &str, candidate: &str) -> Option<(i64, Vec<usize>)> {
+    if pattern.is_empty() {
+        return Some((0, vec![]));
+    }
+
+    let candidate: Vec<char> = candidate.chars().collect();
+    let mut positions = vec![];
+    let mut score = 0;
+    let mut from = 0;
+
+    for pattern_ch in pattern.chars().flat_map(char::to_lowercase) {
+        let offset = candidate[from..].iter().position(|ch| ch.to_lowercase().eq(std::iter::once(pattern_ch)))?;
+        let pos = from + offset;
+
+        score += 1;
+
+        if pos == 0 || matches!(candidate[pos - 1], '_' | '-' | ' ' | '.') {
+            score += 8;
+        }
+
+        if positions.last().is_some_and(|last| last + 1 == pos) {
+            score += 5;
+        } else {
+            score -= offset as i64;
+        }
+
+        positions.push(pos);
+        from = pos + 1;
+    }
+
+    // prefer shorter names, e.g. "id" over "parent_id" when searching for "id"
+    score -= (candidate.len() - positions.len()) as i64 / 4;
+
+    Some((score, positions))
+}
+
+#[cfg(test)]
+mod test {
+    #[allow(unused)]
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert_eq!(fuzzy_match("", "name").map(|(_, positions)| positions), Some(vec![]));
+        assert_eq!(fuzzy_match("nme", "Name").map(|(_, positions)| positions), Some(vec![0, 2, 3]));
+        assert_eq!(fuzzy_match("xyz", "name"), None);
+        assert_eq!(fuzzy_match("emn", "name"), None);
+
+        let score = |pattern: &str, candidate: &str| fuzzy_match(pattern, candidate).unwrap().0;
+        assert!(score("id", "id") > score("id", "parent_id"));
+        assert!(score("tf", "text_field") > score("tf", "textfield"));
+        assert!(score("int", "int_field") > score("int", "point"));
+    }
+
+    #[test]
+    fn test_key_press() {
+        let columns = vec![
+            (0, "geom".to_string()),
+            (1, "text_field".to_string()),
+            (3, "int_field".to_string()),
+            (2, "int64_field".to_string()),
+        ];
+        let mut picker = TatColumnPicker::new(columns);
+
+        assert_eq!(picker.candidates.len(), 4);
+        picker.key_press(KeyCode::Down, false);
+        assert_eq!(picker.key_press(KeyCode::Enter, false), TatColumnPickerResult::Accept(1));
+
+        picker.key_press(KeyCode::Char('i'), false);
+        picker.key_press(KeyCode::Char('6'), false);
+        assert_eq!(picker.candidates.len(), 1);
+        assert_eq!(picker.key_press(KeyCode::Enter, false), TatColumnPickerResult::Accept(2));
+
+        picker.key_press(KeyCode::Char('x'), false);
+        assert!(picker.candidates.is_empty());
+        assert_eq!(picker.key_press(KeyCode::Down, false), TatColumnPickerResult::AcceptedKey);
+        assert_eq!(picker.key_press(KeyCode::Enter, false), TatColumnPickerResult::Close);
+
+        picker.key_press(KeyCode::Backspace, false);
+        assert_eq!(picker.key_press(KeyCode::Esc, false), TatColumnPickerResult::Close);
+    }
+}
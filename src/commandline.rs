@@ -0,0 +1,408 @@
+use std::str::FromStr;
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Layout, Margin, Rect},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear},
+    Frame,
+};
+
+use crate::{config::TatGeometryFormat, textinput::{TatTextInput, TatTextInputResult}, types::TatSortOrder};
+
+/// The names of the commands and their arguments
+pub const COMMANDS: [(&str, &str); 7] = [
+    ("goto-fid", "FID"),
+    ("filter", "[WHERE CLAUSE]"),
+    ("sort", "[FIELD [asc|desc]]"),
+    ("hide", "COLUMN"),
+    ("export", ""),
+    ("layer", "NAME"),
+    ("set", "geomformat=FORMAT"),
+];
+
+/// A command given in the command line
+#[derive(Debug, PartialEq)]
+pub enum TatCommand {
+    /// Jumps to the (1-based) row, given as just a number
+    GotoRow(u64),
+    /// Jumps to the feature with the FID
+    GotoFid(u64),
+    /// Replaces the attribute filter of the layer, None clears it
+    Filter(Option<String>),
+    /// Sorts the features of the layer, None restores their original order
+    Sort(Option<TatSortOrder>),
+    /// Hides the column with the given name
+    Hide(String),
+    /// Opens the export dialog
+    Export,
+    /// Shows the layer with the given name
+    Layer(String),
+    SetGeometryFormat(TatGeometryFormat),
+}
+
+impl FromStr for TatCommand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if !s.is_empty() && s.chars().all(|ch| ch.is_ascii_digit()) {
+            return Ok(TatCommand::GotoRow(s.parse().unwrap_or(u64::MAX)));
+        }
+
+        let (name, args) = match s.split_once(char::is_whitespace) {
+            Some((name, args)) => (name, args.trim()),
+            None => (s, ""),
+        };
+
+        let usage = || {
+            let args = COMMANDS.iter().find(|command| command.0 == name).map_or("", |command| command.1);
+
+            format!("Usage: {} {}", name, args).trim_end().to_string()
+        };
+
+        match name {
+            "goto-fid" => args.parse().map(TatCommand::GotoFid).map_err(|_| usage()),
+            "filter" => Ok(TatCommand::Filter((!args.is_empty()).then(|| args.to_string()))),
+            "sort" if args.is_empty() => Ok(TatCommand::Sort(None)),
+            "sort" => {
+                let (field, descending) = match args.rsplit_once(char::is_whitespace) {
+                    Some((field, direction)) if direction.eq_ignore_ascii_case("asc") => (field.trim(), false),
+                    Some((field, direction)) if direction.eq_ignore_ascii_case("desc") => (field.trim(), true),
+                    _ => (args, false),
+                };
+
+                Ok(TatCommand::Sort(Some(TatSortOrder { field: field.to_string(), descending })))
+            },
+            "hide" if !args.is_empty() => Ok(TatCommand::Hide(args.to_string())),
+            "export" if args.is_empty() => Ok(TatCommand::Export),
+            "layer" if !args.is_empty() => Ok(TatCommand::Layer(args.to_string())),
+            "set" => {
+                match args.split_once('=') {
+                    Some((option, value)) if option.trim() == "geomformat" => {
+                        value.trim().parse()
+                            .map(TatCommand::SetGeometryFormat)
+                            .map_err(|e| format!("Invalid geomformat, {}", e))
+                    },
+                    Some((option, _)) => Err(format!("Unknown option \"{}\"", option.trim())),
+                    None => Err(usage()),
+                }
+            },
+            _ if COMMANDS.iter().any(|command| command.0 == name) => Err(usage()),
+            _ => Err(format!("Unknown command \"{}\"", name)),
+        }
+    }
+}
+
+/// The names which the arguments of the commands are completed from
+#[derive(Debug, Default, PartialEq)]
+pub struct TatCompletionNames {
+    pub layers: Vec<String>,
+    /// The names of the current layer's columns, including the geometry fields
+    pub columns: Vec<String>,
+    /// The names of the current layer's attribute fields
+    pub fields: Vec<String>,
+}
+
+/// Returns the ways to complete the text in the command line, as whole lines. The name of the
+/// command is completed first and then its argument, or the last word of a filter.
+pub fn completions(text: &str, names: &TatCompletionNames) -> Vec<String> {
+    let Some((name, args)) = text.split_once(' ') else {
+        return COMMANDS.iter()
+            .filter(|command| command.0.starts_with(text))
+            .map(|command| if command.1.is_empty() { command.0.to_string() } else { format!("{} ", command.0) })
+            .collect();
+    };
+
+    let args = args.trim_start();
+    let complete = |prefix: &str, partial: &str, candidates: &[String]| -> Vec<String> {
+        let partial = partial.to_lowercase();
+
+        candidates.iter()
+            .filter(|candidate| candidate.to_lowercase().starts_with(&partial))
+            .map(|candidate| format!("{} {}{}", name, prefix, candidate))
+            .collect()
+    };
+
+    match name {
+        "layer" => complete("", args, &names.layers),
+        "hide" => complete("", args, &names.columns),
+        "sort" => {
+            let field = names.fields.iter()
+                .find(|field| args.strip_prefix(field.as_str()).is_some_and(|rest| rest.starts_with(' ')));
+
+            match field {
+                Some(field) => complete(&format!("{} ", field), args[field.len()..].trim_start(), &["asc".to_string(), "desc".to_string()]),
+                None => complete("", args, &names.fields),
+            }
+        },
+        "filter" => {
+            let start = args.rfind(|ch: char| ch.is_whitespace() || "(),=<>!".contains(ch)).map_or(0, |i| i + 1);
+            let fields: Vec<String> = names.fields.iter().map(|field| quoted_identifier(field)).collect();
+
+            complete(&args[..start], &args[start..], &fields)
+        },
+        "set" => {
+            let options: Vec<String> = TatGeometryFormat::ALL.iter().map(|format| format!("geomformat={}", format)).collect();
+
+            complete("", args, &options)
+        },
+        _ => vec![],
+    }
+}
+
+/// Returns the field name as an identifier which can be used in a WHERE clause, i.e. in double
+/// quotes unless it only contains alphanumeric characters and underscores
+fn quoted_identifier(name: &str) -> String {
+    if name.chars().all(|ch| ch.is_alphanumeric() || ch == '_') {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    }
+}
+
+/// Checks that the text is a valid command. A blank text is accepted as it closes the command
+/// line.
+fn validate(text: &str) -> Result<(), String> {
+    if text.trim().is_empty() {
+        return Ok(());
+    }
+
+    text.parse::<TatCommand>().map(|_| ())
+}
+
+/// Result of handling a key press in the command line
+#[derive(Debug, PartialEq)]
+pub enum TatCommandLineResult {
+    RejectedKey,
+    AcceptedKey,
+    Close,
+    Accept(String),
+}
+
+/// Dialog for typing in commands, like the command line in Vim
+#[derive(Debug, PartialEq)]
+pub struct TatCommandLine {
+    input: TatTextInput,
+    names: TatCompletionNames,
+    /// The completions of the typed text and the index of the shown one, while cycling through
+    /// them
+    completions: Option<(Vec<String>, usize)>,
+}
+
+impl TatCommandLine {
+    /// Constructs a new, empty command line with the earlier commands as its history
+    pub fn new(history: Vec<String>, names: TatCompletionNames) -> Self {
+        Self {
+            input: TatTextInput::new().with_history(history).with_validator(validate),
+            names,
+            completions: None,
+        }
+    }
+
+    /// Shows an error after a command could not be run
+    pub fn set_error(&mut self, error: String) {
+        self.input.set_error(error);
+    }
+
+    /// Handles the incoming key code. Tab and Shift+Tab cycle through the completions.
+    pub fn key_press(&mut self, key: KeyCode, ctrl_down: bool) -> TatCommandLineResult {
+        match key {
+            KeyCode::Tab => self.complete(false),
+            KeyCode::BackTab => self.complete(true),
+            // deleting past the start closes the command line like in Vim
+            KeyCode::Backspace if self.input.text().is_empty() => return TatCommandLineResult::Close,
+            _ => {
+                self.completions = None;
+
+                return match self.input.key_press(key, ctrl_down) {
+                    TatTextInputResult::RejectedKey => TatCommandLineResult::RejectedKey,
+                    TatTextInputResult::AcceptedKey => TatCommandLineResult::AcceptedKey,
+                    TatTextInputResult::Close => TatCommandLineResult::Close,
+                    TatTextInputResult::Accept(text) if text.trim().is_empty() => TatCommandLineResult::Close,
+                    TatTextInputResult::Accept(text) => TatCommandLineResult::Accept(text.trim().to_string()),
+                };
+            },
+        }
+
+        TatCommandLineResult::AcceptedKey
+    }
+
+    /// Renders the dialog in the given area
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let block_area = area.inner(Margin { horizontal: 1, vertical: 1 });
+
+        let hint = match self.completions.as_ref() {
+            Some((completions, index)) if completions.len() > 1 => {
+                Line::raw(format!(" Completion {} of {} ", index + 1, completions.len())).centered()
+            },
+            _ => Line::raw(" <Enter to run, Tab to complete, Esc to cancel> ").centered(),
+        };
+
+        let block = Block::default()
+                    .title(Line::raw(" Command ").bold().underlined().centered())
+                    .borders(Borders::ALL)
+                    .border_style(crate::shared::palette::current().highlighted_style())
+                    .border_type(BorderType::Rounded)
+                    .title_bottom(hint);
+
+        let input_area = block_area.inner(Margin { horizontal: 1, vertical: 1 });
+        let [prompt_area, text_area] = Layout::horizontal([Constraint::Length(2), Constraint::Fill(1)]).areas(input_area);
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, block_area);
+        frame.render_widget(Span::styled(":", crate::shared::palette::current().highlighted_style()), prompt_area);
+        self.input.render(frame, text_area, true);
+    }
+
+    /// Replaces the text with the next (or previous) completion. A single completion is
+    /// accepted so that the next Tab continues from it.
+    fn complete(&mut self, backwards: bool) {
+        let (candidates, index) = match self.completions.take() {
+            Some((candidates, index)) if backwards => {
+                let index = index.checked_sub(1).unwrap_or(candidates.len() - 1);
+                (candidates, index)
+            },
+            Some((candidates, index)) => {
+                let index = (index + 1) % candidates.len();
+                (candidates, index)
+            },
+            None => {
+                let candidates = completions(self.input.text(), &self.names);
+                if candidates.is_empty() {
+                    return;
+                }
+
+                let index = if backwards { candidates.len() - 1 } else { 0 };
+                (candidates, index)
+            },
+        };
+
+        self.input.set_text(&candidates[index]);
+
+        if candidates.len() > 1 {
+            self.completions = Some((candidates, index));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[allow(unused)]
+    use super::*;
+
+    fn names() -> TatCompletionNames {
+        TatCompletionNames {
+            layers: vec!["point".to_string(), "polygon".to_string(), "nogeom".to_string()],
+            columns: vec!["geom".to_string(), "field".to_string(), "name".to_string(), "long field".to_string()],
+            fields: vec!["field".to_string(), "name".to_string(), "long field".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let parse = |text: &str| text.parse::<TatCommand>();
+        let sort = |field: &str, descending: bool| TatCommand::Sort(Some(TatSortOrder { field: field.to_string(), descending }));
+
+        assert_eq!(parse("123"), Ok(TatCommand::GotoRow(123)));
+        assert_eq!(parse("99999999999999999999999"), Ok(TatCommand::GotoRow(u64::MAX)));
+        assert_eq!(parse(" goto-fid  5 "), Ok(TatCommand::GotoFid(5)));
+        assert_eq!(parse("goto-fid x"), Err("Usage: goto-fid FID".to_string()));
+        assert_eq!(parse("filter field > 1 AND name = 'a b'"), Ok(TatCommand::Filter(Some("field > 1 AND name = 'a b'".to_string()))));
+        assert_eq!(parse("filter"), Ok(TatCommand::Filter(None)));
+        assert_eq!(parse("sort"), Ok(TatCommand::Sort(None)));
+        assert_eq!(parse("sort name"), Ok(sort("name", false)));
+        assert_eq!(parse("sort long field DESC"), Ok(sort("long field", true)));
+        assert_eq!(parse("sort long field"), Ok(sort("long field", false)));
+        assert_eq!(parse("hide long field"), Ok(TatCommand::Hide("long field".to_string())));
+        assert_eq!(parse("hide"), Err("Usage: hide COLUMN".to_string()));
+        assert_eq!(parse("export"), Ok(TatCommand::Export));
+        assert_eq!(parse("export all"), Err("Usage: export".to_string()));
+        assert_eq!(parse("layer point"), Ok(TatCommand::Layer("point".to_string())));
+        assert_eq!(parse("set geomformat=wkb"), Ok(TatCommand::SetGeometryFormat(TatGeometryFormat::Wkb)));
+        assert_eq!(parse("set geomformat = type"), Ok(TatCommand::SetGeometryFormat(TatGeometryFormat::Type)));
+        assert!(parse("set geomformat=svg").unwrap_err().starts_with("Invalid geomformat, expected one of: wkt"));
+        assert_eq!(parse("set colors=none"), Err("Unknown option \"colors\"".to_string()));
+        assert_eq!(parse("q"), Err("Unknown command \"q\"".to_string()));
+    }
+
+    #[test]
+    fn test_completions() {
+        let names = names();
+
+        assert_eq!(completions("", &names).len(), COMMANDS.len());
+        assert_eq!(completions("ex", &names), vec!["export"]);
+        assert_eq!(completions("go", &names), vec!["goto-fid "]);
+        assert_eq!(completions("layer p", &names), vec!["layer point", "layer polygon"]);
+        assert_eq!(completions("layer  NO", &names), vec!["layer nogeom"]);
+        assert_eq!(completions("hide g", &names), vec!["hide geom"]);
+        assert_eq!(completions("sort g", &names), Vec::<String>::new());
+        assert_eq!(completions("sort long field d", &names), vec!["sort long field desc"]);
+        assert_eq!(completions("sort ", &names).len(), 3);
+        assert_eq!(completions("filter name = 'a' AND (fi", &names), vec!["filter name = 'a' AND (field"]);
+        assert_eq!(completions("filter lo", &names), Vec::<String>::new());
+        assert_eq!(completions("filter \"lo", &names), vec!["filter \"long field\""]);
+        assert_eq!(completions("set geomformat=w", &names), vec!["set geomformat=wkt", "set geomformat=wkb"]);
+        assert_eq!(completions("export ", &names), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_history() {
+        let history = vec!["10".to_string(), "sort name".to_string()];
+        let mut command_line = TatCommandLine::new(history, names());
+        command_line.key_press(KeyCode::Char('h'), false);
+
+        command_line.key_press(KeyCode::Up, false);
+        assert_eq!(command_line.input.text(), "sort name");
+        command_line.key_press(KeyCode::Up, false);
+        command_line.key_press(KeyCode::Up, false);
+        assert_eq!(command_line.input.text(), "10");
+
+        command_line.key_press(KeyCode::Down, false);
+        assert_eq!(command_line.input.text(), "sort name");
+        command_line.key_press(KeyCode::Down, false);
+        assert_eq!(command_line.input.text(), "h");
+        command_line.key_press(KeyCode::Down, false);
+        assert_eq!(command_line.input.text(), "h");
+    }
+
+    #[test]
+    fn test_key_press() {
+        let mut command_line = TatCommandLine::new(vec![], names());
+
+        assert_eq!(command_line.key_press(KeyCode::Enter, false), TatCommandLineResult::Close);
+
+        for ch in "la".chars() {
+            command_line.key_press(KeyCode::Char(ch), false);
+        }
+
+        // a single completion is accepted and the next Tab completes the argument
+        command_line.key_press(KeyCode::Tab, false);
+        assert_eq!(command_line.input.text(), "layer ");
+        command_line.key_press(KeyCode::Tab, false);
+        assert_eq!(command_line.input.text(), "layer point");
+        command_line.key_press(KeyCode::Tab, false);
+        assert_eq!(command_line.input.text(), "layer polygon");
+        command_line.key_press(KeyCode::BackTab, false);
+        command_line.key_press(KeyCode::BackTab, false);
+        assert_eq!(command_line.input.text(), "layer nogeom");
+
+        command_line.set_error("No layer named \"nogeom\"".to_string());
+        assert_eq!(command_line.key_press(KeyCode::Enter, false), TatCommandLineResult::Accept("layer nogeom".to_string()));
+        assert_eq!(command_line.input.error(), None);
+
+        // invalid commands are not accepted
+        command_line.key_press(KeyCode::Char('u'), true);
+        command_line.key_press(KeyCode::Char('q'), false);
+        assert_eq!(command_line.key_press(KeyCode::Enter, false), TatCommandLineResult::AcceptedKey);
+        assert_eq!(command_line.input.error(), Some("Unknown command \"q\""));
+        command_line.key_press(KeyCode::Backspace, false);
+        assert_eq!(command_line.input.error(), None);
+
+        let mut command_line = TatCommandLine::new(vec![], names());
+        assert_eq!(command_line.key_press(KeyCode::Backspace, false), TatCommandLineResult::Close);
+        assert_eq!(command_line.key_press(KeyCode::Esc, false), TatCommandLineResult::Close);
+    }
+}
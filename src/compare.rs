@@ -0,0 +1,374 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use gdal::vector::Geometry;
+use gdal_sys::OGRwkbGeometryType;
+use ratatui::{
+    layout::{Constraint, Margin, Rect},
+    style::Stylize,
+    text::Line,
+    widgets::{Block, BorderType, Borders, Cell, Clear, Row, Table, TableState},
+    Frame,
+};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{config::config, keys::TatAction, layerschema::TatLayerSchema, map::TatMapFeature};
+
+/// The actions which can be used in the comparison view
+const ACTIONS: [TatAction; 5] = [
+    TatAction::PreviousMenu,
+    TatAction::Up,
+    TatAction::Down,
+    TatAction::ScrollTop,
+    TatAction::ScrollBottom,
+];
+
+/// Differences between the geometries of two features
+#[derive(Debug, Clone, PartialEq)]
+pub struct TatGeometryComparison {
+    /// Whether the geometries are topologically equal
+    pub equal: bool,
+    /// The discrete Hausdorff distance between the vertices of the geometries and the other
+    /// geometry, None if either geometry is empty
+    pub hausdorff_distance: Option<f64>,
+    /// The area of the second geometry minus the area of the first one
+    pub area_difference: f64,
+}
+
+impl TatGeometryComparison {
+    /// Compares two geometries
+    pub fn new(a: &Geometry, b: &Geometry) -> Self {
+        let equal = unsafe { gdal_sys::OGR_G_Equals(a.c_geometry(), b.c_geometry()) } != 0;
+
+        let hausdorff_distance = match (
+            TatGeometryComparison::directed_hausdorff_distance(a, b),
+            TatGeometryComparison::directed_hausdorff_distance(b, a),
+        ) {
+            (Some(ab), Some(ba)) => Some(ab.max(ba)),
+            _ => None,
+        };
+
+        Self {
+            equal,
+            hausdorff_distance,
+            area_difference: b.area() - a.area(),
+        }
+    }
+
+    /// Returns the largest distance from a vertex of the first geometry to the second geometry
+    fn directed_hausdorff_distance(from: &Geometry, to: &Geometry) -> Option<f64> {
+        if to.is_empty() {
+            return None;
+        }
+
+        let feature = TatMapFeature::from_geometry(0, from);
+        let mut max_distance: Option<f64> = None;
+
+        for (x, y) in feature.parts().iter().flatten() {
+            let mut point = Geometry::empty(OGRwkbGeometryType::wkbPoint).ok()?;
+            point.set_point_2d(0, (*x, *y));
+
+            let distance = unsafe { gdal_sys::OGR_G_Distance(point.c_geometry(), to.c_geometry()) };
+
+            // GDAL returns a negative distance on errors
+            if distance >= 0.0 {
+                max_distance = Some(max_distance.map_or(distance, |max| max.max(distance)));
+            }
+        }
+
+        max_distance
+    }
+}
+
+/// The values of a single field of two features
+#[derive(Debug, Clone, PartialEq)]
+pub struct TatFieldComparison {
+    pub values: (Option<String>, Option<String>),
+    /// Only set for geometry fields which have a geometry in both features
+    pub geometry: Option<TatGeometryComparison>,
+}
+
+impl TatFieldComparison {
+    /// Returns whether the values of the field differ
+    pub fn differs(&self) -> bool {
+        match self.geometry.as_ref() {
+            Some(geometry) => !geometry.equal,
+            None => self.values.0 != self.values.1,
+        }
+    }
+
+    /// Returns a short description of the difference (if any)
+    pub fn description(&self) -> String {
+        if let Some(geometry) = self.geometry.as_ref() {
+            if geometry.equal {
+                return "equal".to_string();
+            }
+
+            let hausdorff = match geometry.hausdorff_distance {
+                Some(distance) => format!("{distance:.6}"),
+                None => "-".to_string(),
+            };
+
+            return format!("Hausdorff {}, area Δ {:.6}", hausdorff, geometry.area_difference);
+        }
+
+        if self.differs() { "differs".to_string() } else { String::new() }
+    }
+}
+
+/// Two features of the same layer compared field by field
+#[derive(Debug, Clone, PartialEq)]
+pub struct TatComparison {
+    pub layer_index: usize,
+    /// The 1-based rows of the features
+    pub rows: (u64, u64),
+    pub fids: (u64, u64),
+    /// All fields, including the geometry fields
+    pub fields: Vec<TatFieldComparison>,
+}
+
+impl TatComparison {
+    /// Returns the number of fields whose values differ
+    pub fn difference_count(&self) -> usize {
+        self.fields.iter().filter(|field| field.differs()).count()
+    }
+}
+
+/// Result of handling a key press in the comparison view
+#[derive(Debug, PartialEq)]
+pub enum TatCompareViewResult {
+    RejectedKey,
+    AcceptedKey,
+    Close,
+}
+
+/// Shows two features side by side and highlights their differences
+#[derive(Debug)]
+pub struct TatCompareView {
+    comparison: TatComparison,
+    field_names: Vec<String>,
+    /// Whether only the differing fields are listed
+    only_differences: bool,
+    state: TableState,
+}
+
+impl TatCompareView {
+    /// Constructs a new view, the schema has to be the one of the compared layer
+    pub fn new(comparison: TatComparison, schema: &TatLayerSchema) -> Self {
+        let field_names = (0..comparison.fields.len() as i32)
+            .map(|i| schema.field_name_by_id(i).unwrap_or_default().to_string())
+            .collect();
+
+        Self {
+            comparison,
+            field_names,
+            only_differences: false,
+            state: TableState::default().with_selected(Some(0)),
+        }
+    }
+
+    /// Returns the compared features
+    pub fn comparison(&self) -> &TatComparison {
+        &self.comparison
+    }
+
+    /// Handles the incoming key event. The bound keys take precedence over d, which switches
+    /// between showing all fields and only the differences.
+    pub fn key_press(&mut self, key: KeyEvent) -> TatCompareViewResult {
+        let row_count = self.listed_fields().len();
+
+        match (config().keys.view_action(&key, &ACTIONS), key.code) {
+            (Some(TatAction::PreviousMenu), _) => return TatCompareViewResult::Close,
+            (Some(TatAction::Up), _) => self.state.select_previous(),
+            (Some(TatAction::Down), _) => self.state.select_next(),
+            (Some(TatAction::ScrollTop), _) => self.state.select_first(),
+            (Some(TatAction::ScrollBottom), _) => self.state.select(Some(row_count.saturating_sub(1))),
+            (_, KeyCode::Char('d')) => {
+                self.only_differences = !self.only_differences;
+                self.state.select_first();
+            },
+            _ => return TatCompareViewResult::RejectedKey,
+        }
+
+        if let Some(selected) = self.state.selected() {
+            self.state.select(Some(selected.min(row_count.saturating_sub(1))));
+        }
+
+        TatCompareViewResult::AcceptedKey
+    }
+
+    /// Renders the view into the given area
+    pub fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let (row_a, row_b) = self.comparison.rows;
+        let (fid_a, fid_b) = self.comparison.fids;
+
+        let title = format!(
+            " Feature {} (FID {}) vs. Feature {} (FID {}) - {} difference(s) ",
+            row_a, fid_a, row_b, fid_b, self.comparison.difference_count(),
+        );
+
+        let hint = format!(
+            " <{}, d: {}, {}> ",
+            config().keys.hint(&[(&[TatAction::Down, TatAction::Up], "scroll")]),
+            if self.only_differences { "show all fields" } else { "show only differences" },
+            config().keys.hint(&[(&[TatAction::PreviousMenu], "close")]),
+        );
+
+        let block = Block::default()
+            .title(Line::raw(title).bold().underlined().centered())
+            .borders(Borders::ALL)
+            .border_style(crate::shared::palette::current().highlighted_style())
+            .border_type(BorderType::Rounded)
+            .title_bottom(Line::raw(hint).centered());
+
+        let fields = self.listed_fields();
+        let name_width = fields.iter()
+            .map(|i| self.field_names[*i].graphemes(true).count())
+            .max()
+            .unwrap_or(0);
+
+        let header = Row::new([
+            "Field".to_string(),
+            format!("Feature {row_a}"),
+            format!("Feature {row_b}"),
+            "Difference".to_string(),
+        ]).bold().underlined();
+
+        let value_text = |value: &Option<String>| -> String {
+            match value {
+                Some(value) => value.replace('\n', " "),
+                None => crate::shared::MISSING_VALUE.to_string(),
+            }
+        };
+
+        let rows: Vec<Row> = fields.iter().map(|i| {
+            let field = &self.comparison.fields[*i];
+
+            let row = Row::new([
+                Cell::from(self.field_names[*i].as_str()).bold(),
+                Cell::from(value_text(&field.values.0)),
+                Cell::from(value_text(&field.values.1)),
+                Cell::from(field.description()),
+            ]);
+
+            if field.differs() {
+                row.style(crate::shared::palette::current().search_match_style())
+            } else {
+                row
+            }
+        }).collect();
+
+        let widths = [
+            Constraint::Length(name_width.clamp(5, 30) as u16),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Length(36),
+        ];
+
+        let table = Table::new(rows, widths)
+            .header(header)
+            .style(crate::shared::palette::current().default_style())
+            .row_highlight_style(crate::shared::palette::current().selected_style())
+            .column_spacing(2);
+
+        let inner = area.inner(Margin { horizontal: 1, vertical: 1 });
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        frame.render_stateful_widget(table, inner, &mut self.state);
+    }
+
+    /// Returns the indices of the listed fields
+    fn listed_fields(&self) -> Vec<usize> {
+        (0..self.comparison.fields.len())
+            .filter(|i| !self.only_differences || self.comparison.fields[*i].differs())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[allow(unused)]
+    use super::*;
+
+    use crate::{app::TatEvent, dataset::{DatasetRequest, DatasetResponse}, fixtures::{datasets::basic_gpkg, init_table, layer_schema, TatTestStructure}};
+
+    use rstest::*;
+
+    #[test]
+    fn test_geometry_comparison() {
+        let a = Geometry::from_wkt("POLYGON ((0 0, 2 0, 2 2, 0 2, 0 0))").unwrap();
+        let b = Geometry::from_wkt("POLYGON ((0 0, 2 0, 2 2, 0 2, 0 0))").unwrap();
+        let c = Geometry::from_wkt("POLYGON ((0 0, 3 0, 3 2, 0 2, 0 0))").unwrap();
+
+        let same = TatGeometryComparison::new(&a, &b);
+        assert!(same.equal);
+        assert_eq!(same.hausdorff_distance, Some(0.0));
+        assert_eq!(same.area_difference, 0.0);
+
+        let different = TatGeometryComparison::new(&a, &c);
+        assert!(!different.equal);
+        assert_eq!(different.hausdorff_distance, Some(1.0));
+        assert_eq!(different.area_difference, 2.0);
+
+        let empty = Geometry::empty(OGRwkbGeometryType::wkbPolygon).unwrap();
+        assert_eq!(TatGeometryComparison::new(&a, &empty).hausdorff_distance, None);
+    }
+
+    #[rstest]
+    fn test_key_press(layer_schema: TatLayerSchema) {
+        let field = |a: &str, b: &str| TatFieldComparison {
+            values: (Some(a.to_string()), Some(b.to_string())),
+            geometry: None,
+        };
+
+        let comparison = TatComparison {
+            layer_index: 0,
+            rows: (1, 2),
+            fids: (1, 2),
+            fields: vec![field("a", "a"), field("a", "b"), field("1", "1"), field("1", "2"), field("x", "x")],
+        };
+
+        assert_eq!(comparison.difference_count(), 2);
+
+        let mut view = TatCompareView::new(comparison, &layer_schema);
+        assert_eq!(view.listed_fields().len(), 5);
+
+        view.key_press(KeyCode::Char('G').into());
+        assert_eq!(view.state.selected(), Some(4));
+
+        assert_eq!(view.key_press(KeyCode::Char('d').into()), TatCompareViewResult::AcceptedKey);
+        assert_eq!(view.listed_fields(), vec![1, 3]);
+        view.key_press(KeyCode::Char('G').into());
+        view.key_press(KeyCode::Char('j').into());
+        assert_eq!(view.state.selected(), Some(1));
+
+        assert_eq!(view.key_press(KeyCode::Char('x').into()), TatCompareViewResult::RejectedKey);
+        assert_eq!(view.key_press(KeyCode::Char('q').into()), TatCompareViewResult::Close);
+    }
+
+    #[rstest]
+    fn test_compare_features(basic_gpkg: TatTestStructure) {
+        let (test, _) = init_table(basic_gpkg);
+
+        let compare = |row_a: u64, row_b: u64| {
+            test.ds_request_tx.send(DatasetRequest::Compare(4, row_a, row_b)).unwrap();
+
+            loop {
+                if let TatEvent::Dataset(DatasetResponse::Comparison(comparison)) = test.tatevent_rx.recv().unwrap() {
+                    return comparison;
+                }
+            }
+        };
+
+        let comparison = compare(1, 1).unwrap();
+        assert_eq!(comparison.rows, (1, 1));
+        assert_eq!(comparison.difference_count(), 0);
+
+        let comparison = compare(1, 2).unwrap();
+        assert_eq!(comparison.layer_index, 4);
+        assert!(comparison.difference_count() > 0);
+
+        assert_eq!(compare(1, 100000), None);
+
+        test.terminate();
+    }
+}
@@ -0,0 +1,569 @@
+use std::{env::temp_dir, fmt::{Display, Write}, ops::Range, path::PathBuf, str::FromStr, sync::OnceLock};
+
+use ratatui::style::Color;
+use toml_edit::{Document, Item};
+
+use crate::{keys::{TatAction, TatKey, TatKeymap}, shared::{palette, TatPalette}};
+
+static CONFIG: OnceLock<TatConfig> = OnceLock::new();
+
+/// The sections of the configuration file and their keys
+const SECTIONS: [(&str, &[&str]); 7] = [
+    ("defaults", &["allow_untested_drivers", "where", "layers"]),
+    ("drivers", &["allowed"]),
+    ("table", &["geometry_format", "min_column_width", "min_fitted_column_width", "max_fitted_column_width", "column_resize_step"]),
+    ("theme", &["name"]),
+    // the keys are the names of the user-defined themes
+    ("themes", &[]),
+    ("log", &["gdal_log"]),
+    // the keys are the names of the actions
+    ("keys", &[]),
+];
+
+/// The largest column width which can be configured
+const MAX_CONFIGURED_WIDTH: i64 = 1000;
+
+/// How geometries are shown in the table
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TatGeometryFormat {
+    Wkt,
+    GeoJson,
+    /// Only the name of the geometry type e.g. POLYGON
+    Type,
+    /// Well-known binary as hexadecimal
+    Wkb,
+}
+
+impl TatGeometryFormat {
+    pub const ALL: [TatGeometryFormat; 4] = [
+        TatGeometryFormat::Wkt,
+        TatGeometryFormat::GeoJson,
+        TatGeometryFormat::Type,
+        TatGeometryFormat::Wkb,
+    ];
+}
+
+impl Display for TatGeometryFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TatGeometryFormat::Wkt => write!(f, "wkt"),
+            TatGeometryFormat::GeoJson => write!(f, "geojson"),
+            TatGeometryFormat::Type => write!(f, "type"),
+            TatGeometryFormat::Wkb => write!(f, "wkb"),
+        }
+    }
+}
+
+impl FromStr for TatGeometryFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TatGeometryFormat::ALL.into_iter()
+            .find(|format| format.to_string() == s)
+            .ok_or(format!("expected one of: {}", TatGeometryFormat::ALL.map(|format| format.to_string()).join(", ")))
+    }
+}
+
+/// The user's configuration, read from a TOML file. Anything not given in the file keeps its
+/// default value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TatConfig {
+    /// Used when --allow-untested-drivers is not given
+    pub allow_untested_drivers: bool,
+    /// Used when --where is not given
+    pub where_sql: Option<String>,
+    /// Used when --layers is not given
+    pub layers: Option<Vec<String>>,
+    /// The short names of the drivers which can be used to open datasets
+    pub allowed_drivers: Vec<String>,
+    pub geometry_format: TatGeometryFormat,
+    /// The width of columns which are not fitted to their content
+    pub min_column_width: u64,
+    pub min_fitted_column_width: u64,
+    pub max_fitted_column_width: u64,
+    /// How much a column is widened or narrowed at a time
+    pub column_resize_step: u64,
+    /// The name of a built-in or user-defined theme
+    pub theme: String,
+    /// The user-defined themes and their names
+    pub themes: Vec<(String, TatPalette)>,
+    /// Where the messages of GDAL are written
+    pub gdal_log: String,
+    pub keys: TatKeymap,
+}
+
+impl Default for TatConfig {
+    fn default() -> Self {
+        Self {
+            allow_untested_drivers: false,
+            where_sql: None,
+            layers: None,
+            allowed_drivers: [
+                "CSV",
+                "OpenFileGDB",
+                "GeoJSON",
+                "GeoJSONSeq",
+                "GML",
+                "GPKG",
+                "JML",
+                "JSONFG",
+                "MapML",
+                "ODS",
+                "ESRI Shapefile",
+                "MapInfo File",
+                "XLSX",
+            ].map(|driver| driver.to_string()).to_vec(),
+            geometry_format: TatGeometryFormat::Wkt,
+            min_column_width: 30,
+            min_fitted_column_width: 3,
+            max_fitted_column_width: 40,
+            column_resize_step: 2,
+            theme: "dark".to_string(),
+            themes: vec![],
+            gdal_log: format!("{}/tat_gdal.log", temp_dir().display()),
+            keys: TatKeymap::default(),
+        }
+    }
+}
+
+impl TatConfig {
+    /// Reads the configuration from the given file, or from the default location if no file is
+    /// given. A missing file is only an error if it was given explicitly.
+    pub fn load(path: Option<&str>) -> Result<Self, String> {
+        let path = match path {
+            Some(path) => PathBuf::from(path),
+            None => {
+                match default_config_path() {
+                    Some(path) if path.exists() => path,
+                    _ => return Ok(TatConfig::default()),
+                }
+            },
+        };
+
+        let source = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Could not read configuration file \"{}\": {}", path.display(), e))?;
+
+        TatConfig::from_toml(&source)
+            .map_err(|e| format!("Invalid configuration file \"{}\": {}", path.display(), e))
+    }
+
+    /// Parses the configuration from TOML. Unknown sections and keys are errors.
+    pub fn from_toml(source: &str) -> Result<Self, String> {
+        let document = Document::parse(source)
+            .map_err(|e| format!("{}{}", location(source, e.span()), e.message().trim_end()))?;
+
+        let mut config = TatConfig::default();
+
+        for (section_name, section) in document.iter() {
+            let Some((_, keys)) = SECTIONS.iter().find(|(name, _)| *name == section_name) else {
+                return Err(
+                    format!(
+                        "{}unknown section \"{}\", expected one of: {}",
+                        location(source, section.span()),
+                        section_name,
+                        SECTIONS.map(|(name, _)| name).join(", "),
+                    )
+                );
+            };
+
+            let table = section.as_table_like().ok_or(
+                format!("{}\"{}\" should be a table", location(source, section.span()), section_name)
+            )?;
+
+            if section_name == "themes" {
+                for (name, theme) in table.iter() {
+                    config.themes.push((name.to_string(), parse_theme(source, name, theme)?));
+                }
+
+                continue;
+            }
+
+            if section_name == "keys" {
+                for (name, item) in table.iter() {
+                    let value = TatConfigValue { source, path: format!("keys.{name}"), item };
+                    let action = name.parse::<TatAction>().map_err(|e| {
+                        format!("{}{}, see the README for the available actions", location(source, item.span()), e)
+                    })?;
+
+                    config.keys.bind(action, value.key_list()?);
+                }
+
+                continue;
+            }
+
+            for (key, item) in table.iter() {
+                if !keys.contains(&key) {
+                    return Err(
+                        format!(
+                            "{}unknown key \"{}.{}\", expected one of: {}",
+                            location(source, item.span()),
+                            section_name,
+                            key,
+                            keys.join(", "),
+                        )
+                    );
+                }
+
+                let value = TatConfigValue { source, path: format!("{section_name}.{key}"), item };
+
+                match (section_name, key) {
+                    ("defaults", "allow_untested_drivers") => config.allow_untested_drivers = value.bool()?,
+                    ("defaults", "where") => config.where_sql = Some(value.string()?),
+                    ("defaults", "layers") => config.layers = Some(value.string_list()?),
+                    ("drivers", "allowed") => config.allowed_drivers = value.string_list()?,
+                    ("table", "geometry_format") => config.geometry_format = value.parse()?,
+                    ("table", "min_column_width") => config.min_column_width = value.width()?,
+                    ("table", "min_fitted_column_width") => config.min_fitted_column_width = value.width()?,
+                    ("table", "max_fitted_column_width") => config.max_fitted_column_width = value.width()?,
+                    ("table", "column_resize_step") => config.column_resize_step = value.width()?,
+                    ("theme", "name") => config.theme = value.string()?,
+                    ("log", "gdal_log") => config.gdal_log = value.string()?,
+                    _ => unreachable!(),
+                }
+            }
+        }
+
+        if config.min_fitted_column_width > config.max_fitted_column_width {
+            return Err("\"table.min_fitted_column_width\" is larger than \"table.max_fitted_column_width\"".to_string());
+        }
+
+        if let Some((key, action, other_action)) = config.keys.conflict() {
+            return Err(
+                format!(
+                    "key \"{}\" is bound to both \"keys.{}\" and \"keys.{}\"",
+                    key,
+                    action.name(),
+                    other_action.name(),
+                )
+            );
+        }
+
+        if config.theme_palette(&config.theme).is_none() {
+            return Err(
+                format!(
+                    "unknown theme \"{}\", expected one of: {}",
+                    config.theme,
+                    config.theme_names().join(", "),
+                )
+            );
+        }
+
+        Ok(config)
+    }
+
+    /// Returns the palette of the configured theme, or the monochrome palette if colors are
+    /// disabled
+    pub fn palette(&self, no_color: bool) -> TatPalette {
+        if no_color {
+            return palette::MONOCHROME;
+        }
+
+        self.theme_palette(&self.theme).unwrap_or(palette::DEFAULT)
+    }
+
+    /// Returns the palette of a built-in or user-defined theme
+    fn theme_palette(&self, name: &str) -> Option<TatPalette> {
+        palette::built_in(name).or(
+            self.themes.iter().find(|(theme_name, _)| theme_name == name).map(|(_, palette)| *palette)
+        )
+    }
+
+    /// Returns the names of all built-in and user-defined themes
+    fn theme_names(&self) -> Vec<&str> {
+        palette::THEMES.iter().map(|(name, _)| *name)
+            .chain(self.themes.iter().map(|(name, _)| name.as_str()))
+            .collect()
+    }
+
+    /// Returns the configuration as TOML which can be read back with from_toml()
+    pub fn to_toml(&self) -> String {
+        let mut text = String::new();
+
+        writeln!(text, "[defaults]").unwrap();
+        writeln!(text, "allow_untested_drivers = {}", self.allow_untested_drivers).unwrap();
+        match self.where_sql.as_ref() {
+            Some(where_sql) => writeln!(text, "where = {}", toml_string(where_sql)).unwrap(),
+            None => writeln!(text, "# where = \"field_1 = 12\"").unwrap(),
+        }
+        match self.layers.as_ref() {
+            Some(layers) => writeln!(text, "layers = {}", toml_string_list(layers)).unwrap(),
+            None => writeln!(text, "# layers = [\"layer_1\", \"layer_2\"]").unwrap(),
+        }
+
+        writeln!(text, "\n[drivers]").unwrap();
+        writeln!(text, "allowed = {}", toml_string_list(&self.allowed_drivers)).unwrap();
+
+        writeln!(text, "\n[table]").unwrap();
+        writeln!(text, "# one of: {}", TatGeometryFormat::ALL.map(|format| format.to_string()).join(", ")).unwrap();
+        writeln!(text, "geometry_format = {}", toml_string(&self.geometry_format.to_string())).unwrap();
+        writeln!(text, "min_column_width = {}", self.min_column_width).unwrap();
+        writeln!(text, "min_fitted_column_width = {}", self.min_fitted_column_width).unwrap();
+        writeln!(text, "max_fitted_column_width = {}", self.max_fitted_column_width).unwrap();
+        writeln!(text, "column_resize_step = {}", self.column_resize_step).unwrap();
+
+        writeln!(text, "\n[theme]").unwrap();
+        writeln!(text, "# built-in themes: {}", palette::THEMES.map(|(name, _)| name).join(", ")).unwrap();
+        writeln!(text, "name = {}", toml_string(&self.theme)).unwrap();
+
+        for (name, theme) in &self.themes {
+            writeln!(text, "\n[themes.{}]", toml_string(name)).unwrap();
+
+            for color_name in TatPalette::COLOR_NAMES {
+                writeln!(text, "{} = {}", color_name, toml_string(&theme.color(color_name).unwrap().to_string())).unwrap();
+            }
+        }
+
+        writeln!(text, "\n[log]").unwrap();
+        writeln!(text, "gdal_log = {}", toml_string(&self.gdal_log)).unwrap();
+
+        writeln!(text, "\n[keys]").unwrap();
+        writeln!(text, "# single characters, f1-f12, named keys e.g. \"pagedown\", optionally prefixed with \"ctrl+\"").unwrap();
+        for action in TatAction::ALL {
+            let keys: Vec<String> = self.keys.keys(action).iter().map(|key| key.to_string()).collect();
+            writeln!(text, "{} = {}", action.name(), toml_string_list(&keys)).unwrap();
+        }
+
+        text
+    }
+}
+
+/// Returns the configuration in use, the default configuration if none has been set
+pub fn config() -> &'static TatConfig {
+    CONFIG.get_or_init(TatConfig::default)
+}
+
+/// Sets the configuration in use. Has no effect if the configuration has already been used.
+pub fn set_config(config: TatConfig) {
+    let _ = CONFIG.set(config);
+}
+
+/// Returns the path of the configuration file which is read if none is given, i.e.
+/// $XDG_CONFIG_HOME/tat/config.toml or ~/.config/tat/config.toml
+pub fn default_config_path() -> Option<PathBuf> {
+    let config_dir = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+
+    Some(config_dir.join("tat").join("config.toml"))
+}
+
+/// Parses a user-defined theme. The colors which are not given are taken from the base theme,
+/// which is the dark theme by default.
+fn parse_theme(source: &str, name: &str, item: &Item) -> Result<TatPalette, String> {
+    let table = item.as_table_like().ok_or(
+        format!("{}\"themes.{}\" should be a table", location(source, item.span()), name)
+    )?;
+
+    if palette::built_in(name).is_some() {
+        return Err(format!("{}\"{}\" is a built-in theme and cannot be redefined", location(source, item.span()), name));
+    }
+
+    let mut theme = palette::DEFAULT;
+
+    if let Some(base) = table.get("base") {
+        let value = TatConfigValue { source, path: format!("themes.{name}.base"), item: base };
+        let base_name = value.string()?;
+
+        theme = palette::built_in(&base_name).ok_or(
+            value.error(&format!("expected one of: {}", palette::THEMES.map(|(name, _)| name).join(", ")))
+        )?;
+    }
+
+    for (key, item) in table.iter() {
+        if key == "base" {
+            continue;
+        }
+
+        let value = TatConfigValue { source, path: format!("themes.{name}.{key}"), item };
+
+        if !TatPalette::COLOR_NAMES.contains(&key) {
+            return Err(
+                format!(
+                    "{}unknown key \"{}\", expected one of: base, {}",
+                    location(source, item.span()),
+                    value.path,
+                    TatPalette::COLOR_NAMES.join(", "),
+                )
+            );
+        }
+
+        let color = value.string()?.parse::<Color>()
+            .map_err(|_| value.error("should be a color name (e.g. \"blue\"), a hex code (e.g. \"#1e293b\") or an index (0-255)"))?;
+        theme.set_color(key, color);
+    }
+
+    Ok(theme)
+}
+
+/// A value in the configuration file
+struct TatConfigValue<'a> {
+    source: &'a str,
+    /// The section and key of the value e.g. "table.min_column_width"
+    path: String,
+    item: &'a Item,
+}
+
+impl TatConfigValue<'_> {
+    fn bool(&self) -> Result<bool, String> {
+        self.item.as_bool().ok_or(self.error("should be true or false"))
+    }
+
+    fn string(&self) -> Result<String, String> {
+        self.item.as_str().map(|s| s.to_string()).ok_or(self.error("should be a string"))
+    }
+
+    fn string_list(&self) -> Result<Vec<String>, String> {
+        let error = self.error("should be a list of strings");
+        let array = self.item.as_array().ok_or(error.clone())?;
+
+        array.iter()
+            .map(|value| value.as_str().map(|s| s.to_string()).ok_or(error.clone()))
+            .collect()
+    }
+
+    fn key_list(&self) -> Result<Vec<TatKey>, String> {
+        self.string_list()?.iter()
+            .map(|key| key.parse().map_err(|e: String| self.error(&format!("contains an {e}"))))
+            .collect()
+    }
+
+    fn width(&self) -> Result<u64, String> {
+        self.item.as_integer()
+            .filter(|width| (1..=MAX_CONFIGURED_WIDTH).contains(width))
+            .map(|width| width as u64)
+            .ok_or(self.error(&format!("should be an integer between 1 and {}", MAX_CONFIGURED_WIDTH)))
+    }
+
+    fn parse<T: FromStr<Err = String>>(&self) -> Result<T, String> {
+        self.string()?.parse().map_err(|e: String| self.error(&e))
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("{}\"{}\" {}", location(self.source, self.item.span()), self.path, message)
+    }
+}
+
+/// Returns the line of the span as a prefix for error messages
+fn location(source: &str, span: Option<Range<usize>>) -> String {
+    match span {
+        Some(span) => {
+            let line = source[..span.start.min(source.len())].matches('\n').count() + 1;
+            format!("line {}: ", line)
+        },
+        None => String::new(),
+    }
+}
+
+/// Returns the string as a TOML basic string
+fn toml_string(value: &str) -> String {
+    let mut escaped = String::from("\"");
+
+    for ch in value.chars() {
+        match ch {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            ch if ch.is_control() => write!(escaped, "\\u{:04X}", ch as u32).unwrap(),
+            ch => escaped.push(ch),
+        }
+    }
+
+    escaped.push('"');
+    escaped
+}
+
+/// Returns the strings as a TOML array
+fn toml_string_list(values: &[String]) -> String {
+    format!("[{}]", values.iter().map(|value| toml_string(value)).collect::<Vec<String>>().join(", "))
+}
+
+#[cfg(test)]
+mod test {
+    #[allow(unused)]
+    use super::*;
+
+    use crossterm::event::KeyCode;
+
+    #[test]
+    fn test_to_toml() {
+        let config = TatConfig::default();
+        assert_eq!(TatConfig::from_toml(&config.to_toml()), Ok(config.clone()));
+
+        let config = TatConfig {
+            where_sql: Some("name = 'a \"b\"\\\\'".to_string()),
+            layers: Some(vec!["layer_1".to_string(), "layer_2".to_string()]),
+            geometry_format: TatGeometryFormat::GeoJson,
+            ..config
+        };
+        assert_eq!(TatConfig::from_toml(&config.to_toml()), Ok(config));
+    }
+
+    #[test]
+    fn test_from_toml() {
+        let config = TatConfig::from_toml(
+            "[defaults]\nallow_untested_drivers = true\n\n[table]\ngeometry_format = \"type\"\nmin_column_width = 20\n"
+        ).unwrap();
+
+        assert!(config.allow_untested_drivers);
+        assert_eq!(config.geometry_format, TatGeometryFormat::Type);
+        assert_eq!(config.min_column_width, 20);
+        assert_eq!(config.max_fitted_column_width, TatConfig::default().max_fitted_column_width);
+
+        let error = |source: &str| TatConfig::from_toml(source).unwrap_err();
+
+        assert!(error("[tabel]\n").starts_with("line 1: unknown section \"tabel\""));
+        assert!(error("[table]\n\nmin_colum_width = 2\n").starts_with("line 3: unknown key \"table.min_colum_width\""));
+        assert!(error("[table]\nmin_column_width = 0\n").contains("\"table.min_column_width\" should be an integer between 1 and"));
+        assert!(error("[table]\ngeometry_format = \"ewkb\"\n").contains("expected one of: wkt, geojson, type, wkb"));
+        assert!(error("[drivers]\nallowed = [\"GPKG\", 1]\n").contains("\"drivers.allowed\" should be a list of strings"));
+        assert!(error("[table]\nmin_fitted_column_width = 50\n").contains("is larger than"));
+        assert!(error("[table\n").starts_with("line 1: "));
+    }
+
+    #[test]
+    fn test_themes() {
+        assert_eq!(TatConfig::default().palette(false), palette::DEFAULT);
+        assert_eq!(TatConfig::default().palette(true), palette::MONOCHROME);
+
+        let config = TatConfig::from_toml(
+            "[theme]\nname = \"mine\"\n\n[themes.mine]\nbase = \"light\"\nselected_bg = \"#ff0000\"\nselected_fg = \"blue\"\n"
+        ).unwrap();
+
+        let theme = config.palette(false);
+        assert_eq!(theme.selected_bg, Color::Rgb(255, 0, 0));
+        assert_eq!(theme.selected_fg, Color::Blue);
+        assert_eq!(theme.default_fg, palette::LIGHT.default_fg);
+        assert_eq!(TatConfig::from_toml(&config.to_toml()), Ok(config));
+
+        let config = TatConfig::from_toml("[theme]\nname = \"high-contrast\"\n").unwrap();
+        assert_eq!(config.palette(false), palette::HIGH_CONTRAST);
+
+        let error = |source: &str| TatConfig::from_toml(source).unwrap_err();
+
+        assert!(error("[theme]\nname = \"solarized\"\n").contains("unknown theme \"solarized\", expected one of: dark, light"));
+        assert!(error("[themes.mine]\nselected_bg = \"nope\"\n").contains("\"themes.mine.selected_bg\" should be a color"));
+        assert!(error("[themes.mine]\nbackground = \"red\"\n").contains("unknown key \"themes.mine.background\""));
+        assert!(error("[themes.dark]\nselected_bg = \"red\"\n").contains("cannot be redefined"));
+    }
+
+    #[test]
+    fn test_keys() {
+        let config = TatConfig::from_toml("[keys]\ndown = [\"down\", \"n\"]\nnext_match = [\"ctrl+n\"]\nhelp = []\n").unwrap();
+
+        assert_eq!(config.keys.keys(TatAction::Down), [TatKey::new(KeyCode::Down), TatKey::char('n')]);
+        assert_eq!(config.keys.keys(TatAction::NextMatch), [TatKey::ctrl('n')]);
+        assert!(config.keys.keys(TatAction::Help).is_empty());
+        assert_eq!(config.keys.keys(TatAction::Up), TatAction::Up.default_keys());
+        assert_eq!(TatConfig::from_toml(&config.to_toml()), Ok(config));
+
+        let error = |source: &str| TatConfig::from_toml(source).unwrap_err();
+
+        assert!(error("[keys]\njump = [\"j\"]\n").starts_with("line 2: unknown action \"jump\""));
+        assert!(error("[keys]\ndown = [\"pgdn\"]\n").contains("\"keys.down\" contains an unknown key \"pgdn\""));
+        assert!(error("[keys]\ndown = \"j\"\n").contains("\"keys.down\" should be a list of strings"));
+        assert!(error("[keys]\ndown = [\"n\"]\n").contains("key \"n\" is bound to both \"keys.down\" and \"keys.next_match\""));
+    }
+}
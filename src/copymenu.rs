@@ -0,0 +1,142 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin, Rect},
+    style::Stylize,
+    text::Line,
+    widgets::{Block, BorderType, Borders, Clear, List, ListState},
+    Frame,
+};
+
+use crate::{config::config, keys::TatAction, serialize::TatFeatureFormat};
+
+/// Result of handling a key press in the copy menu
+#[derive(Debug, PartialEq)]
+pub enum TatCopyMenuResult {
+    RejectedKey,
+    AcceptedKey,
+    Close,
+    Accept(TatFeatureFormat),
+}
+
+/// Small menu for choosing the format in which whole features are copied to the clipboard
+#[derive(Debug, PartialEq)]
+pub struct TatCopyMenu {
+    selected: usize,
+}
+
+impl TatCopyMenu {
+    /// Constructs a new menu with the first format selected
+    pub fn new() -> Self {
+        Self {
+            selected: 0,
+        }
+    }
+
+    /// Returns the shortcut key for a format
+    pub fn shortcut(format: TatFeatureFormat) -> char {
+        match format {
+            TatFeatureFormat::GeoJson => 'g',
+            TatFeatureFormat::Csv => 'c',
+            TatFeatureFormat::Json => 'j',
+        }
+    }
+
+    /// Handles the incoming key event. A format can either be chosen with its shortcut or by
+    /// moving the selection and pressing Enter. The shortcuts take precedence over the bound keys.
+    pub fn key_press(&mut self, key: KeyEvent) -> TatCopyMenuResult {
+        if let KeyCode::Char(ch) = key.code
+            && !key.modifiers.contains(KeyModifiers::CONTROL)
+            && let Some(format) = TatFeatureFormat::ALL.iter().find(|format| TatCopyMenu::shortcut(**format) == ch)
+        {
+            return TatCopyMenuResult::Accept(*format);
+        }
+
+        let actions = [TatAction::PreviousMenu, TatAction::Up, TatAction::Down];
+
+        match (config().keys.view_action(&key, &actions), key.code) {
+            (Some(TatAction::PreviousMenu), _) => TatCopyMenuResult::Close,
+            (Some(TatAction::Up), _) => {
+                self.selected = self.selected.saturating_sub(1);
+
+                TatCopyMenuResult::AcceptedKey
+            },
+            (Some(TatAction::Down), _) => {
+                self.selected = (self.selected + 1).min(TatFeatureFormat::ALL.len() - 1);
+
+                TatCopyMenuResult::AcceptedKey
+            },
+            (_, KeyCode::Enter) => TatCopyMenuResult::Accept(TatFeatureFormat::ALL[self.selected]),
+            _ => TatCopyMenuResult::RejectedKey,
+        }
+    }
+
+    /// Renders the menu in the center of the given area
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let vertical = Layout::vertical([Constraint::Length(TatFeatureFormat::ALL.len() as u16 + 4)]).flex(Flex::Center);
+        let horizontal = Layout::horizontal([Constraint::Length(40)]).flex(Flex::Center);
+        let [cleared_area] = vertical.areas(area);
+        let [cleared_area] = horizontal.areas(cleared_area);
+        let block_area = cleared_area.inner(Margin { horizontal: 1, vertical: 1 });
+
+        let items: Vec<String> = TatFeatureFormat::ALL.iter().map(|format| {
+            format!(" {}: {}", TatCopyMenu::shortcut(*format), TatCopyMenu::description(*format))
+        }).collect();
+
+        let list = List::new(items)
+            .fg(crate::shared::palette::current().default_fg)
+            .highlight_style(crate::shared::palette::current().selected_style())
+            .block(
+                Block::default()
+                    .title(Line::raw(" Copy Feature As ").bold().underlined().centered())
+                    .borders(Borders::ALL)
+                    .border_style(crate::shared::palette::current().highlighted_style())
+                    .border_type(BorderType::Rounded)
+                    .title_bottom(Line::raw(format!(" <press {} to cancel> ", config().keys.hint_key(TatAction::PreviousMenu))).centered())
+            );
+
+        let mut state = ListState::default().with_selected(Some(self.selected));
+
+        frame.render_widget(Clear, cleared_area);
+        frame.render_stateful_widget(list, block_area, &mut state);
+    }
+
+    /// Returns a description of the format for the menu
+    fn description(format: TatFeatureFormat) -> &'static str {
+        match format {
+            TatFeatureFormat::GeoJson => "GeoJSON Feature",
+            TatFeatureFormat::Csv => "CSV with header",
+            TatFeatureFormat::Json => "JSON object",
+        }
+    }
+}
+
+impl Default for TatCopyMenu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[allow(unused)]
+    use super::*;
+
+    #[test]
+    fn test_key_press() {
+        let mut menu = TatCopyMenu::new();
+
+        assert_eq!(menu.key_press(KeyCode::Char('c').into()), TatCopyMenuResult::Accept(TatFeatureFormat::Csv));
+        assert_eq!(menu.key_press(KeyCode::Char('x').into()), TatCopyMenuResult::RejectedKey);
+        assert_eq!(menu.key_press(KeyCode::Enter.into()), TatCopyMenuResult::Accept(TatFeatureFormat::GeoJson));
+
+        menu.key_press(KeyCode::Down.into());
+        menu.key_press(KeyCode::Down.into());
+        menu.key_press(KeyCode::Down.into());
+        assert_eq!(menu.key_press(KeyCode::Enter.into()), TatCopyMenuResult::Accept(TatFeatureFormat::Json));
+
+        menu.key_press(KeyCode::Char('k').into());
+        assert_eq!(menu.key_press(KeyCode::Enter.into()), TatCopyMenuResult::Accept(TatFeatureFormat::Csv));
+
+        assert_eq!(menu.key_press(KeyCode::Esc.into()), TatCopyMenuResult::Close);
+    }
+}
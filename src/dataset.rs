@@ -1,18 +1,31 @@
 #![allow(unused_imports)]
 use cli_log::*;
 
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Write};
 use std::sync::mpsc::{Receiver, Sender};
 use std::sync::{Arc, Mutex};
 
-use gdal::vector::{field_type_to_name, Feature};
+use gdal::vector::{field_type_to_name, Feature, FieldValue, OGRFieldType};
 use gdal::Dataset;
 use gdal::{vector::{geometry_type_to_name, Layer, LayerAccess}, Metadata};
 use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 use crate::app::TatEvent;
+use crate::compare::{TatComparison, TatFieldComparison, TatGeometryComparison};
+use crate::export::{export_layers, TatExportProgress, TatExportRequest};
+use crate::map::{TatLayerGeometries, TatMapFeature, MAX_MAP_FEATURES};
 use crate::navparagraph::TatNavigableParagraph;
-use crate::{layerschema::TatLayerSchema, layerlist::TatLayerInfo, types::{TatCrs, TatField, TatGeomField}};
+use crate::recordview::TatRecord;
+use crate::search::{TatSearchMatch, TatSearchQuery, TatSearchResults};
+use crate::serialize::{features_to_string, geometry_string, TatFeatureFormat};
+use crate::session::TatSession;
+use crate::config::{config, TatGeometryFormat};
+use crate::info::{TatDatasetReport, TatLayerReport};
+use crate::valuetree::summary;
+use crate::{layerschema::TatLayerSchema, layerlist::TatLayerInfo, types::{TatCrs, TatField, TatGeomField, TatSortOrder}};
 
 /// Used to communicate which chunk of attributes should be transmitted
 #[derive(Debug)]
@@ -20,8 +33,8 @@ pub struct TatAttributeViewRequest {
     pub layer_index: usize,
     pub top_row: u64,
     pub bottom_row: u64,
-    pub first_column: u64,
-    pub last_column: u64,
+    /// The columns (including the geometry fields) in the order in which they are displayed
+    pub columns: Vec<u64>,
     pub total_geom_fields: usize,
 }
 
@@ -30,8 +43,7 @@ impl Display for TatAttributeViewRequest {
         writeln!(f, "layer_index: {}", self.layer_index)?;
         writeln!(f, "top_row: {}", self.top_row)?;
         writeln!(f, "bottom_row: {}", self.bottom_row)?;
-        writeln!(f, "first_column: {}", self.first_column)?;
-        writeln!(f, "last_column: {}", self.last_column)?;
+        writeln!(f, "columns: {:?}", self.columns)?;
         writeln!(f, "layer_index: {}", self.layer_index)?;
 
         Ok(())
@@ -39,7 +51,17 @@ impl Display for TatAttributeViewRequest {
 }
 
  // TODO: see if you can make this &str, and also not Vec<Vec
-pub type TatAttributeView = Vec<Vec<Option<String>>>;
+/// The attributes of the currently visible features
+#[derive(Debug, Default)]
+pub struct TatAttributeView {
+    /// The values of the requested columns for each visible row
+    pub rows: Vec<Vec<Option<String>>>,
+    /// The FIDs of the visible rows
+    pub fids: Vec<u64>,
+    /// The summaries of the list and JSON values by their row and column in rows, they are shown
+    /// in the table instead of the values
+    pub summaries: HashMap<(usize, usize), String>,
+}
 
 #[derive(Debug)]
 pub enum DatasetRequest {
@@ -50,6 +72,34 @@ pub enum DatasetRequest {
     GetAttributeView,
     UpdateAttributeView(TatAttributeViewRequest),
     DatasetInfo,
+    /// Collects the information about the dataset and its layers
+    DatasetReport,
+    LayerGeometries(usize),
+    /// Reads the geometry of the feature on the given (1-based) row of a layer for highlighting
+    /// it on the map
+    MapFeature(usize, u64),
+    Export(TatExportRequest),
+    /// Serializes the features on the given (1-based) rows of a layer
+    SerializeFeatures(usize, Vec<u64>, TatFeatureFormat),
+    /// Searches all values of a layer
+    Search(usize, TatSearchQuery),
+    /// Finds the (1-based) row of the feature with the given FID in a layer
+    FindFid(usize, u64),
+    /// Finds the (1-based) rows of the features with the given FIDs in a layer
+    FindFids(usize, Vec<u64>),
+    /// Reads all values of the feature on the given (1-based) row of a layer
+    Record(usize, u64),
+    /// Compares the features on the given (1-based) rows of a layer
+    Compare(usize, u64, u64),
+    /// Sets the attribute filter of a layer, replacing the one given with --where. None clears
+    /// the filter.
+    SetFilter(usize, Option<String>),
+    /// Sets the order of a layer's features, None restores their original order
+    Sort(usize, Option<TatSortOrder>),
+    /// Sets how geometries are shown in the attributes which are read after this
+    SetGeometryFormat(TatGeometryFormat),
+    /// Stores the session with the FIDs of the features on the marked rows of its layers
+    SaveSession(TatSession),
     Terminate,
 }
 
@@ -60,13 +110,38 @@ pub enum DatasetResponse {
     AttributeView(Arc<Mutex<TatAttributeView>>),
     AttributeViewUpdated,
     DatasetInfo(String),
+    DatasetReport(TatDatasetReport),
     LayersBuilt,
+    LayerGeometries(TatLayerGeometries),
+    /// The index of a layer and the highlighted feature, None if the row does not exist or the
+    /// feature has no geometry
+    MapFeature(usize, Option<TatMapFeature>),
+    ExportProgress(TatExportProgress),
+    ExportFinished(Result<u64, String>),
+    /// The format, the number of features and the serialized features
+    FeaturesSerialized(TatFeatureFormat, usize, Result<String, String>),
+    SearchFinished(Result<TatSearchResults, String>),
+    /// The requested FID and its row, None if the feature is not in the layer or was filtered
+    /// out
+    FidFound(u64, Option<u64>),
+    /// The index of a layer and the rows of the requested FIDs in ascending order. FIDs which
+    /// are not in the layer or were filtered out are left out.
+    FidsFound(usize, Vec<u64>),
+    /// None if the row does not exist
+    Record(Option<TatRecord>),
+    /// None if either row does not exist
+    Comparison(Option<TatComparison>),
+    /// The index of a layer which was filtered or sorted and its updated schema
+    LayerUpdated(usize, Result<Box<TatLayerSchema>, String>),
     InvalidDataset,
     DatasetCreated,
 }
 
 type TatFidCache = Vec<u64>;
 
+/// How many features are sampled when estimating the widths of the columns
+const CONTENT_WIDTH_SAMPLE_SIZE: usize = 100;
+
 /// Struct for handling interfacing with GDAL in a separate thread
 pub struct TatDataset<'layers> {
     gdal_ds: Dataset,
@@ -76,6 +151,11 @@ pub struct TatDataset<'layers> {
     where_clause: Option<String>,
     layers: Vec<(Layer<'layers>, TatFidCache)>,
     attribute_view: Arc<Mutex<TatAttributeView>>,
+    geometry_format: TatGeometryFormat,
+    /// Attribute filters which replace the --where clause, by layer index
+    attribute_filters: HashMap<usize, Option<String>>,
+    /// The orders of the sorted layers, by layer index
+    sort_orders: HashMap<usize, TatSortOrder>,
 }
 
 impl<'layers> TatDataset<'layers> {
@@ -88,24 +168,44 @@ impl<'layers> TatDataset<'layers> {
         where_clause: Option<String>,
         layer_filter: Option<Vec<String>>,
     ) -> Option<Self> {
+        let ds = match TatDataset::open_gdal_dataset(&uri, all_drivers) {
+            Ok(ds) => ds,
+            Err(e) => {
+                println!("{}", e);
+                println!();
+
+                return None;
+            },
+        };
+
+        response_tx.send(TatEvent::Dataset(
+            DatasetResponse::DatasetCreated,
+        )).unwrap();
+
+        Some(
+            Self {
+                gdal_ds: ds,
+                response_tx,
+                request_rx,
+                where_clause, // TODO: are these still needed as members?
+                layer_filter,
+                layers: vec![],
+                attribute_view: Arc::new(Mutex::new(TatAttributeView::default())),
+                geometry_format: config().geometry_format,
+                attribute_filters: HashMap::new(),
+                sort_orders: HashMap::new(),
+            }
+        )
+    }
+
+    /// Opens the dataset for reading its vector layers. Unless all drivers are allowed, only the
+    /// drivers allowed in the configuration are tried. Returns the message to show if the dataset
+    /// could not be opened.
+    pub fn open_gdal_dataset(uri: &str, all_drivers: bool) -> Result<Dataset, String> {
         // deal with vectors only at least for now
         let flags = gdal::GdalOpenFlags::GDAL_OF_VECTOR | gdal::GdalOpenFlags::GDAL_OF_READONLY;
 
-        let allowed_drivers = vec![
-            "CSV",
-            "OpenFileGDB",
-            "GeoJSON",
-            "GeoJSONSeq",
-            "GML",
-            "GPKG",
-            "JML",
-            "JSONFG",
-            "MapML",
-            "ODS",
-            "ESRI Shapefile",
-            "MapInfo File",
-            "XLSX",
-        ];
+        let allowed_drivers: Vec<&str> = config().allowed_drivers.iter().map(|driver| driver.as_str()).collect();
 
         let options = gdal::DatasetOptions {
             open_flags: flags,
@@ -114,15 +214,13 @@ impl<'layers> TatDataset<'layers> {
             sibling_files: None,
         };
 
-        let ds = match gdal::Dataset::open_ex(uri, options) {
-            Ok(ds) => ds,
+        match gdal::Dataset::open_ex(uri, options) {
+            Ok(ds) => Ok(ds),
             Err(error) => {
                 match error {
                     gdal::errors::GdalError::NullPointer { method_name: _, msg } => {
                         if msg.is_empty() {
-                            println!("ERROR! Could not open dataset.");
-                            println!();
-                            return None;
+                            return Err("ERROR! Could not open dataset.".to_string());
                         }
 
                         let mut display_string = msg.clone();
@@ -141,36 +239,14 @@ impl<'layers> TatDataset<'layers> {
                             let squished: String = graph.into_iter().take(max_length as usize).collect();
                             display_string = format!("{}…", squished);
                         } 
-                        println!("ERROR! Could not open dataset. GDAL message:\n{}", display_string);
-                        println!();
-
-                        return None;
+                        Err(format!("ERROR! Could not open dataset. GDAL message:\n{}", display_string))
                     }
                     _ => {
-                        println!("ERROR! Could not open dataset:\n{}", error.to_string());
-                        println!();
-
-                        return None;
+                        Err(format!("ERROR! Could not open dataset:\n{}", error))
                     }
                 }
             },
-        };
-
-        response_tx.send(TatEvent::Dataset(
-            DatasetResponse::DatasetCreated,
-        )).unwrap();
-
-        Some(
-            Self {
-                gdal_ds: ds,
-                response_tx,
-                request_rx,
-                where_clause, // TODO: are these still needed as members?
-                layer_filter,
-                layers: vec![],
-                attribute_view: Arc::new(Mutex::new(vec![])),
-            }
-        )
+        }
     }
 
     /// Convenience function for sending a TatEvent::Dataset response
@@ -195,7 +271,7 @@ impl<'layers> TatDataset<'layers> {
                                 (
                                     layer.0.name().to_string(),
                                     TatNavigableParagraph::new(
-                                        self.layer_info_text(i, &layer.0)
+                                        TatDataset::layer_info_text(&self.schema_from_gdal_layer(i, &layer.0))
                                     )
                                 )
 
@@ -208,10 +284,7 @@ impl<'layers> TatDataset<'layers> {
                             );
                         },
                         DatasetRequest::LayerSchemas => {
-                            let schemas = self.layers.iter().enumerate().map(|(i, layer)| {
-                                self.schema_from_gdal_layer(i, &layer.0)
-
-                            }).collect();
+                            let schemas = (0..self.layers.len()).map(|i| self.layer_schema(i)).collect();
 
                             self.send_response(
                                 DatasetResponse::LayerSchemas(
@@ -221,20 +294,32 @@ impl<'layers> TatDataset<'layers> {
                         },
                         DatasetRequest::UpdateAttributeView(request) => {
                             let mut v = self.attribute_view.lock().unwrap();
-                            v.clear();
+                            v.rows.clear();
+                            v.fids.clear();
+                            v.summaries.clear();
 
                             let layer = self.layers.get_mut(request.layer_index).unwrap();
+                            let nested = TatDataset::nested_columns(&layer.0);
 
                             for _row in request.top_row..=request.bottom_row {
                                 if let Some(fid) = layer.1.get(_row as usize - 1) {
                                     let feature = layer.0.feature(*fid).unwrap();
                                     let mut row = vec![];
-                                    for current_column in request.first_column..request.last_column {
-                                        let value = TatDataset::get_attribute_from_feature(&feature, current_column as i32, request.total_geom_fields);
+                                    for (j, current_column) in request.columns.iter().enumerate() {
+                                        let value = TatDataset::get_attribute_from_feature(&feature, *current_column as i32, request.total_geom_fields, self.geometry_format);
+
+                                        if nested.get(*current_column as usize).is_some_and(|nested| *nested)
+                                            && let Some(value) = value.as_deref()
+                                        {
+                                            let i = v.rows.len();
+                                            v.summaries.insert((i, j), summary(value));
+                                        }
+
                                         row.push(value);
                                     }
 
-                                    v.push(row);
+                                    v.rows.push(row);
+                                    v.fids.push(*fid);
                                 }
                             }
 
@@ -254,6 +339,15 @@ impl<'layers> TatDataset<'layers> {
                                 )
                             );
                         },
+                        DatasetRequest::DatasetReport => {
+                            let report = self.report();
+
+                            self.send_response(
+                                DatasetResponse::DatasetReport(
+                                    report,
+                                )
+                            );
+                        },
                         DatasetRequest::BuildLayers => {
                             for mut layer in self.gdal_ds.layers() {
                                 if let Some(lf) = self.layer_filter.as_ref() {
@@ -266,14 +360,7 @@ impl<'layers> TatDataset<'layers> {
                                     layer.set_attribute_filter(wc.as_str()).unwrap();
                                 }
 
-                                let fid_cache = layer.features()
-                                    .map(|feature| {
-                                        if let Some(fid) = feature.fid() {
-                                            return fid;
-                                        } else {
-                                            panic!();
-                                        }
-                                    }).collect();
+                                let fid_cache = TatDataset::read_fids(&mut layer, None).unwrap();
 
                                 self.layers.push((layer, fid_cache));
                             }
@@ -289,6 +376,145 @@ impl<'layers> TatDataset<'layers> {
                                 )
                             );
                         },
+                        DatasetRequest::LayerGeometries(layer_index) => {
+                            let geometries = self.layer_geometries(layer_index);
+
+                            self.send_response(
+                                DatasetResponse::LayerGeometries(
+                                    geometries,
+                                )
+                            );
+                        },
+                        DatasetRequest::MapFeature(layer_index, row) => {
+                            let feature = self.map_feature(layer_index, row);
+
+                            self.send_response(
+                                DatasetResponse::MapFeature(
+                                    layer_index,
+                                    feature,
+                                )
+                            );
+                        },
+                        DatasetRequest::Export(request) => {
+                            let result = self.export(&request);
+
+                            self.send_response(
+                                DatasetResponse::ExportFinished(
+                                    result,
+                                )
+                            );
+                        },
+                        DatasetRequest::SerializeFeatures(layer_index, rows, format) => {
+                            let (layer, fid_cache) = self.layers.get(layer_index).unwrap();
+                            let fids = TatDataset::fids_for_rows(fid_cache, &rows);
+
+                            self.send_response(
+                                DatasetResponse::FeaturesSerialized(
+                                    format,
+                                    fids.len(),
+                                    features_to_string(layer, &fids, format),
+                                )
+                            );
+                        },
+                        DatasetRequest::Search(layer_index, query) => {
+                            let result = self.search(layer_index, query);
+
+                            self.send_response(
+                                DatasetResponse::SearchFinished(
+                                    result,
+                                )
+                            );
+                        },
+                        DatasetRequest::FindFid(layer_index, fid) => {
+                            let (_, fid_cache) = self.layers.get(layer_index).unwrap();
+                            let row = fid_cache.iter().position(|f| *f == fid).map(|i| i as u64 + 1);
+
+                            self.send_response(
+                                DatasetResponse::FidFound(
+                                    fid,
+                                    row,
+                                )
+                            );
+                        },
+                        DatasetRequest::FindFids(layer_index, fids) => {
+                            let (_, fid_cache) = self.layers.get(layer_index).unwrap();
+                            let rows = TatDataset::rows_for_fids(fid_cache, &fids);
+
+                            self.send_response(
+                                DatasetResponse::FidsFound(
+                                    layer_index,
+                                    rows,
+                                )
+                            );
+                        },
+                        DatasetRequest::Record(layer_index, row) => {
+                            let record = self.record(layer_index, row);
+
+                            self.send_response(
+                                DatasetResponse::Record(
+                                    record,
+                                )
+                            );
+                        },
+                        DatasetRequest::Compare(layer_index, row_a, row_b) => {
+                            let comparison = self.compare(layer_index, row_a, row_b);
+
+                            self.send_response(
+                                DatasetResponse::Comparison(
+                                    comparison,
+                                )
+                            );
+                        },
+                        DatasetRequest::SetFilter(layer_index, filter) => {
+                            let previous = self.attribute_filter(layer_index);
+                            let order = self.sort_orders.get(&layer_index);
+                            let result = TatDataset::set_filter(&mut self.layers[layer_index], filter.as_deref(), previous.as_deref(), order);
+
+                            if result.is_ok() {
+                                self.attribute_filters.insert(layer_index, filter);
+                            }
+
+                            self.send_response(
+                                DatasetResponse::LayerUpdated(
+                                    layer_index,
+                                    result.map(|_| Box::new(self.layer_schema(layer_index))),
+                                )
+                            );
+                        },
+                        DatasetRequest::Sort(layer_index, order) => {
+                            let result = TatDataset::sort(&mut self.layers[layer_index], order.as_ref());
+
+                            if result.is_ok() {
+                                match order {
+                                    Some(order) => self.sort_orders.insert(layer_index, order),
+                                    None => self.sort_orders.remove(&layer_index),
+                                };
+                            }
+
+                            self.send_response(
+                                DatasetResponse::LayerUpdated(
+                                    layer_index,
+                                    result.map(|_| Box::new(self.layer_schema(layer_index))),
+                                )
+                            );
+                        },
+                        DatasetRequest::SetGeometryFormat(format) => {
+                            self.geometry_format = format;
+                        },
+                        DatasetRequest::SaveSession(mut session) => {
+                            for layer_session in session.layers.iter_mut() {
+                                let Some((_, fid_cache)) = self.layers.iter().find(|(layer, _)| layer.name() == layer_session.name) else {
+                                    continue;
+                                };
+
+                                let rows: Vec<u64> = layer_session.marked_rows.iter().copied().collect();
+                                layer_session.marked_fids = TatDataset::fids_for_rows(fid_cache, &rows).into_iter().collect();
+                            }
+
+                            if let Err(e) = session.save() {
+                                error!("Could not save the session: {}", e);
+                            }
+                        },
                         DatasetRequest::Terminate => {
                             break;
                         },
@@ -303,11 +529,7 @@ impl<'layers> TatDataset<'layers> {
 
     /// Returns the coordinate reference system of the given layer as a TatCrs
     pub fn crs_from_layer(layer: &Layer) -> Option<TatCrs> {
-        if let Some(sref) = layer.spatial_ref() {
-            return TatCrs::from_spatial_ref(&sref);
-        }
-
-        None
+        layer.spatial_ref().map(|sref| TatCrs::from_spatial_ref(&sref))
     }
 
     /// Returns all geometry field found in the given layer
@@ -321,7 +543,7 @@ impl<'layers> TatDataset<'layers> {
             };
 
             let crs = match &field.spatial_ref() {
-                Ok(sref) => TatCrs::from_spatial_ref(sref),
+                Ok(sref) => Some(TatCrs::from_spatial_ref(sref)),
                 Err(_) => None,
             };
 
@@ -339,11 +561,17 @@ impl<'layers> TatDataset<'layers> {
     /// Return all the attribute fields in the given layer
     pub fn attribute_fields_from_layer(layer: &Layer) -> Vec<TatField> {
         let mut fields: Vec<TatField> = vec![];
-        for field in layer.defn().fields() {
+        for (i, field) in layer.defn().fields().enumerate() {
+            // the subtype (e.g. JSON) is not exposed by the gdal crate
+            let subtype = unsafe {
+                gdal_sys::OGR_Fld_GetSubType(gdal_sys::OGR_FD_GetFieldDefn(layer.defn().c_defn(), i as i32))
+            };
+
             fields.push(
                 TatField::new(
                     field.name(),
                     field.field_type(),
+                    subtype,
                 )
             );
         }
@@ -351,32 +579,390 @@ impl<'layers> TatDataset<'layers> {
         fields
     }
 
-    /// Returns an attribute from a feature
-    fn get_attribute_from_feature(f: &Feature, field_idx: i32, total_geom_fields: usize) -> Option<String> {
+    /// Returns an attribute from a feature, geometries in the given format
+    fn get_attribute_from_feature(f: &Feature, field_idx: i32, total_geom_fields: usize, geometry_format: TatGeometryFormat) -> Option<String> {
         if total_geom_fields == 0 {
-            return f.field_as_string(field_idx as usize)
-                .unwrap_or(None);
+            return TatDataset::field_as_string(f, field_idx as usize);
         }
 
         if field_idx < total_geom_fields as i32 {
-            let res = f.geometry_by_index(field_idx as usize);
-            if res.is_err() {
-                return None;
+            return geometry_string(f, field_idx as usize, geometry_format);
+        } else {
+            let attribute_field_idx = field_idx - total_geom_fields as i32;
+            return TatDataset::field_as_string(f, attribute_field_idx as usize);
+        }
+    }
+
+    /// Reads the value of an attribute field as a string. Lists are read as JSON arrays so that
+    /// their items can be told apart, GDAL would join them into e.g. "(2:a,b)".
+    fn field_as_string(f: &Feature, field_idx: usize) -> Option<String> {
+        let field_type = unsafe {
+            gdal_sys::OGR_Fld_GetType(gdal_sys::OGR_F_GetFieldDefnRef(f.c_feature(), field_idx as i32))
+        };
+
+        match field_type {
+            OGRFieldType::OFTStringList
+            | OGRFieldType::OFTIntegerList
+            | OGRFieldType::OFTInteger64List
+            | OGRFieldType::OFTRealList => (),
+            _ => return f.field_as_string(field_idx).unwrap_or(None),
+        }
+
+        match f.field(field_idx) {
+            Ok(Some(FieldValue::StringListValue(values))) => serde_json::to_string(&values).ok(),
+            Ok(Some(FieldValue::IntegerListValue(values))) => serde_json::to_string(&values).ok(),
+            Ok(Some(FieldValue::Integer64ListValue(values))) => serde_json::to_string(&values).ok(),
+            Ok(Some(FieldValue::RealListValue(values))) => serde_json::to_string(&values).ok(),
+            _ => None,
+        }
+    }
+
+    /// Reads all values of the feature on the given (1-based) row of a layer
+    fn record(&self, layer_index: usize, row: u64) -> Option<TatRecord> {
+        let (layer, fid_cache) = self.layers.get(layer_index)?;
+        let fid = *fid_cache.get((row as usize).checked_sub(1)?)?;
+        let feature = layer.feature(fid)?;
+
+        let total_geom_fields = layer.defn().geom_fields().count();
+        let field_count = total_geom_fields + layer.defn().fields().count();
+
+        let values = (0..field_count as i32)
+            .map(|i| TatDataset::get_attribute_from_feature(&feature, i, total_geom_fields, self.geometry_format))
+            .collect();
+
+        Some(
+            TatRecord {
+                layer_index,
+                row,
+                fid,
+                values,
             }
+        )
+    }
 
-            let wkt_res = res.unwrap().wkt();
-            if wkt_res.is_err() {
-                return None;
+    /// Compares the features on the given (1-based) rows of a layer field by field
+    fn compare(&self, layer_index: usize, row_a: u64, row_b: u64) -> Option<TatComparison> {
+        let record_a = self.record(layer_index, row_a)?;
+        let record_b = self.record(layer_index, row_b)?;
+
+        let (layer, _) = self.layers.get(layer_index)?;
+        let feature_a = layer.feature(record_a.fid)?;
+        let feature_b = layer.feature(record_b.fid)?;
+        let total_geom_fields = layer.defn().geom_fields().count();
+
+        let fields = record_a.values.into_iter().zip(record_b.values).enumerate().map(|(i, values)| {
+            let geometry = if i < total_geom_fields
+                && let Ok(geom_a) = feature_a.geometry_by_index(i)
+                && let Ok(geom_b) = feature_b.geometry_by_index(i)
+            {
+                Some(TatGeometryComparison::new(geom_a, geom_b))
+            } else {
+                None
+            };
+
+            TatFieldComparison {
+                values,
+                geometry,
+            }
+        }).collect();
+
+        Some(
+            TatComparison {
+                layer_index,
+                rows: (row_a, row_b),
+                fids: (record_a.fid, record_b.fid),
+                fields,
+            }
+        )
+    }
+
+    /// Collects the geometries of a layer's features for drawing them on the map. If the layer
+    /// has more than MAX_MAP_FEATURES features they are sampled evenly. Only the first geometry
+    /// field is considered.
+    fn layer_geometries(&self, layer_index: usize) -> TatLayerGeometries {
+        let (layer, fid_cache) = self.layers.get(layer_index).unwrap();
+        let stride = fid_cache.len().div_ceil(MAX_MAP_FEATURES).max(1);
+
+        let mut features = vec![];
+        if layer.defn().geom_fields().count() > 0 {
+            for (i, fid) in fid_cache.iter().enumerate().step_by(stride) {
+                if let Some(feature) = layer.feature(*fid)
+                    && let Ok(geom) = feature.geometry_by_index(0)
+                {
+                    features.push(TatMapFeature::from_geometry(i as u64 + 1, geom));
+                }
+            }
+        }
+
+        TatLayerGeometries::new(layer_index, features, fid_cache.len() as u64)
+    }
+
+    /// Reads the first geometry of the feature on the given (1-based) row for drawing it on the
+    /// map, since the feature may not be among the sampled ones
+    fn map_feature(&self, layer_index: usize, row: u64) -> Option<TatMapFeature> {
+        let (layer, fid_cache) = self.layers.get(layer_index)?;
+        let fid = TatDataset::fids_for_rows(fid_cache, &[row]).pop()?;
+        let feature = layer.feature(fid)?;
+
+        Some(TatMapFeature::from_geometry(row, feature.geometry_by_index(0).ok()?))
+    }
+
+    /// Exports the requested layers, reporting the progress as responses. Only the features in
+    /// the FID cache are exported, which means the attribute filter is respected. If rows are
+    /// given, only those features are exported.
+    fn export(&self, request: &TatExportRequest) -> Result<u64, String> {
+        if request.rows.is_some() && request.layers.len() != 1 {
+            return Err("Features can only be selected from a single layer".to_string());
+        }
+
+        if request.columns.is_some() && request.layers.len() != 1 {
+            return Err("Columns can only be selected from a single layer".to_string());
+        }
+
+        let mut layers = vec![];
+        for layer_index in &request.layers {
+            let (layer, fid_cache) = self.layers.get(*layer_index)
+                .ok_or(format!("No layer with index {}", layer_index))?;
+
+            let fids = match request.rows.as_ref() {
+                Some(rows) => TatDataset::fids_for_rows(fid_cache, rows),
+                None => fid_cache.clone(),
+            };
+
+            layers.push((layer, fids));
+        }
+
+        let layers: Vec<(&Layer, &[u64])> = layers.iter().map(|(layer, fids)| (*layer, fids.as_slice())).collect();
+
+        export_layers(&layers, request, &self.gdal_ds.description().unwrap_or_default(), |progress| {
+            self.send_response(
+                DatasetResponse::ExportProgress(
+                    progress,
+                )
+            );
+        })
+    }
+
+    /// Finds all cells of a layer whose values match the query. Only the features in the FID
+    /// cache are searched.
+    fn search(&self, layer_index: usize, query: TatSearchQuery) -> Result<TatSearchResults, String> {
+        let matcher = query.matcher()?;
+        let (layer, fid_cache) = self.layers.get(layer_index)
+            .ok_or(format!("No layer with index {}", layer_index))?;
+
+        let total_geom_fields = layer.defn().geom_fields().count();
+        let total_columns = (total_geom_fields + layer.defn().fields().count()) as u64;
+
+        let mut matches = vec![];
+        for (i, fid) in fid_cache.iter().enumerate() {
+            let feature = match layer.feature(*fid) {
+                Some(feature) => feature,
+                None => continue,
+            };
+
+            for column in 0..total_columns {
+                if let Some(value) = TatDataset::get_attribute_from_feature(&feature, column as i32, total_geom_fields, self.geometry_format)
+                    && matcher.is_match(&value)
+                {
+                    matches.push(TatSearchMatch { row: i as u64 + 1, column });
+                }
+            }
+        }
+
+        Ok(TatSearchResults { layer_index, query, matches })
+    }
+
+    /// Returns the information about the dataset and its (built) layers
+    fn report(&self) -> TatDatasetReport {
+        let layers = self.layers.iter().enumerate().map(|(i, (layer, _))| {
+            let extent = if layer.defn().geom_fields().count() > 0 {
+                layer.get_extent()
+                    .ok()
+                    .map(|envelope| [envelope.MinX, envelope.MinY, envelope.MaxX, envelope.MaxY])
+            } else {
+                None
+            };
+
+            TatLayerReport {
+                schema: self.schema_from_gdal_layer(i, layer),
+                extent,
             }
+        }).collect();
+
+        TatDatasetReport {
+            uri: self.gdal_ds.description().unwrap_or_default(),
+            driver_short_name: self.gdal_ds.driver().short_name(),
+            driver_long_name: self.gdal_ds.driver().long_name(),
+            layers,
+        }
+    }
+
+    /// Returns whether the values of each column (including the geometry fields) are lists or JSON
+    fn nested_columns(layer: &Layer) -> Vec<bool> {
+        std::iter::repeat_n(false, layer.defn().geom_fields().count())
+            .chain(TatDataset::attribute_fields_from_layer(layer).iter().map(TatField::is_nested))
+            .collect()
+    }
 
-            return Some(wkt_res.unwrap());
+    /// Returns the width of the widest value (in terminal cells) in each column among the first
+    /// features of the layer
+    fn sample_content_widths(layer: &Layer, fid_cache: &TatFidCache, geometry_format: TatGeometryFormat) -> Vec<u16> {
+        let total_geom_fields = layer.defn().geom_fields().count();
+        let total_columns = total_geom_fields + layer.defn().fields().count();
+        let mut widths = vec![0u16; total_columns];
+
+        // lists and JSON are summarized in the table
+        let nested = TatDataset::nested_columns(layer);
+
+        for fid in fid_cache.iter().take(CONTENT_WIDTH_SAMPLE_SIZE) {
+            let feature = match layer.feature(*fid) {
+                Some(feature) => feature,
+                None => continue,
+            };
+
+            for (column, width) in widths.iter_mut().enumerate() {
+                if let Some(mut value) = TatDataset::get_attribute_from_feature(&feature, column as i32, total_geom_fields, geometry_format) {
+                    if nested[column] {
+                        value = summary(&value);
+                    }
+
+                    let value_width = value.width().min(u16::MAX as usize) as u16;
+                    *width = (*width).max(value_width);
+                }
+            }
+        }
+
+        widths
+    }
+
+    /// Sets the attribute filter of a layer and reads its features again. If the filter is
+    /// invalid the previous one is kept.
+    fn set_filter(
+        (layer, fid_cache): &mut (Layer, TatFidCache),
+        filter: Option<&str>,
+        previous: Option<&str>,
+        order: Option<&TatSortOrder>,
+    ) -> Result<(), String> {
+        let result = match filter {
+            Some(sql) => layer.set_attribute_filter(sql),
+            None => {
+                layer.clear_attribute_filter();
+
+                Ok(())
+            },
+        };
+
+        if let Err(e) = result {
+            match previous {
+                Some(sql) => layer.set_attribute_filter(sql).map_err(|e| e.to_string())?,
+                None => layer.clear_attribute_filter(),
+            }
+
+            return Err(e.to_string());
+        }
+
+        *fid_cache = TatDataset::read_fids(layer, order)?;
+
+        Ok(())
+    }
+
+    /// Sets the order of a layer's features (None for their original order) and reads them
+    /// again
+    fn sort((layer, fid_cache): &mut (Layer, TatFidCache), order: Option<&TatSortOrder>) -> Result<(), String> {
+        if let Some(order) = order
+            && layer.defn().field_index(&order.field).is_err()
+        {
+            return Err(format!("No field named \"{}\"", order.field));
+        }
+
+        *fid_cache = TatDataset::read_fids(layer, order)?;
+
+        Ok(())
+    }
+
+    /// Returns the attribute filter of a layer, which is the --where clause unless it has been
+    /// replaced
+    fn attribute_filter(&self, layer_index: usize) -> Option<String> {
+        match self.attribute_filters.get(&layer_index) {
+            Some(filter) => filter.clone(),
+            None => self.where_clause.clone(),
+        }
+    }
+
+    /// Returns the FIDs of the layer's features which pass its attribute filter, in the given
+    /// order (if any). Features without a value in the sorted field are last.
+    fn read_fids(layer: &mut Layer, order: Option<&TatSortOrder>) -> Result<TatFidCache, String> {
+        let Some(order) = order else {
+            return Ok(layer.features().map(|feature| feature.fid().unwrap()).collect());
+        };
+
+        let field_index = layer.defn().field_index(&order.field).map_err(|e| e.to_string())?;
+        let numeric = matches!(
+            layer.defn().fields().nth(field_index).map(|field| field.field_type()),
+            Some(OGRFieldType::OFTInteger | OGRFieldType::OFTInteger64 | OGRFieldType::OFTReal),
+        );
+
+        if numeric {
+            let values = layer.features()
+                .map(|feature| (feature.fid().unwrap(), feature.field_as_double(field_index).unwrap_or(None)))
+                .collect();
+
+            Ok(TatDataset::sorted_fids(values, order.descending, |a: &f64, b| a.total_cmp(b)))
         } else {
-            let attribute_field_idx = field_idx - total_geom_fields as i32;
-            return f.field_as_string(attribute_field_idx as usize)
-                .unwrap_or(None);
+            let values = layer.features()
+                .map(|feature| (feature.fid().unwrap(), feature.field_as_string(field_index).unwrap_or(None)))
+                .collect();
+
+            Ok(TatDataset::sorted_fids(values, order.descending, |a: &String, b| a.cmp(b)))
         }
     }
 
+    /// Sorts the FIDs by their values, keeping the original order of equal values. FIDs
+    /// without a value are last in either direction.
+    fn sorted_fids<T>(mut values: Vec<(u64, Option<T>)>, descending: bool, compare: impl Fn(&T, &T) -> Ordering) -> TatFidCache {
+        values.sort_by(|(_, a), (_, b)| {
+            match (a, b) {
+                (Some(a), Some(b)) if descending => compare(b, a),
+                (Some(a), Some(b)) => compare(a, b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+        });
+
+        values.into_iter().map(|(fid, _)| fid).collect()
+    }
+
+    /// Returns the FIDs of the features on the given (1-based) rows
+    fn fids_for_rows(fid_cache: &TatFidCache, rows: &[u64]) -> Vec<u64> {
+        rows.iter()
+            .filter_map(|row| fid_cache.get((*row as usize).checked_sub(1)?).copied())
+            .collect()
+    }
+
+    /// Returns the (1-based) rows of the features with the given FIDs in ascending order
+    fn rows_for_fids(fid_cache: &TatFidCache, fids: &[u64]) -> Vec<u64> {
+        let fids: HashSet<u64> = fids.iter().copied().collect();
+
+        fid_cache.iter().enumerate()
+            .filter(|(_, fid)| fids.contains(fid))
+            .map(|(i, _)| i as u64 + 1)
+            .collect()
+    }
+
+    /// Returns the schema of a layer with the estimated widths of its columns
+    fn layer_schema(&self, layer_index: usize) -> TatLayerSchema {
+        let (layer, fid_cache) = &self.layers[layer_index];
+        let mut schema = self.schema_from_gdal_layer(layer_index, layer);
+        schema.set_content_widths(TatDataset::sample_content_widths(layer, fid_cache, self.geometry_format));
+        schema.set_filter_and_sort(
+            self.attribute_filters.get(&layer_index).cloned().flatten(),
+            self.sort_orders.get(&layer_index).cloned(),
+        );
+
+        schema
+    }
+
     fn schema_from_gdal_layer(&self, layer_index: usize, layer: &Layer) -> TatLayerSchema {
         TatLayerSchema::new(
             layer.name(),
@@ -388,17 +974,14 @@ impl<'layers> TatDataset<'layers> {
         )
     }
 
-    /// Constructs the layer information object for one layer
-    fn layer_info_text(&self, layer_index: usize, layer: &Layer) -> String {
-        let schema = self.schema_from_gdal_layer(layer_index, layer);
-
-        let mut text: String = format!("- Name: {}\n", layer.name());
+    /// Constructs the layer information text for one layer
+    pub fn layer_info_text(schema: &TatLayerSchema) -> String {
+        let mut text: String = format!("- Name: {}\n", schema.name());
         if let Some(crs) = schema.crs() {
             write!(
                 text,
-                "- CRS: {}:{} ({})\n",
-                crs.auth_name(),
-                crs.auth_code(),
+                "- CRS: {} ({})\n",
+                crs.identifier(),
                 crs.name(),
             ).unwrap();
         }
@@ -423,9 +1006,8 @@ impl<'layers> TatDataset<'layers> {
                 if let Some(crs) = field.crs() {
                     write!(
                         text,
-                        ", {}:{}",
-                        crs.auth_name(),
-                        crs.auth_code(),
+                        ", {}",
+                        crs.identifier(),
                     ).unwrap();
                 }
 
@@ -453,3 +1035,56 @@ impl<'layers> TatDataset<'layers> {
         text
     }
 }
+
+#[cfg(test)]
+mod test {
+    #[allow(unused)]
+    use super::*;
+
+    #[test]
+    fn test_nested_fields() {
+        // the GeoJSON driver can open the text itself
+        let ds = Dataset::open(r#"{"type": "FeatureCollection", "features": [{"type": "Feature", "geometry": null, "properties": {
+            "tags": ["a", "b,c"], "counts": [1, 2, 3], "ratios": [0.5, 1.5], "json": {"key": [1, {"a": null}]}, "name": "text"
+        }}]}"#).unwrap();
+        let mut layer = ds.layer(0).unwrap();
+
+        let nested: Vec<bool> = TatDataset::attribute_fields_from_layer(&layer).iter().map(TatField::is_nested).collect();
+        assert_eq!(nested, vec![true, true, true, true, false]);
+
+        let feature = layer.features().next().unwrap();
+        let value = |i: usize| TatDataset::field_as_string(&feature, i).unwrap();
+
+        assert_eq!(value(0), "[\"a\",\"b,c\"]");
+        assert_eq!(value(1), "[1,2,3]");
+        assert_eq!(value(2), "[0.5,1.5]");
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&value(3)).unwrap(),
+            serde_json::json!({"key": [1, {"a": null}]}),
+        );
+        assert_eq!(value(4), "text");
+    }
+
+    #[test]
+    fn test_sample_content_widths() {
+        let ds = Dataset::open(r#"{"type": "FeatureCollection", "features": [
+            {"type": "Feature", "geometry": null, "properties": {"name": "日本語", "emoji": "👍", "text": "abc"}},
+            {"type": "Feature", "geometry": null, "properties": {"name": "ab", "emoji": null, "text": "abcd"}}
+        ]}"#).unwrap();
+        let mut layer = ds.layer(0).unwrap();
+        let fid_cache = TatDataset::read_fids(&mut layer, None).unwrap();
+        let widths = TatDataset::sample_content_widths(&layer, &fid_cache, TatGeometryFormat::Wkt);
+
+        // wide characters take two cells
+        assert_eq!(widths[widths.len() - 3..], [6, 2, 4]);
+    }
+
+    #[test]
+    fn test_rows_for_fids() {
+        let fid_cache: TatFidCache = vec![7, 3, 10, 1];
+
+        assert_eq!(TatDataset::rows_for_fids(&fid_cache, &[1, 7, 99]), vec![1, 4]);
+        assert_eq!(TatDataset::fids_for_rows(&fid_cache, &[1, 4]), vec![7, 1]);
+        assert!(TatDataset::rows_for_fids(&fid_cache, &[]).is_empty());
+    }
+}
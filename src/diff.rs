@@ -0,0 +1,614 @@
+use std::{collections::HashMap, fmt::Display};
+
+use gdal::{
+    spatial_ref::SpatialRef,
+    vector::{field_type_to_name, Feature, FieldDefn, Geometry, Layer, LayerAccess, LayerOptions, OGRFieldType, OGRwkbGeometryType},
+    Dataset,
+    DriverManager,
+};
+
+use crate::dataset::TatDataset;
+
+/// Name of the layer which lists the schema changes in the written diff. Prefixed so that it does
+/// not collide with the compared layers.
+pub const SCHEMA_CHANGES_LAYER: &str = "tat_schema_changes";
+
+/// Names of the fields which describe the changes of the features in the written diff. Prefixed
+/// so that they do not collide with the fields of the compared layers.
+const CHANGE_FIELD: &str = "tat_change";
+const KEY_FIELD: &str = "tat_key";
+const CHANGED_FIELDS_FIELD: &str = "tat_changed_fields";
+const OLD_FID_FIELD: &str = "tat_old_fid";
+const NEW_FID_FIELD: &str = "tat_new_fid";
+
+/// Options of the written layers, the default FID and geometry column names could collide with
+/// the fields of the compared layers
+const LAYER_OPTIONS: [&str; 2] = ["FID=tat_fid", "GEOMETRY_NAME=tat_geom"];
+
+/// A difference in the structure of the datasets
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TatSchemaChangeKind {
+    AddedLayer,
+    RemovedLayer,
+    AddedField,
+    RemovedField,
+    RetypedField,
+    ChangedCrs,
+    ChangedGeometryType,
+}
+
+impl Display for TatSchemaChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TatSchemaChangeKind::AddedLayer => write!(f, "added layer"),
+            TatSchemaChangeKind::RemovedLayer => write!(f, "removed layer"),
+            TatSchemaChangeKind::AddedField => write!(f, "added field"),
+            TatSchemaChangeKind::RemovedField => write!(f, "removed field"),
+            TatSchemaChangeKind::RetypedField => write!(f, "retyped field"),
+            TatSchemaChangeKind::ChangedCrs => write!(f, "changed CRS"),
+            TatSchemaChangeKind::ChangedGeometryType => write!(f, "changed geometry type"),
+        }
+    }
+}
+
+/// A single schema difference of a layer
+#[derive(Debug, Clone, PartialEq)]
+pub struct TatSchemaChange {
+    pub layer: String,
+    pub kind: TatSchemaChangeKind,
+    /// The name of the changed field (if any)
+    pub name: Option<String>,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// How a feature differs between the datasets
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TatFeatureChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+impl Display for TatFeatureChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TatFeatureChangeKind::Added => write!(f, "added"),
+            TatFeatureChangeKind::Removed => write!(f, "removed"),
+            TatFeatureChangeKind::Modified => write!(f, "modified"),
+        }
+    }
+}
+
+/// A single feature difference of a layer
+#[derive(Debug, Clone, PartialEq)]
+pub struct TatFeatureChange {
+    pub kind: TatFeatureChangeKind,
+    /// The FID or the value of the key field which was used to match the features
+    pub key: String,
+    pub old_fid: Option<u64>,
+    pub new_fid: Option<u64>,
+    /// The names of the changed fields of a modified feature, "geometry" if the geometry changed
+    pub changed_fields: Vec<String>,
+    /// The values of the layer's fields. For modified features the changed values are given as
+    /// "old → new".
+    pub values: Vec<Option<String>>,
+    /// The (first) geometry as WKT, the old one for removed features
+    pub geometry: Option<String>,
+}
+
+/// The feature differences of a layer which exists in both datasets
+#[derive(Debug, Clone, PartialEq)]
+pub struct TatLayerDiff {
+    pub name: String,
+    /// The names of the fields in both datasets, the old fields first
+    pub fields: Vec<String>,
+    /// The spatial reference of the new layer, or of the old one if the new one has none
+    pub srs: Option<SpatialRef>,
+    pub changes: Vec<TatFeatureChange>,
+}
+
+impl TatLayerDiff {
+    /// Returns the number of changes of the given kind
+    pub fn count(&self, kind: TatFeatureChangeKind) -> usize {
+        self.changes.iter().filter(|change| change.kind == kind).count()
+    }
+}
+
+/// All differences between two datasets
+#[derive(Debug, Clone, PartialEq)]
+pub struct TatDatasetDiff {
+    pub schema_changes: Vec<TatSchemaChange>,
+    pub layers: Vec<TatLayerDiff>,
+}
+
+/// The values of a feature which are compared
+struct TatDiffFeature {
+    fid: u64,
+    values: HashMap<String, Option<String>>,
+    geometry: Option<String>,
+}
+
+impl TatDatasetDiff {
+    /// Compares the layers of two datasets which have the same name. Features are matched by
+    /// their FIDs, or by the values of the key field if one is given. Only the first geometry
+    /// field of the layers is compared. All features of a layer are read into memory, so very
+    /// large layers take a lot of memory.
+    pub fn new(old: &Dataset, new: &Dataset, key_field: Option<&str>) -> Result<Self, String> {
+        let mut schema_changes = vec![];
+        let mut layers = vec![];
+
+        let new_names: Vec<String> = new.layers().map(|layer| layer.name()).collect();
+        let old_names: Vec<String> = old.layers().map(|layer| layer.name()).collect();
+
+        for name in &old_names {
+            if !new_names.contains(name) {
+                schema_changes.push(TatSchemaChange {
+                    layer: name.clone(),
+                    kind: TatSchemaChangeKind::RemovedLayer,
+                    name: None,
+                    old: None,
+                    new: None,
+                });
+
+                continue;
+            }
+
+            let mut old_layer = old.layer_by_name(name).map_err(|e| e.to_string())?;
+            let mut new_layer = new.layer_by_name(name).map_err(|e| e.to_string())?;
+
+            schema_changes.extend(TatDatasetDiff::layer_schema_changes(&old_layer, &new_layer));
+            layers.push(TatDatasetDiff::layer_diff(&mut old_layer, &mut new_layer, key_field)?);
+        }
+
+        for name in &new_names {
+            if !old_names.contains(name) {
+                schema_changes.push(TatSchemaChange {
+                    layer: name.clone(),
+                    kind: TatSchemaChangeKind::AddedLayer,
+                    name: None,
+                    old: None,
+                    new: None,
+                });
+            }
+        }
+
+        Ok(
+            Self {
+                schema_changes,
+                layers,
+            }
+        )
+    }
+
+    /// Returns a short human-readable summary of the differences
+    pub fn summary(&self) -> String {
+        let mut text = format!("{} schema change(s)\n", self.schema_changes.len());
+
+        for layer in &self.layers {
+            text.push_str(
+                &format!(
+                    "Layer \"{}\": {} added, {} removed, {} modified\n",
+                    layer.name,
+                    layer.count(TatFeatureChangeKind::Added),
+                    layer.count(TatFeatureChangeKind::Removed),
+                    layer.count(TatFeatureChangeKind::Modified),
+                )
+            );
+        }
+
+        text
+    }
+
+    /// Writes the differences into a new GeoPackage so that they can be browsed like any other
+    /// dataset. The schema changes are written into their own layer and the feature changes of
+    /// each layer into a layer with the same name.
+    pub fn write(&self, path: &str) -> Result<(), String> {
+        let driver = DriverManager::get_driver_by_name("GPKG").map_err(|e| e.to_string())?;
+        let mut ds = driver.create_vector_only(path).map_err(|e| e.to_string())?;
+
+        let layer = TatDatasetDiff::create_layer(&mut ds, SCHEMA_CHANGES_LAYER, None, OGRwkbGeometryType::wkbNone, &["layer", "change", "name", "old", "new"])?;
+
+        for change in &self.schema_changes {
+            let mut feature = Feature::new(layer.defn()).map_err(|e| e.to_string())?;
+            let values = [Some(change.layer.clone()), Some(change.kind.to_string()), change.name.clone(), change.old.clone(), change.new.clone()];
+
+            for (i, value) in values.iter().enumerate() {
+                if let Some(value) = value {
+                    feature.set_field_string(i, value).map_err(|e| e.to_string())?;
+                }
+            }
+
+            feature.create(&layer).map_err(|e| e.to_string())?;
+        }
+
+        for layer_diff in &self.layers {
+            let has_geometry = layer_diff.changes.iter().any(|change| change.geometry.is_some());
+            let geom_type = if has_geometry { OGRwkbGeometryType::wkbUnknown } else { OGRwkbGeometryType::wkbNone };
+
+            let mut fields = vec![CHANGE_FIELD, KEY_FIELD, CHANGED_FIELDS_FIELD];
+            fields.extend(layer_diff.fields.iter().map(|field| field.as_str()));
+
+            let layer = TatDatasetDiff::create_layer(&mut ds, &layer_diff.name, layer_diff.srs.as_ref(), geom_type, &fields)?;
+            let old_fid_idx = TatDatasetDiff::add_field(&layer, OLD_FID_FIELD, OGRFieldType::OFTInteger64)?;
+            let new_fid_idx = TatDatasetDiff::add_field(&layer, NEW_FID_FIELD, OGRFieldType::OFTInteger64)?;
+
+            for change in &layer_diff.changes {
+                let mut feature = Feature::new(layer.defn()).map_err(|e| e.to_string())?;
+
+                feature.set_field_string(0, &change.kind.to_string()).map_err(|e| e.to_string())?;
+                feature.set_field_string(1, &change.key).map_err(|e| e.to_string())?;
+                feature.set_field_string(2, &change.changed_fields.join(", ")).map_err(|e| e.to_string())?;
+
+                for (i, value) in change.values.iter().enumerate() {
+                    if let Some(value) = value {
+                        feature.set_field_string(i + 3, value).map_err(|e| e.to_string())?;
+                    }
+                }
+
+                if let Some(fid) = change.old_fid {
+                    feature.set_field_integer64(old_fid_idx, fid as i64).map_err(|e| e.to_string())?;
+                }
+
+                if let Some(fid) = change.new_fid {
+                    feature.set_field_integer64(new_fid_idx, fid as i64).map_err(|e| e.to_string())?;
+                }
+
+                if let Some(wkt) = change.geometry.as_ref()
+                    && let Ok(geom) = Geometry::from_wkt(wkt)
+                {
+                    feature.set_geometry(geom).map_err(|e| e.to_string())?;
+                }
+
+                feature.create(&layer).map_err(|e| e.to_string())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Creates a layer with the given text fields
+    fn create_layer<'a>(
+        ds: &'a mut Dataset,
+        name: &str,
+        srs: Option<&SpatialRef>,
+        geom_type: OGRwkbGeometryType::Type,
+        fields: &[&str],
+    ) -> Result<Layer<'a>, String> {
+        let layer = ds.create_layer(
+            LayerOptions {
+                name,
+                srs,
+                ty: geom_type,
+                options: Some(&LAYER_OPTIONS),
+            }
+        ).map_err(|e| format!("Could not create layer \"{}\": {}", name, e))?;
+
+        for field in fields {
+            TatDatasetDiff::add_field(&layer, field, OGRFieldType::OFTString)?;
+        }
+
+        Ok(layer)
+    }
+
+    /// Adds a field to the layer and returns its index
+    fn add_field(layer: &Layer, name: &str, field_type: OGRFieldType::Type) -> Result<usize, String> {
+        FieldDefn::new(name, field_type)
+            .map_err(|e| e.to_string())?
+            .add_to_layer(layer)
+            .map_err(|e| format!("Could not create field \"{}\": {}", name, e))?;
+
+        Ok(layer.defn().fields().count() - 1)
+    }
+
+    /// Returns the differences in the fields, CRS and geometry type of a layer
+    fn layer_schema_changes(old: &Layer, new: &Layer) -> Vec<TatSchemaChange> {
+        let name = old.name();
+        let mut changes = vec![];
+
+        let change = |kind: TatSchemaChangeKind, field: Option<&str>, old: Option<String>, new: Option<String>| TatSchemaChange {
+            layer: name.clone(),
+            kind,
+            name: field.map(|field| field.to_string()),
+            old,
+            new,
+        };
+
+        let old_fields = TatDataset::attribute_fields_from_layer(old);
+        let new_fields = TatDataset::attribute_fields_from_layer(new);
+
+        for old_field in &old_fields {
+            match new_fields.iter().find(|field| field.name() == old_field.name()) {
+                Some(new_field) if new_field.dtype() != old_field.dtype() => {
+                    changes.push(
+                        change(
+                            TatSchemaChangeKind::RetypedField,
+                            Some(old_field.name()),
+                            Some(field_type_to_name(old_field.dtype())),
+                            Some(field_type_to_name(new_field.dtype())),
+                        )
+                    );
+                },
+                Some(_) => (),
+                None => {
+                    changes.push(change(TatSchemaChangeKind::RemovedField, Some(old_field.name()), Some(field_type_to_name(old_field.dtype())), None));
+                },
+            }
+        }
+
+        for new_field in &new_fields {
+            if !old_fields.iter().any(|field| field.name() == new_field.name()) {
+                changes.push(change(TatSchemaChangeKind::AddedField, Some(new_field.name()), None, Some(field_type_to_name(new_field.dtype()))));
+            }
+        }
+
+        let old_crs = TatDataset::crs_from_layer(old).map(|crs| crs.identifier());
+        let new_crs = TatDataset::crs_from_layer(new).map(|crs| crs.identifier());
+
+        if old_crs != new_crs {
+            changes.push(change(TatSchemaChangeKind::ChangedCrs, None, old_crs, new_crs));
+        }
+
+        let geom_type = |layer: &Layer| TatDataset::geom_fields_from_layer(layer).first().map(|field| field.geom_type().to_string());
+        let (old_geom_type, new_geom_type) = (geom_type(old), geom_type(new));
+
+        if old_geom_type != new_geom_type {
+            changes.push(change(TatSchemaChangeKind::ChangedGeometryType, None, old_geom_type, new_geom_type));
+        }
+
+        changes
+    }
+
+    /// Returns the added, removed and modified features of a layer
+    fn layer_diff(old: &mut Layer, new: &mut Layer, key_field: Option<&str>) -> Result<TatLayerDiff, String> {
+        let mut fields: Vec<String> = old.defn().fields().map(|field| field.name()).collect();
+        for field in new.defn().fields() {
+            if !fields.contains(&field.name()) {
+                fields.push(field.name());
+            }
+        }
+
+        let (old_keys, mut old_features) = TatDatasetDiff::read_features(old, key_field)?;
+        let (new_keys, mut new_features) = TatDatasetDiff::read_features(new, key_field)?;
+
+        let values_of = |feature: &TatDiffFeature| -> Vec<Option<String>> {
+            fields.iter().map(|field| feature.values.get(field).cloned().flatten()).collect()
+        };
+
+        let mut changes = vec![];
+
+        for key in &old_keys {
+            let old_feature = old_features.remove(key).unwrap();
+
+            let new_feature = match new_features.remove(key) {
+                Some(feature) => feature,
+                None => {
+                    changes.push(
+                        TatFeatureChange {
+                            kind: TatFeatureChangeKind::Removed,
+                            key: key.clone(),
+                            old_fid: Some(old_feature.fid),
+                            new_fid: None,
+                            changed_fields: vec![],
+                            values: values_of(&old_feature),
+                            geometry: old_feature.geometry,
+                        }
+                    );
+
+                    continue;
+                },
+            };
+
+            let mut changed_fields = vec![];
+            let mut values = vec![];
+
+            // fields which exist in only one of the layers are covered by the schema changes
+            for field in &fields {
+                let (old_value, new_value) = match (old_feature.values.get(field), new_feature.values.get(field)) {
+                    (Some(old_value), Some(new_value)) => (old_value, new_value),
+                    (old_value, new_value) => {
+                        values.push(new_value.or(old_value).cloned().flatten());
+                        continue;
+                    },
+                };
+
+                if old_value == new_value {
+                    values.push(new_value.clone());
+                } else {
+                    changed_fields.push(field.clone());
+
+                    let text = |value: &Option<String>| value.clone().unwrap_or(crate::shared::MISSING_VALUE.to_string());
+                    values.push(Some(format!("{} → {}", text(old_value), text(new_value))));
+                }
+            }
+
+            if old_feature.geometry != new_feature.geometry {
+                changed_fields.push("geometry".to_string());
+            }
+
+            if !changed_fields.is_empty() {
+                changes.push(
+                    TatFeatureChange {
+                        kind: TatFeatureChangeKind::Modified,
+                        key: key.clone(),
+                        old_fid: Some(old_feature.fid),
+                        new_fid: Some(new_feature.fid),
+                        changed_fields,
+                        values,
+                        geometry: new_feature.geometry,
+                    }
+                );
+            }
+        }
+
+        for key in &new_keys {
+            if let Some(new_feature) = new_features.remove(key) {
+                changes.push(
+                    TatFeatureChange {
+                        kind: TatFeatureChangeKind::Added,
+                        key: key.clone(),
+                        old_fid: None,
+                        new_fid: Some(new_feature.fid),
+                        changed_fields: vec![],
+                        values: values_of(&new_feature),
+                        geometry: new_feature.geometry,
+                    }
+                );
+            }
+        }
+
+        Ok(
+            TatLayerDiff {
+                name: old.name(),
+                fields,
+                srs: new.spatial_ref().or(old.spatial_ref()),
+                changes,
+            }
+        )
+    }
+
+    /// Reads the values and the first geometry of all features of a layer. Returns the keys in the
+    /// order of the features and the features by their keys.
+    fn read_features(layer: &mut Layer, key_field: Option<&str>) -> Result<(Vec<String>, HashMap<String, TatDiffFeature>), String> {
+        let name = layer.name();
+        let fields: Vec<String> = layer.defn().fields().map(|field| field.name()).collect();
+
+        let key_idx = match key_field {
+            Some(key_field) => Some(
+                fields.iter()
+                    .position(|field| field == key_field)
+                    .ok_or(format!("Layer \"{}\" has no field \"{}\"", name, key_field))?
+            ),
+            None => None,
+        };
+
+        let mut keys = vec![];
+        let mut features = HashMap::new();
+
+        for feature in layer.features() {
+            let fid = match feature.fid() {
+                Some(fid) => fid,
+                None => continue,
+            };
+
+            let values: Vec<Option<String>> = (0..fields.len())
+                .map(|i| feature.field_as_string(i).unwrap_or(None))
+                .collect();
+
+            let key = match key_idx {
+                Some(i) => values[i].clone().unwrap_or(crate::shared::MISSING_VALUE.to_string()),
+                None => fid.to_string(),
+            };
+
+            let geometry = feature.geometry_by_index(0).ok().and_then(|geom| geom.wkt().ok());
+
+            let feature = TatDiffFeature {
+                fid,
+                values: fields.iter().cloned().zip(values).collect(),
+                geometry,
+            };
+
+            if features.insert(key.clone(), feature).is_some() {
+                return Err(format!("Value \"{}\" of the key field is not unique in layer \"{}\"", key, name));
+            }
+
+            keys.push(key);
+        }
+
+        Ok((keys, features))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[allow(unused)]
+    use super::*;
+
+    /// Creates a GeoPackage with a single point layer "schema_changes" in EPSG:3067 which has the
+    /// fields name, value and optionally change, and contains the given features.
+    fn create_dataset(path: &str, features: &[(&str, i64, &str)], extra_field: bool) -> Dataset {
+        let driver = DriverManager::get_driver_by_name("GPKG").unwrap();
+        let mut ds = driver.create_vector_only(path).unwrap();
+        let layer = ds.create_layer(
+            LayerOptions {
+                name: "schema_changes",
+                srs: Some(&SpatialRef::from_epsg(3067).unwrap()),
+                ty: OGRwkbGeometryType::wkbPoint,
+                options: None,
+            }
+        ).unwrap();
+
+        FieldDefn::new("name", OGRFieldType::OFTString).unwrap().add_to_layer(&layer).unwrap();
+        FieldDefn::new("value", OGRFieldType::OFTInteger64).unwrap().add_to_layer(&layer).unwrap();
+        if extra_field {
+            FieldDefn::new("change", OGRFieldType::OFTReal).unwrap().add_to_layer(&layer).unwrap();
+        }
+
+        for (name, value, wkt) in features {
+            let mut feature = Feature::new(layer.defn()).unwrap();
+            feature.set_field_string(0, name).unwrap();
+            feature.set_field_integer64(1, *value).unwrap();
+            feature.set_geometry(Geometry::from_wkt(wkt).unwrap()).unwrap();
+            feature.create(&layer).unwrap();
+        }
+
+        drop(layer);
+
+        ds
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = create_dataset("/vsimem/tat_test_diff_old.gpkg", &[("a", 1, "POINT (0 0)"), ("b", 2, "POINT (1 1)"), ("c", 3, "POINT (2 2)")], false);
+        let new = create_dataset("/vsimem/tat_test_diff_new.gpkg", &[("a", 1, "POINT (0 0)"), ("b", 5, "POINT (1 1)"), ("d", 3, "POINT (3 3)"), ("e", 4, "POINT (4 4)")], true);
+
+        let diff = TatDatasetDiff::new(&old, &new, None).unwrap();
+        assert_eq!(diff.schema_changes.len(), 1);
+        assert_eq!(diff.schema_changes[0].kind, TatSchemaChangeKind::AddedField);
+        assert_eq!(diff.schema_changes[0].name, Some("change".to_string()));
+
+        let layer = &diff.layers[0];
+        assert_eq!(layer.fields, vec!["name", "value", "change"]);
+        assert_eq!(layer.count(TatFeatureChangeKind::Added), 1);
+        assert_eq!(layer.count(TatFeatureChangeKind::Removed), 0);
+        assert_eq!(layer.count(TatFeatureChangeKind::Modified), 2);
+
+        let modified = &layer.changes[0];
+        assert_eq!(modified.key, "2");
+        assert_eq!(modified.changed_fields, vec!["value"]);
+        assert_eq!(modified.values[1], Some("2 → 5".to_string()));
+        assert_eq!(layer.changes[1].changed_fields, vec!["name", "geometry"]);
+
+        let diff = TatDatasetDiff::new(&old, &new, Some("name")).unwrap();
+        let layer = &diff.layers[0];
+        assert_eq!(layer.count(TatFeatureChangeKind::Added), 2);
+        assert_eq!(layer.count(TatFeatureChangeKind::Removed), 1);
+        assert_eq!(layer.count(TatFeatureChangeKind::Modified), 1);
+
+        assert!(TatDatasetDiff::new(&old, &new, Some("missing")).is_err());
+
+        let path = "/vsimem/tat_test_diff.gpkg";
+        diff.write(path).unwrap();
+
+        let written = Dataset::open(path).unwrap();
+        assert_eq!(written.layer_count(), 2);
+        assert_eq!(written.layer_by_name(SCHEMA_CHANGES_LAYER).unwrap().feature_count(), 1);
+
+        let layer = written.layer_by_name("schema_changes").unwrap();
+        assert_eq!(layer.feature_count(), 4);
+        assert_eq!(layer.spatial_ref().unwrap().auth_code().unwrap(), 3067);
+
+        let fields: Vec<String> = layer.defn().fields().map(|field| field.name()).collect();
+        assert_eq!(fields, vec!["tat_change", "tat_key", "tat_changed_fields", "name", "value", "change", "tat_old_fid", "tat_new_fid"]);
+    }
+
+    #[test]
+    fn test_diff_same_dataset() {
+        let ds = Dataset::open("./testdata/basic.gpkg").unwrap();
+        let diff = TatDatasetDiff::new(&ds, &ds, None).unwrap();
+
+        assert!(diff.schema_changes.is_empty());
+        assert_eq!(diff.layers.len(), ds.layer_count());
+        assert!(diff.layers.iter().all(|layer| layer.changes.is_empty()));
+    }
+}
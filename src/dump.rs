@@ -0,0 +1,308 @@
+use std::{fmt::Display, io::{ErrorKind, Write}, str::FromStr};
+
+use gdal::vector::{Feature, Layer, LayerAccess};
+use serde_json::{Map, Value};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{config::TatGeometryFormat, serialize::{csv_escape, field_value_to_json, geometry_string}};
+
+/// Formats in which a layer can be dumped
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TatDumpFormat {
+    Csv,
+    Tsv,
+    /// One JSON object per line
+    Jsonl,
+    Markdown,
+    /// Aligned columns like in the table
+    Pretty,
+}
+
+impl TatDumpFormat {
+    pub const ALL: [TatDumpFormat; 5] = [
+        TatDumpFormat::Csv,
+        TatDumpFormat::Tsv,
+        TatDumpFormat::Jsonl,
+        TatDumpFormat::Markdown,
+        TatDumpFormat::Pretty,
+    ];
+}
+
+impl Display for TatDumpFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TatDumpFormat::Csv => write!(f, "csv"),
+            TatDumpFormat::Tsv => write!(f, "tsv"),
+            TatDumpFormat::Jsonl => write!(f, "jsonl"),
+            TatDumpFormat::Markdown => write!(f, "markdown"),
+            TatDumpFormat::Pretty => write!(f, "pretty"),
+        }
+    }
+}
+
+impl FromStr for TatDumpFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TatDumpFormat::ALL.into_iter()
+            .find(|format| format.to_string() == s.to_lowercase())
+            .ok_or(
+                format!(
+                    "unknown format \"{}\", expected one of: {}",
+                    s,
+                    TatDumpFormat::ALL.map(|format| format.to_string()).join(", "),
+                )
+            )
+    }
+}
+
+/// Which features and columns are dumped and how
+#[derive(Debug, Clone, PartialEq)]
+pub struct TatDumpOptions {
+    pub format: TatDumpFormat,
+    /// The names of the dumped columns in order, all columns if None
+    pub columns: Option<Vec<String>>,
+    /// The maximum number of dumped features
+    pub limit: Option<u64>,
+    /// How geometries are written
+    pub geometry_format: TatGeometryFormat,
+}
+
+/// A dumped column
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TatDumpColumn {
+    Geometry(usize),
+    Field(usize),
+}
+
+/// Writes the features of the layer into the output, one line per feature. Any attribute filter
+/// set on the layer is respected. Geometries are written in the given format, as objects in JSONL
+/// if the format is GeoJSON. All formats except pretty are streamed, pretty has to read all
+/// features first to align the columns. Returns the number of written features.
+pub fn dump_layer(layer: &mut Layer, options: &TatDumpOptions, out: &mut impl Write) -> Result<u64, String> {
+    let columns = dump_columns(layer, options.columns.as_deref())?;
+
+    match write_features(layer, &columns, options, out) {
+        Ok(count) => Ok(count),
+        // the reader has gone away e.g. when piped into head, which is not an error
+        Err(e) if e.kind() == ErrorKind::BrokenPipe => Ok(0),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Returns the names and the columns which should be dumped. Fails if a requested column does
+/// not exist.
+fn dump_columns(layer: &Layer, requested: Option<&[String]>) -> Result<Vec<(String, TatDumpColumn)>, String> {
+    let geom_columns = layer.defn().geom_fields().enumerate().map(|(i, field)| {
+        let name = if field.name().is_empty() { "geometry".to_string() } else { field.name() };
+
+        (name, TatDumpColumn::Geometry(i))
+    });
+    let field_columns = layer.defn().fields().enumerate().map(|(i, field)| (field.name(), TatDumpColumn::Field(i)));
+    let all_columns: Vec<(String, TatDumpColumn)> = geom_columns.chain(field_columns).collect();
+
+    let Some(requested) = requested else {
+        return Ok(all_columns);
+    };
+
+    requested.iter().map(|name| {
+        all_columns.iter()
+            .find(|(column_name, _)| column_name == name)
+            .cloned()
+            .ok_or(format!("no column \"{}\" in layer \"{}\"", name, layer.name()))
+    }).collect()
+}
+
+/// Writes the header (if the format has one) and the features
+fn write_features(layer: &mut Layer, columns: &[(String, TatDumpColumn)], options: &TatDumpOptions, out: &mut impl Write) -> std::io::Result<u64> {
+    let names: Vec<&str> = columns.iter().map(|(name, _)| name.as_str()).collect();
+    let limit = options.limit.unwrap_or(u64::MAX) as usize;
+
+    // only used by the pretty format
+    let mut pretty_rows = vec![names.iter().map(|name| name.to_string()).collect::<Vec<String>>()];
+
+    match options.format {
+        TatDumpFormat::Csv => writeln!(out, "{}", names.iter().map(|name| csv_escape(name)).collect::<Vec<_>>().join(","))?,
+        TatDumpFormat::Tsv => writeln!(out, "{}", names.iter().map(|name| tsv_escape(name)).collect::<Vec<_>>().join("\t"))?,
+        TatDumpFormat::Markdown => {
+            writeln!(out, "| {} |", names.iter().map(|name| markdown_escape(name)).collect::<Vec<_>>().join(" | "))?;
+            writeln!(out, "|{}", "---|".repeat(names.len()))?;
+        },
+        TatDumpFormat::Jsonl | TatDumpFormat::Pretty => (),
+    }
+
+    let mut count = 0;
+    for feature in layer.features().take(limit) {
+        match options.format {
+            TatDumpFormat::Csv => {
+                let values = feature_values(&feature, columns, options.geometry_format);
+                writeln!(out, "{}", values.iter().map(|v| csv_escape(v.as_deref().unwrap_or_default())).collect::<Vec<_>>().join(","))?;
+            },
+            TatDumpFormat::Tsv => {
+                let values = feature_values(&feature, columns, options.geometry_format);
+                writeln!(out, "{}", values.iter().map(|v| tsv_escape(v.as_deref().unwrap_or_default())).collect::<Vec<_>>().join("\t"))?;
+            },
+            TatDumpFormat::Markdown => {
+                let values = feature_values(&feature, columns, options.geometry_format);
+                writeln!(out, "| {} |", values.iter().map(|v| markdown_escape(v.as_deref().unwrap_or_default())).collect::<Vec<_>>().join(" | "))?;
+            },
+            TatDumpFormat::Jsonl => {
+                let mut object = Map::new();
+
+                for (name, column) in columns {
+                    let value = match column {
+                        TatDumpColumn::Geometry(i) => {
+                            match geometry_string(&feature, *i, options.geometry_format) {
+                                Some(geom) if options.geometry_format == TatGeometryFormat::GeoJson => {
+                                    serde_json::from_str(&geom).unwrap_or(Value::String(geom))
+                                },
+                                Some(geom) => Value::String(geom),
+                                None => Value::Null,
+                            }
+                        },
+                        TatDumpColumn::Field(i) => field_value_to_json(feature.field(*i).unwrap_or(None)),
+                    };
+
+                    object.insert(name.clone(), value);
+                }
+
+                writeln!(out, "{}", Value::Object(object))?;
+            },
+            TatDumpFormat::Pretty => {
+                pretty_rows.push(
+                    feature_values(&feature, columns, options.geometry_format).into_iter().map(|value| {
+                        match value {
+                            Some(value) => value.replace(['\n', '\r', '\t'], " "),
+                            None => crate::shared::MISSING_VALUE.to_string(),
+                        }
+                    }).collect()
+                );
+            },
+        }
+
+        count += 1;
+    }
+
+    if options.format == TatDumpFormat::Pretty {
+        write_pretty(&pretty_rows, out)?;
+    }
+
+    out.flush()?;
+
+    Ok(count)
+}
+
+/// Writes the rows as aligned columns, the first row is the header
+fn write_pretty(rows: &[Vec<String>], out: &mut impl Write) -> std::io::Result<()> {
+    let column_count = rows.first().map_or(0, |header| header.len());
+    let widths: Vec<usize> = (0..column_count).map(|column| {
+        rows.iter().map(|row| row[column].graphemes(true).count()).max().unwrap_or(0)
+    }).collect();
+
+    let pad = |row: &[String]| -> String {
+        row.iter().zip(widths.iter()).map(|(value, width)| {
+            let padding = width - value.graphemes(true).count();
+            format!("{}{}", value, " ".repeat(padding))
+        }).collect::<Vec<String>>().join("  ").trim_end().to_string()
+    };
+
+    for (i, row) in rows.iter().enumerate() {
+        writeln!(out, "{}", pad(row))?;
+
+        if i == 0 {
+            writeln!(out, "{}", widths.iter().map(|width| "─".repeat(*width)).collect::<Vec<String>>().join("  "))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the values of the columns as strings, geometries in the given format
+fn feature_values(feature: &Feature, columns: &[(String, TatDumpColumn)], geometry_format: TatGeometryFormat) -> Vec<Option<String>> {
+    columns.iter().map(|(_, column)| {
+        match column {
+            TatDumpColumn::Geometry(i) => geometry_string(feature, *i, geometry_format),
+            TatDumpColumn::Field(i) => feature.field_as_string(*i).unwrap_or(None),
+        }
+    }).collect()
+}
+
+/// Escapes the characters which would break the structure of a TSV file
+fn tsv_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+/// Escapes the characters which would break a Markdown table cell
+fn markdown_escape(value: &str) -> String {
+    value.replace('|', "\\|").replace("\r\n", "<br>").replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod test {
+    #[allow(unused)]
+    use super::*;
+
+    use gdal::Dataset;
+
+    fn dump(options: TatDumpOptions) -> Result<String, String> {
+        let ds = Dataset::open("./testdata/basic.gpkg").unwrap();
+        let mut layer = ds.layer_by_name("point").unwrap();
+        let mut out = vec![];
+
+        dump_layer(&mut layer, &options, &mut out)?;
+
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_format_from_str() {
+        assert_eq!("csv".parse(), Ok(TatDumpFormat::Csv));
+        assert_eq!("Markdown".parse(), Ok(TatDumpFormat::Markdown));
+        assert!("xml".parse::<TatDumpFormat>().is_err());
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(tsv_escape("a\tb\nc\\"), "a\\tb\\nc\\\\");
+        assert_eq!(markdown_escape("a|b\nc"), "a\\|b<br>c");
+    }
+
+    #[test]
+    fn test_dump_layer() {
+        let options = |format: TatDumpFormat| TatDumpOptions { format, columns: None, limit: Some(1), geometry_format: TatGeometryFormat::Wkt };
+
+        assert_eq!(dump(options(TatDumpFormat::Csv)).unwrap(), "geom,field\nPOINT (0 0),\n");
+        assert_eq!(dump(options(TatDumpFormat::Tsv)).unwrap(), "geom\tfield\nPOINT (0 0)\t\n");
+        assert_eq!(dump(options(TatDumpFormat::Jsonl)).unwrap(), "{\"geom\":\"POINT (0 0)\",\"field\":null}\n");
+        assert_eq!(dump(options(TatDumpFormat::Markdown)).unwrap(), "| geom | field |\n|---|---|\n| POINT (0 0) |  |\n");
+        assert_eq!(dump(options(TatDumpFormat::Pretty)).unwrap(), "geom         field\n───────────  ─────\nPOINT (0 0)  NULL\n");
+
+        let all = dump(TatDumpOptions { format: TatDumpFormat::Csv, columns: None, limit: None, geometry_format: TatGeometryFormat::Wkt }).unwrap();
+        assert!(all.lines().count() > 2);
+
+        let columns = TatDumpOptions {
+            format: TatDumpFormat::Csv,
+            columns: Some(vec!["field".to_string(), "geom".to_string()]),
+            limit: Some(1),
+            geometry_format: TatGeometryFormat::Wkt,
+        };
+        assert_eq!(dump(columns).unwrap(), "field,geom\n,POINT (0 0)\n");
+
+        let missing = TatDumpOptions {
+            format: TatDumpFormat::Csv,
+            columns: Some(vec!["nope".to_string()]),
+            limit: None,
+            geometry_format: TatGeometryFormat::Wkt,
+        };
+        assert!(dump(missing).is_err());
+
+        let geometry_format = |format: TatDumpFormat, geometry_format: TatGeometryFormat| {
+            TatDumpOptions { format, columns: None, limit: Some(1), geometry_format }
+        };
+        assert_eq!(dump(geometry_format(TatDumpFormat::Csv, TatGeometryFormat::Type)).unwrap(), "geom,field\nPOINT,\n");
+        assert_eq!(
+            dump(geometry_format(TatDumpFormat::Jsonl, TatGeometryFormat::GeoJson)).unwrap(),
+            "{\"geom\":{\"type\":\"Point\",\"coordinates\":[0.0,0.0]},\"field\":null}\n",
+        );
+    }
+}
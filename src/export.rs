@@ -0,0 +1,341 @@
+use std::path::Path;
+
+#[allow(unused_imports)]
+use cli_log::*;
+use gdal::{
+    vector::{Feature, FieldDefn, Layer, LayerAccess, LayerOptions, OGRwkbGeometryType},
+    Dataset,
+    Driver,
+    DriverManager,
+    DriverType,
+    Metadata,
+};
+
+/// Describes which layers should be exported and where
+#[derive(Debug, Clone, PartialEq)]
+pub struct TatExportRequest {
+    /// Indices of the layers to export
+    pub layers: Vec<usize>,
+    /// The (1-based) rows of the features to export if only some features of a single layer
+    /// should be exported
+    pub rows: Option<Vec<u64>>,
+    /// The columns (as indices which include the geometry fields) to export in their order if
+    /// only the shown columns of a single layer should be exported
+    pub columns: Option<Vec<u64>>,
+    /// Short name of the output driver. If empty, the driver is guessed from the path.
+    pub driver: String,
+    pub path: String,
+    /// Whether an existing dataset in the path may be replaced
+    pub overwrite: bool,
+}
+
+/// Progress of an ongoing export
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TatExportProgress {
+    pub exported: u64,
+    pub total: u64,
+}
+
+impl TatExportProgress {
+    /// Returns the progress as a ratio between 0.0 and 1.0
+    pub fn ratio(&self) -> f64 {
+        if self.total == 0 {
+            return 1.0;
+        }
+
+        (self.exported as f64 / self.total as f64).clamp(0.0, 1.0)
+    }
+}
+
+/// Returns the driver which should be used for the export. If no driver name is given, the
+/// driver is guessed from the path's extension.
+pub fn resolve_driver(driver: &str, path: &str) -> Result<Driver, String> {
+    let driver = driver.trim();
+
+    if driver.is_empty() {
+        return DriverManager::get_output_driver_for_dataset_name(path, DriverType::Vector)
+            .ok_or(format!("Could not determine a driver for \"{}\", please specify one", path));
+    }
+
+    let driver = DriverManager::get_driver_by_name(driver)
+        .map_err(|_| format!("No driver named \"{}\"", driver))?;
+
+    if driver.metadata_item("DCAP_VECTOR", "").is_none() || driver.metadata_item("DCAP_CREATE", "").is_none() {
+        return Err(format!("Driver \"{}\" cannot create vector datasets", driver.short_name()));
+    }
+
+    Ok(driver)
+}
+
+/// Returns whether the two paths point to the same existing file
+fn same_file(path: &str, other: &str) -> bool {
+    match (Path::new(path).canonicalize(), Path::new(other).canonicalize()) {
+        (Ok(path), Ok(other)) => path == other,
+        _ => false,
+    }
+}
+
+/// Writes the given layers into a new dataset. Only the features whose FIDs are given are
+/// exported, so any filters applied when caching the FIDs are respected. The dataset which the
+/// layers are read from (source) is never overwritten. Returns the number of exported features.
+pub fn export_layers(
+    layers: &[(&Layer, &[u64])],
+    request: &TatExportRequest,
+    source: &str,
+    mut on_progress: impl FnMut(TatExportProgress),
+) -> Result<u64, String> {
+    let driver = resolve_driver(&request.driver, &request.path)?;
+
+    if same_file(&request.path, source) {
+        return Err(format!("\"{}\" is the open dataset and cannot be overwritten", request.path));
+    }
+
+    if Path::new(&request.path).exists() {
+        if !request.overwrite {
+            return Err(format!("\"{}\" already exists", request.path));
+        }
+
+        // the driver knows about any sidecar files (e.g. with shapefiles)
+        if driver.delete(&request.path).is_err() {
+            std::fs::remove_file(&request.path).map_err(|e| e.to_string())?;
+        }
+    }
+
+    let mut ds = driver.create_vector_only(&request.path).map_err(|e| e.to_string())?;
+
+    let mut progress = TatExportProgress {
+        exported: 0,
+        total: layers.iter().map(|(_, fids)| fids.len() as u64).sum(),
+    };
+
+    // don't flood the receiver, roughly every percent is enough
+    let step = (progress.total / 100).max(1);
+    let mut report = |p: TatExportProgress| {
+        if p.exported.is_multiple_of(step) || p.exported == p.total {
+            on_progress(p);
+        }
+    };
+
+    report(progress);
+
+    // writing is considerably faster within a transaction with drivers which support them
+    let supports_transactions = ds.start_transaction().map(|txn| txn.rollback()).is_ok();
+
+    if supports_transactions {
+        let mut txn = ds.start_transaction().map_err(|e| e.to_string())?;
+        for (layer, fids) in layers {
+            export_layer(&mut txn, layer, fids, request.columns.as_deref(), &mut progress, &mut report)?;
+        }
+
+        txn.commit().map_err(|e| e.to_string())?;
+    } else {
+        for (layer, fids) in layers {
+            export_layer(&mut ds, layer, fids, request.columns.as_deref(), &mut progress, &mut report)?;
+        }
+    }
+
+    Ok(progress.exported)
+}
+
+/// Creates a copy of the layer in the dataset and writes the given features into it. If columns
+/// are given, only those fields are written in that order. Only the first of the given geometry
+/// fields is written.
+fn export_layer(
+    ds: &mut Dataset,
+    layer: &Layer,
+    fids: &[u64],
+    columns: Option<&[u64]>,
+    progress: &mut TatExportProgress,
+    report: &mut impl FnMut(TatExportProgress),
+) -> Result<(), String> {
+    let defn = layer.defn();
+    let geom_count = defn.geom_fields().count() as u64;
+    let all_columns: Vec<u64> = (0..geom_count + defn.fields().count() as u64).collect();
+    let columns = columns.unwrap_or(&all_columns);
+
+    let geom_field = columns.iter().find(|col| **col < geom_count).map(|col| *col as usize);
+    let field_indices: Vec<usize> = columns.iter()
+        .filter(|col| **col >= geom_count)
+        .map(|col| (col - geom_count) as usize)
+        .collect();
+
+    let (geom_type, srs) = match geom_field.and_then(|i| defn.geom_fields().nth(i)) {
+        Some(field) => (field.field_type(), field.spatial_ref().ok()),
+        None => (OGRwkbGeometryType::wkbNone, None),
+    };
+
+    let name = layer.name();
+
+    let out_layer = ds.create_layer(
+        LayerOptions {
+            name: &name,
+            srs: srs.as_ref(),
+            ty: geom_type,
+            options: None,
+        }
+    ).map_err(|e| format!("Could not create layer \"{}\": {}", name, e))?;
+
+    let fields: Vec<_> = defn.fields().collect();
+    for i in &field_indices {
+        let field = fields.get(*i).ok_or(format!("No field with index {}", i))?;
+        let field_defn = FieldDefn::new(&field.name(), field.field_type()).map_err(|e| e.to_string())?;
+        field_defn.set_width(field.width());
+        field_defn.set_precision(field.precision());
+        field_defn.add_to_layer(&out_layer)
+            .map_err(|e| format!("Could not create field \"{}\": {}", field.name(), e))?;
+    }
+
+    for fid in fids {
+        let feature = match layer.feature(*fid) {
+            Some(feature) => feature,
+            None => continue,
+        };
+
+        let mut out_feature = Feature::new(out_layer.defn()).map_err(|e| e.to_string())?;
+
+        for (out_i, i) in field_indices.iter().enumerate() {
+            if let Some(value) = feature.field(*i).map_err(|e| e.to_string())? {
+                out_feature.set_field(out_i, &value).map_err(|e| e.to_string())?;
+            }
+        }
+
+        if let Some(i) = geom_field
+            && let Ok(geom) = feature.geometry_by_index(i)
+        {
+            out_feature.set_geometry(geom.clone()).map_err(|e| e.to_string())?;
+        }
+
+        out_feature.create(&out_layer)
+            .map_err(|e| format!("Could not write feature {}: {}", fid, e))?;
+
+        progress.exported += 1;
+        report(*progress);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    #[allow(unused)]
+    use super::*;
+
+    use std::env::temp_dir;
+
+    use crate::{app::TatEvent, dataset::{DatasetRequest, DatasetResponse}, fixtures::{datasets::basic_gpkg, init_table, TatTestStructure}};
+
+    use rstest::*;
+
+    #[test]
+    fn test_progress_ratio() {
+        assert_eq!(TatExportProgress { exported: 0, total: 0 }.ratio(), 1.0);
+        assert_eq!(TatExportProgress { exported: 1, total: 4 }.ratio(), 0.25);
+    }
+
+    #[test]
+    fn test_resolve_driver() {
+        assert_eq!(resolve_driver("", "out.gpkg").unwrap().short_name(), "GPKG");
+        assert_eq!(resolve_driver(" GeoJSON ", "out.gpkg").unwrap().short_name(), "GeoJSON");
+        assert!(resolve_driver("", "out.unknownextension").is_err());
+        assert!(resolve_driver("NotADriver", "out.gpkg").is_err());
+        assert!(resolve_driver("GTiff", "out.tif").is_err());
+    }
+
+    #[rstest]
+    fn test_export(basic_gpkg: TatTestStructure) {
+        let (test, _) = init_table(basic_gpkg);
+        let path = format!("{}/tat_test_export.geojson", temp_dir().display());
+        let _ = std::fs::remove_file(&path);
+
+        let export = |overwrite: bool| {
+            test.ds_request_tx.send(
+                DatasetRequest::Export(
+                    TatExportRequest {
+                        layers: vec![1],
+                        rows: None,
+                        columns: None,
+                        driver: "".to_string(),
+                        path: path.clone(),
+                        overwrite,
+                    }
+                )
+            ).unwrap();
+
+            let mut last_progress = None;
+            loop {
+                match test.tatevent_rx.recv().unwrap() {
+                    TatEvent::Dataset(DatasetResponse::ExportProgress(progress)) => last_progress = Some(progress),
+                    TatEvent::Dataset(DatasetResponse::ExportFinished(result)) => return (last_progress, result),
+                    _ => panic!(),
+                }
+            }
+        };
+
+        let (progress, result) = export(false);
+        assert_eq!(result, Ok(4));
+        assert_eq!(progress, Some(TatExportProgress { exported: 4, total: 4 }));
+
+        let ds = Dataset::open(&path).unwrap();
+        let layer = ds.layer(0).unwrap();
+        assert_eq!(layer.name(), "line");
+        assert_eq!(layer.feature_count(), 4);
+
+        let (_, result) = export(false);
+        assert!(result.unwrap_err().ends_with("already exists"));
+
+        let (_, result) = export(true);
+        assert_eq!(result, Ok(4));
+
+        test.ds_request_tx.send(
+            DatasetRequest::Export(
+                TatExportRequest {
+                    layers: vec![1],
+                    rows: Some(vec![2, 4]),
+                    columns: Some(vec![1]),
+                    driver: "GeoJSON".to_string(),
+                    path: path.clone(),
+                    overwrite: true,
+                }
+            )
+        ).unwrap();
+
+        loop {
+            if let TatEvent::Dataset(DatasetResponse::ExportFinished(result)) = test.tatevent_rx.recv().unwrap() {
+                assert_eq!(result, Ok(2));
+                break;
+            }
+        }
+
+        // only the shown column was exported, without the geometry
+        let ds = Dataset::open(&path).unwrap();
+        let mut layer = ds.layer(0).unwrap();
+        let fields: Vec<String> = layer.defn().fields().map(|field| field.name()).collect();
+        assert_eq!(fields, vec!["field"]);
+        assert!(layer.features().all(|feature| feature.geometry().is_none()));
+
+        // the open dataset is never overwritten
+        test.ds_request_tx.send(
+            DatasetRequest::Export(
+                TatExportRequest {
+                    layers: vec![1],
+                    rows: None,
+                    columns: None,
+                    driver: "GPKG".to_string(),
+                    path: "./testdata/../testdata/basic.gpkg".to_string(),
+                    overwrite: true,
+                }
+            )
+        ).unwrap();
+
+        loop {
+            if let TatEvent::Dataset(DatasetResponse::ExportFinished(result)) = test.tatevent_rx.recv().unwrap() {
+                assert!(result.unwrap_err().ends_with("is the open dataset and cannot be overwritten"));
+                break;
+            }
+        }
+        assert!(Path::new("./testdata/basic.gpkg").exists());
+
+        std::fs::remove_file(&path).unwrap();
+        test.terminate();
+    }
+}
@@ -0,0 +1,406 @@
+use std::path::Path;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Margin, Rect},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear},
+    Frame,
+};
+
+use crate::{
+    config::config,
+    export::{resolve_driver, TatExportRequest},
+    keys::TatAction,
+    textinput::{TatTextInput, TatTextInputResult},
+};
+
+const LABEL_WIDTH: u16 = 8;
+
+/// Result of handling a key press in the export dialog
+#[derive(Debug, PartialEq)]
+pub enum TatExportDialogResult {
+    RejectedKey,
+    AcceptedKey,
+    Close,
+    Accept(TatExportRequest),
+}
+
+/// Specifies which field in the export dialog has the focus
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum TatExportField {
+    Driver,
+    Path,
+    Layers,
+}
+
+/// Specifies what is exported
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum TatExportScope {
+    SelectedFeatures,
+    CurrentLayer,
+    AllLayers,
+}
+
+/// Dialog for choosing the driver, path and layers of an export
+#[derive(Debug, PartialEq)]
+pub struct TatExportDialog {
+    driver: TatTextInput,
+    path: TatTextInput,
+    focus: TatExportField,
+    scope: TatExportScope,
+    selected_rows: Vec<u64>,
+    /// The shown columns of the current layer in display order
+    columns: Vec<u64>,
+    layer_index: usize,
+    layer_name: String,
+    layer_count: usize,
+    confirm_overwrite: bool,
+}
+
+impl TatExportDialog {
+    /// Constructs a new dialog. By default the selected features (or the current layer if there
+    /// is no selection) are exported into a GeoPackage named after the layer. Only the shown
+    /// columns of the current layer are exported. The earlier paths can be browsed with Ctrl+P
+    /// and Ctrl+N.
+    pub fn new(
+        layer_index: usize,
+        layer_name: &str,
+        layer_count: usize,
+        selected_rows: Vec<u64>,
+        columns: Vec<u64>,
+        path_history: Vec<String>,
+    ) -> Self {
+        Self {
+            driver: TatTextInput::with_text("GPKG"),
+            path: TatTextInput::with_text(&format!("{}.gpkg", layer_name)).with_history(path_history),
+            focus: TatExportField::Path,
+            scope: if selected_rows.is_empty() { TatExportScope::CurrentLayer } else { TatExportScope::SelectedFeatures },
+            selected_rows,
+            columns,
+            layer_index,
+            layer_name: layer_name.to_string(),
+            layer_count,
+            confirm_overwrite: false,
+        }
+    }
+
+    /// Returns the export request matching the current state of the dialog
+    pub fn request(&self, overwrite: bool) -> TatExportRequest {
+        TatExportRequest {
+            layers: match self.scope {
+                TatExportScope::AllLayers => (0..self.layer_count).collect(),
+                _ => vec![self.layer_index],
+            },
+            rows: match self.scope {
+                TatExportScope::SelectedFeatures => Some(self.selected_rows.clone()),
+                _ => None,
+            },
+            columns: match self.scope {
+                TatExportScope::AllLayers => None,
+                _ => Some(self.columns.clone()),
+            },
+            driver: self.driver.text().trim().to_string(),
+            path: self.path.text().trim().to_string(),
+            overwrite,
+        }
+    }
+
+    /// Handles the incoming key code. The text fields take all keys except for the ones which
+    /// switch fields, in the layers field the bound left and right keys change what is exported.
+    pub fn key_press(&mut self, key: KeyCode, ctrl_down: bool) -> TatExportDialogResult {
+        if self.confirm_overwrite {
+            return match key {
+                KeyCode::Char('y') => TatExportDialogResult::Accept(self.request(true)),
+                KeyCode::Char('n') | KeyCode::Esc => {
+                    self.confirm_overwrite = false;
+
+                    TatExportDialogResult::AcceptedKey
+                },
+                _ => TatExportDialogResult::RejectedKey,
+            };
+        }
+
+        match key {
+            KeyCode::Esc => return TatExportDialogResult::Close,
+            KeyCode::Enter => return self.submit(),
+            KeyCode::Tab | KeyCode::Down => {
+                self.cycle_focus(false);
+
+                return TatExportDialogResult::AcceptedKey;
+            },
+            KeyCode::BackTab | KeyCode::Up => {
+                self.cycle_focus(true);
+
+                return TatExportDialogResult::AcceptedKey;
+            },
+            _ => (),
+        }
+
+        let input = match self.focus {
+            TatExportField::Driver => &mut self.driver,
+            TatExportField::Path => &mut self.path,
+            TatExportField::Layers => {
+                let modifiers = if ctrl_down { KeyModifiers::CONTROL } else { KeyModifiers::NONE };
+                let action = config().keys.view_action(&KeyEvent::new(key, modifiers), &[TatAction::Left, TatAction::Right]);
+
+                return match (action, key) {
+                    (Some(TatAction::Left), _) => {
+                        self.cycle_scope(true);
+
+                        TatExportDialogResult::AcceptedKey
+                    },
+                    (Some(TatAction::Right), _) | (_, KeyCode::Char(' ')) => {
+                        self.cycle_scope(false);
+
+                        TatExportDialogResult::AcceptedKey
+                    },
+                    _ => TatExportDialogResult::RejectedKey,
+                };
+            },
+        };
+
+        match input.key_press(key, ctrl_down) {
+            TatTextInputResult::AcceptedKey => TatExportDialogResult::AcceptedKey,
+            _ => TatExportDialogResult::RejectedKey,
+        }
+    }
+
+    /// Renders the dialog into the given area
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let block_area = area.inner(Margin { horizontal: 1, vertical: 1 });
+
+        let hint = if self.confirm_overwrite {
+            " <press y to overwrite, n to cancel> "
+        } else {
+            " <Tab to switch fields, Enter to export, Esc to cancel> "
+        };
+
+        let block = Block::default()
+            .title(Line::raw(" Export ").bold().underlined().centered())
+            .borders(Borders::ALL)
+            .border_style(crate::shared::palette::current().highlighted_style())
+            .border_type(BorderType::Rounded)
+            .title_bottom(Line::raw(hint).centered());
+
+        let inner = block_area.inner(Margin { horizontal: 1, vertical: 1 });
+        let [driver_area, path_area, layers_area, _, message_area] = Layout::vertical([Constraint::Length(1); 5]).areas(inner);
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, block_area);
+
+        let focused = |field: TatExportField| self.focus == field && !self.confirm_overwrite;
+
+        self.render_field(frame, driver_area, "Driver:", TatExportField::Driver);
+        self.render_field(frame, path_area, "Path:", TatExportField::Path);
+
+        let [label_area, value_area] = TatExportDialog::field_areas(layers_area);
+        frame.render_widget(self.label("Layers:", TatExportField::Layers), label_area);
+
+        let layers = match self.scope {
+            TatExportScope::SelectedFeatures => format!("◄ selected features ({}) of \"{}\" ►", self.selected_rows.len(), self.layer_name),
+            TatExportScope::CurrentLayer => format!("◄ current layer \"{}\" ►", self.layer_name),
+            TatExportScope::AllLayers => format!("◄ all layers ({}) ►", self.layer_count),
+        };
+
+        frame.render_widget(
+            if focused(TatExportField::Layers) {
+                Span::styled(layers, crate::shared::palette::current().highlighted_style())
+            } else {
+                Span::styled(layers, crate::shared::palette::current().default_style())
+            },
+            value_area,
+        );
+
+        if self.confirm_overwrite {
+            frame.render_widget(
+                Span::styled(
+                    format!("\"{}\" already exists. Overwrite?", self.path.text().trim()),
+                    crate::shared::palette::current().highlighted_style(),
+                ),
+                message_area,
+            );
+        }
+    }
+
+    /// Returns the area which the dialog should be rendered into
+    pub fn area(area: Rect, percent_x: u16) -> Rect {
+        let vertical = Layout::vertical([Constraint::Length(9)]).flex(Flex::Center);
+        let horizontal = Layout::horizontal([Constraint::Percentage(percent_x)]).flex(Flex::Center);
+        let [area] = vertical.areas(area);
+        let [area] = horizontal.areas(area);
+
+        area
+    }
+
+    /// Validates the dialog and either accepts it or asks whether an existing file should be
+    /// overwritten
+    fn submit(&mut self) -> TatExportDialogResult {
+        let request = self.request(false);
+
+        if request.path.is_empty() {
+            self.focus = TatExportField::Path;
+            self.path.set_error("Path is required".to_string());
+
+            return TatExportDialogResult::AcceptedKey;
+        }
+
+        if let Err(e) = resolve_driver(&request.driver, &request.path) {
+            self.focus = TatExportField::Driver;
+            self.driver.set_error(e);
+
+            return TatExportDialogResult::AcceptedKey;
+        }
+
+        if Path::new(&request.path).exists() {
+            self.confirm_overwrite = true;
+
+            return TatExportDialogResult::AcceptedKey;
+        }
+
+        TatExportDialogResult::Accept(request)
+    }
+
+    /// Moves the focus to the next or previous field (looping)
+    fn cycle_focus(&mut self, back: bool) {
+        self.focus = match self.focus {
+            TatExportField::Driver if back => TatExportField::Layers,
+            TatExportField::Path if back => TatExportField::Driver,
+            TatExportField::Layers if back => TatExportField::Path,
+
+            TatExportField::Driver => TatExportField::Path,
+            TatExportField::Path => TatExportField::Layers,
+            TatExportField::Layers => TatExportField::Driver,
+        }
+    }
+
+    /// Moves to the next or previous export scope (looping). Selected features are only available
+    /// if there is a selection.
+    fn cycle_scope(&mut self, back: bool) {
+        let mut scopes = vec![];
+        if !self.selected_rows.is_empty() {
+            scopes.push(TatExportScope::SelectedFeatures);
+        }
+        scopes.push(TatExportScope::CurrentLayer);
+        scopes.push(TatExportScope::AllLayers);
+
+        let current = scopes.iter().position(|scope| *scope == self.scope).unwrap_or(0);
+        let next = (if back { current + scopes.len() - 1 } else { current + 1 }) % scopes.len();
+
+        self.scope = scopes[next];
+    }
+
+    /// Renders a labeled text field
+    fn render_field(&self, frame: &mut Frame, area: Rect, label: &str, field: TatExportField) {
+        let [label_area, input_area] = TatExportDialog::field_areas(area);
+        let input = match field {
+            TatExportField::Driver => &self.driver,
+            TatExportField::Path => &self.path,
+            TatExportField::Layers => return,
+        };
+
+        frame.render_widget(self.label(label, field), label_area);
+        input.render(frame, input_area, self.focus == field && !self.confirm_overwrite);
+    }
+
+    /// Returns the label of a field, highlighted if the field has the focus
+    fn label<'a>(&self, label: &'a str, field: TatExportField) -> Span<'a> {
+        if self.focus == field && !self.confirm_overwrite {
+            Span::styled(label, crate::shared::palette::current().highlighted_style()).bold()
+        } else {
+            Span::styled(label, crate::shared::palette::current().default_style())
+        }
+    }
+
+    /// Splits a row of the dialog into the label and value areas
+    fn field_areas(area: Rect) -> [Rect; 2] {
+        Layout::horizontal([
+            Constraint::Length(LABEL_WIDTH),
+            Constraint::Fill(1),
+        ]).areas(area)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[allow(unused)]
+    use super::*;
+
+    #[test]
+    fn test_request() {
+        let mut dialog = TatExportDialog::new(2, "polygon", 5, vec![], vec![1, 0], vec![]);
+        assert_eq!(
+            dialog.request(false),
+            TatExportRequest {
+                layers: vec![2],
+                rows: None,
+                columns: Some(vec![1, 0]),
+                driver: "GPKG".to_string(),
+                path: "polygon.gpkg".to_string(),
+                overwrite: false,
+            },
+        );
+
+        dialog.key_press(KeyCode::Tab, false);
+        dialog.key_press(KeyCode::Right, false);
+        assert_eq!(dialog.request(true).layers, vec![0, 1, 2, 3, 4]);
+        assert_eq!(dialog.request(true).columns, None);
+        assert!(dialog.request(true).overwrite);
+
+        // typing goes to the focused field
+        dialog.key_press(KeyCode::Tab, false);
+        dialog.key_press(KeyCode::End, false);
+        for _ in 0..4 {
+            dialog.key_press(KeyCode::Backspace, false);
+        }
+        assert_eq!(dialog.request(false).driver, "");
+    }
+
+    #[test]
+    fn test_scope() {
+        let mut dialog = TatExportDialog::new(1, "line", 5, vec![2, 3], vec![0, 1], vec![]);
+        assert_eq!(dialog.request(false).rows, Some(vec![2, 3]));
+
+        dialog.focus = TatExportField::Layers;
+        dialog.key_press(KeyCode::Right, false);
+        assert_eq!(dialog.request(false).rows, None);
+        assert_eq!(dialog.request(false).layers, vec![1]);
+
+        dialog.key_press(KeyCode::Right, false);
+        assert_eq!(dialog.request(false).layers, vec![0, 1, 2, 3, 4]);
+
+        dialog.key_press(KeyCode::Right, false);
+        assert_eq!(dialog.scope, TatExportScope::SelectedFeatures);
+
+        dialog.key_press(KeyCode::Left, false);
+        assert_eq!(dialog.scope, TatExportScope::AllLayers);
+    }
+
+    #[test]
+    fn test_submit() {
+        let mut dialog = TatExportDialog::new(0, "point", 1, vec![], vec![0], vec!["out.gpkg".to_string()]);
+        assert_eq!(dialog.key_press(KeyCode::Esc, false), TatExportDialogResult::Close);
+
+        dialog.key_press(KeyCode::Char('u'), true);
+        assert_eq!(dialog.key_press(KeyCode::Enter, false), TatExportDialogResult::AcceptedKey);
+        assert_eq!(dialog.path.error(), Some("Path is required"));
+
+        dialog.key_press(KeyCode::Char('p'), true);
+        assert_eq!(dialog.path.text(), "out.gpkg");
+        assert_eq!(dialog.path.error(), None);
+
+        // an existing file requires confirmation
+        dialog.path = TatTextInput::with_text("./testdata/basic.gpkg");
+        assert_eq!(dialog.key_press(KeyCode::Enter, false), TatExportDialogResult::AcceptedKey);
+        assert!(dialog.confirm_overwrite);
+        assert_eq!(dialog.key_press(KeyCode::Char('n'), false), TatExportDialogResult::AcceptedKey);
+        assert!(!dialog.confirm_overwrite);
+
+        dialog.key_press(KeyCode::Enter, false);
+        match dialog.key_press(KeyCode::Char('y'), false) {
+            TatExportDialogResult::Accept(request) => assert!(request.overwrite),
+            _ => panic!(),
+        }
+    }
+}
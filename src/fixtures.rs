@@ -20,8 +20,7 @@ impl TatTestUtils {
             layer_index,
             top_row: 1,
             bottom_row: 10,
-            first_column: 0,
-            last_column: 4,
+            columns: (0..4).collect(),
             total_geom_fields,
         };
         tx.send(DatasetRequest::UpdateAttributeView(r)).unwrap();
@@ -60,8 +59,7 @@ impl TatTestUtils {
             layer_index: ls.index(),
             top_row: 1,
             bottom_row: feature_count,
-            first_column: 0,
-            last_column: field_count,
+            columns: (0..field_count).collect(),
             total_geom_fields: ls.geom_fields().len(),
         };
 
@@ -284,9 +282,9 @@ pub fn no_geom_field() -> Vec<TatGeomField> {
 #[fixture]
 pub fn attribute_fields() -> Vec<TatField> {
     vec![
-        TatField::new("Field1".to_string(), 0),
-        TatField::new("Field2".to_string(), 1),
-        TatField::new("Field3".to_string(), 2),
+        TatField::new("Field1".to_string(), 0, 0),
+        TatField::new("Field2".to_string(), 1, 0),
+        TatField::new("Field3".to_string(), 2, 0),
     ]
 }
 
@@ -0,0 +1,155 @@
+use std::fmt::Write;
+
+use gdal::vector::field_type_to_name;
+use serde_json::{json, Value};
+
+use crate::{dataset::TatDataset, layerschema::TatLayerSchema, types::TatCrs};
+
+/// The bounding box of a layer's features as (min x, min y, max x, max y)
+pub type TatExtent = [f64; 4];
+
+/// Information about one layer of a dataset
+#[derive(Debug)]
+pub struct TatLayerReport {
+    pub schema: TatLayerSchema,
+    /// None if the layer has no geometries or the extent could not be computed
+    pub extent: Option<TatExtent>,
+}
+
+/// Information about a dataset and its layers which can be printed without the UI
+#[derive(Debug)]
+pub struct TatDatasetReport {
+    pub uri: String,
+    pub driver_short_name: String,
+    pub driver_long_name: String,
+    pub layers: Vec<TatLayerReport>,
+}
+
+impl TatDatasetReport {
+    /// Returns the report as human-readable text, formatted like the information in the UI
+    pub fn text(&self) -> String {
+        let mut text = format!("Dataset\n- URI: \"{}\"\n", self.uri);
+        writeln!(text, "- Driver: {} ({})", self.driver_long_name, self.driver_short_name).unwrap();
+        writeln!(text, "- Layers: {}", self.layers.len()).unwrap();
+
+        for (i, layer) in self.layers.iter().enumerate() {
+            writeln!(text, "\nLayer {}", i + 1).unwrap();
+            text.push_str(&TatDataset::layer_info_text(&layer.schema));
+
+            if let Some([min_x, min_y, max_x, max_y]) = layer.extent {
+                writeln!(text, "- Extent: ({}, {}) - ({}, {})", min_x, min_y, max_x, max_y).unwrap();
+            }
+        }
+
+        text
+    }
+
+    /// Returns the report as a JSON object
+    pub fn to_json(&self) -> Value {
+        let layers: Vec<Value> = self.layers.iter().map(|layer| {
+            let schema = &layer.schema;
+
+            let geometry_fields: Vec<Value> = schema.geom_fields().iter().map(|field| {
+                json!({
+                    "name": field.name(),
+                    "type": field.geom_type(),
+                    "crs": crs_to_json(field.crs()),
+                })
+            }).collect();
+
+            let fields: Vec<Value> = schema.attribute_fields().iter().map(|field| {
+                json!({
+                    "name": field.name(),
+                    "type": field_type_to_name(field.dtype()),
+                })
+            }).collect();
+
+            json!({
+                "name": schema.name(),
+                "feature_count": schema.feature_count(),
+                "crs": crs_to_json(schema.crs()),
+                "extent": layer.extent,
+                "geometry_fields": geometry_fields,
+                "fields": fields,
+            })
+        }).collect();
+
+        json!({
+            "uri": self.uri,
+            "driver": {
+                "short_name": self.driver_short_name,
+                "long_name": self.driver_long_name,
+            },
+            "layers": layers,
+        })
+    }
+}
+
+/// Returns the CRS as a JSON object, or null if there is none
+fn crs_to_json(crs: Option<&TatCrs>) -> Value {
+    match crs {
+        Some(crs) => json!({
+            "identifier": crs.identifier(),
+            "name": crs.name(),
+            "kind": crs.kind().map(|kind| kind.to_string()),
+        }),
+        None => Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[allow(unused)]
+    use super::*;
+
+    use crate::{app::TatEvent, dataset::{DatasetRequest, DatasetResponse}, fixtures::{datasets::basic_gpkg, init_table, layer_schema, TatTestStructure}};
+
+    use rstest::*;
+
+    #[rstest]
+    fn test_to_json(layer_schema: TatLayerSchema) {
+        let report = TatDatasetReport {
+            uri: "test.gpkg".to_string(),
+            driver_short_name: "GPKG".to_string(),
+            driver_long_name: "GeoPackage".to_string(),
+            layers: vec![
+                TatLayerReport {
+                    schema: layer_schema,
+                    extent: Some([0.0, 1.0, 2.0, 3.0]),
+                }
+            ],
+        };
+
+        let json = report.to_json();
+        assert_eq!(json["driver"]["short_name"], "GPKG");
+        assert_eq!(json["layers"][0]["extent"], json!([0.0, 1.0, 2.0, 3.0]));
+        assert_eq!(json["layers"][0]["geometry_fields"][1]["name"], "geom2");
+        assert_eq!(json["layers"][0]["geometry_fields"][1]["type"], "Point");
+        assert_eq!(json["layers"][0]["fields"][2]["type"], "Real");
+
+        let text = report.text();
+        assert!(text.contains("- Driver: GeoPackage (GPKG)\n- Layers: 1\n"));
+        assert!(text.contains("- Extent: (0, 1) - (2, 3)\n"));
+    }
+
+    #[rstest]
+    fn test_dataset_report(basic_gpkg: TatTestStructure) {
+        let (test, t) = init_table(basic_gpkg);
+
+        test.ds_request_tx.send(DatasetRequest::DatasetReport).unwrap();
+
+        let report = loop {
+            if let TatEvent::Dataset(DatasetResponse::DatasetReport(report)) = test.tatevent_rx.recv().unwrap() {
+                break report;
+            }
+        };
+
+        assert_eq!(report.driver_short_name, "GPKG");
+        assert_eq!(report.layers.len(), t.layer_schemas().len());
+        assert_eq!(report.layers[4].schema.name(), t.layer_schemas()[4].name());
+        assert_eq!(report.layers[4].extent, None);
+        assert!(report.layers[0].extent.is_some());
+
+        test.terminate();
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+/// A cell as (row, column), the column being an index which includes the geometry fields
+pub type TatPosition = (u64, u64);
+
+/// The number of positions kept in the jump list, the oldest ones are dropped first
+const MAX_JUMPS: usize = 100;
+
+/// Named positions and a list of the positions jumped from within a layer, like the marks and the
+/// jump list in Vim
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TatJumps {
+    marks: HashMap<char, TatPosition>,
+    jump_list: Vec<TatPosition>,
+    /// The position in the jump list, equal to its length when not moving in the list
+    index: usize,
+}
+
+impl TatJumps {
+    /// Sets the mark with the given name to the position
+    pub fn set_mark(&mut self, name: char, position: TatPosition) {
+        self.marks.insert(name, position);
+    }
+
+    /// Returns the position of the mark with the given name (if it has been set)
+    pub fn mark(&self, name: char) -> Option<TatPosition> {
+        self.marks.get(&name).copied()
+    }
+
+    /// Adds the position which is jumped from to the end of the jump list. An earlier entry of
+    /// the same position is removed.
+    pub fn push(&mut self, position: TatPosition) {
+        self.jump_list.retain(|p| *p != position);
+        self.jump_list.push(position);
+
+        if self.jump_list.len() > MAX_JUMPS {
+            self.jump_list.remove(0);
+        }
+
+        self.index = self.jump_list.len();
+    }
+
+    /// Moves back in the jump list and returns the position to jump to. The current position is
+    /// added to the list first if not already moving in it, so that it can be jumped back to.
+    pub fn back(&mut self, current: TatPosition) -> Option<TatPosition> {
+        if self.index >= self.jump_list.len() {
+            if self.jump_list.is_empty() {
+                return None;
+            }
+
+            self.push(current);
+            self.index = self.jump_list.len() - 1;
+        }
+
+        let target = self.jump_list[..self.index].iter().rposition(|p| *p != current)?;
+        self.index = target;
+
+        Some(self.jump_list[target])
+    }
+
+    /// Moves forward in the jump list and returns the position to jump to
+    pub fn forward(&mut self, current: TatPosition) -> Option<TatPosition> {
+        let target = (self.index + 1..self.jump_list.len()).find(|i| self.jump_list[*i] != current)?;
+        self.index = target;
+
+        Some(self.jump_list[target])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[allow(unused)]
+    use super::*;
+
+    #[test]
+    fn test_marks() {
+        let mut jumps = TatJumps::default();
+        assert_eq!(jumps.mark('a'), None);
+
+        jumps.set_mark('a', (5, 1));
+        jumps.set_mark('b', (7, 0));
+        jumps.set_mark('a', (6, 2));
+        assert_eq!(jumps.mark('a'), Some((6, 2)));
+        assert_eq!(jumps.mark('b'), Some((7, 0)));
+    }
+
+    #[test]
+    fn test_jump_list() {
+        let mut jumps = TatJumps::default();
+        assert_eq!(jumps.back((1, 0)), None);
+        assert_eq!(jumps.forward((1, 0)), None);
+
+        // 1 -> 60 -> 30 -> 10
+        jumps.push((1, 0));
+        jumps.push((60, 0));
+        jumps.push((30, 2));
+
+        assert_eq!(jumps.back((10, 0)), Some((30, 2)));
+        assert_eq!(jumps.back((30, 2)), Some((60, 0)));
+        assert_eq!(jumps.back((60, 0)), Some((1, 0)));
+        assert_eq!(jumps.back((1, 0)), None);
+
+        assert_eq!(jumps.forward((1, 0)), Some((60, 0)));
+        assert_eq!(jumps.forward((60, 0)), Some((30, 2)));
+        assert_eq!(jumps.forward((30, 2)), Some((10, 0)));
+        assert_eq!(jumps.forward((10, 0)), None);
+
+        // jumping from the middle of the list moves the position to its end
+        jumps.back((10, 0));
+        jumps.back((30, 2));
+        jumps.push((60, 0));
+        assert_eq!(jumps.jump_list, vec![(1, 0), (30, 2), (10, 0), (60, 0)]);
+        assert_eq!(jumps.forward((5, 0)), None);
+        assert_eq!(jumps.back((5, 0)), Some((60, 0)));
+
+        for row in 0..200 {
+            jumps.push((row, 0));
+        }
+        assert_eq!(jumps.jump_list.len(), MAX_JUMPS);
+        assert_eq!(jumps.jump_list[0], (100, 0));
+    }
+}
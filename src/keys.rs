@@ -0,0 +1,839 @@
+use std::{fmt::Display, str::FromStr};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// The keys which have a name in the configuration file, single characters are written as is
+const KEY_NAMES: [(&str, KeyCode); 14] = [
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("enter", KeyCode::Enter),
+    ("tab", KeyCode::Tab),
+    ("shift+tab", KeyCode::BackTab),
+    ("esc", KeyCode::Esc),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("backspace", KeyCode::Backspace),
+    ("space", KeyCode::Char(' ')),
+];
+
+/// A key, optionally pressed together with the control key
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TatKey {
+    pub code: KeyCode,
+    pub ctrl: bool,
+}
+
+impl TatKey {
+    pub const fn new(code: KeyCode) -> Self {
+        Self { code, ctrl: false }
+    }
+
+    pub const fn char(ch: char) -> Self {
+        Self::new(KeyCode::Char(ch))
+    }
+
+    pub const fn ctrl(ch: char) -> Self {
+        Self { code: KeyCode::Char(ch), ctrl: true }
+    }
+
+    /// Returns whether the key event is a press of this key. Shift is not compared since it is
+    /// already part of the character.
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        self.code == event.code && self.ctrl == event.modifiers.contains(KeyModifiers::CONTROL)
+    }
+
+    /// Returns the key as it is shown in the help text e.g. 'q' or CTRL + Q
+    pub fn label(&self) -> String {
+        let label = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(ch) if self.ctrl => return format!("CTRL + {}", ch.to_uppercase()),
+            KeyCode::Char(ch) => format!("'{ch}'"),
+            KeyCode::Left => "\u{ea9b}".to_string(),
+            KeyCode::Down => "\u{ea9a}".to_string(),
+            KeyCode::Up => "\u{eaa1}".to_string(),
+            KeyCode::Right => "\u{ea9c}".to_string(),
+            KeyCode::BackTab => "SHIFT+Tab".to_string(),
+            KeyCode::F(n) => format!("F{n}"),
+            code => format!("{code:?}"),
+        };
+
+        if self.ctrl { format!("CTRL + {label}") } else { label }
+    }
+
+    /// Returns the key as it is shown in the hints of the pop-up views e.g. q, Enter or Ctrl+C
+    pub fn hint_label(&self) -> String {
+        let label = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(ch) if self.ctrl => ch.to_uppercase().to_string(),
+            KeyCode::Char(ch) => ch.to_string(),
+            KeyCode::BackTab => "Shift+Tab".to_string(),
+            KeyCode::F(n) => format!("F{n}"),
+            code => format!("{code:?}"),
+        };
+
+        if self.ctrl { format!("Ctrl+{label}") } else { label }
+    }
+}
+
+impl Display for TatKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.ctrl {
+            write!(f, "ctrl+")?;
+        }
+
+        match self.code {
+            KeyCode::F(n) => write!(f, "f{n}"),
+            code => {
+                match KEY_NAMES.iter().find(|(_, named)| *named == code) {
+                    Some((name, _)) => write!(f, "{name}"),
+                    None => match code {
+                        KeyCode::Char(ch) => write!(f, "{ch}"),
+                        _ => write!(f, "{}", format!("{code:?}").to_lowercase()),
+                    },
+                }
+            },
+        }
+    }
+}
+
+impl FromStr for TatKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || format!(
+            "unknown key \"{}\", expected a single character, f1-f12 or one of: {} (optionally prefixed with ctrl+)",
+            s,
+            KEY_NAMES.map(|(name, _)| name).join(", "),
+        );
+
+        let (ctrl, name) = match s.get(..5) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("ctrl+") && s.len() > 5 => (true, &s[5..]),
+            _ => (false, s),
+        };
+
+        let mut chars = name.chars();
+        let code = match (chars.next(), chars.next()) {
+            // the terminal reports CTRL + Q as a lowercase q
+            (Some(ch), None) if ctrl => KeyCode::Char(ch.to_ascii_lowercase()),
+            (Some(ch), None) => KeyCode::Char(ch),
+            _ => {
+                let name = name.to_lowercase();
+
+                match KEY_NAMES.iter().find(|(key_name, _)| *key_name == name) {
+                    Some((_, code)) => *code,
+                    None => {
+                        let n = name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()).ok_or_else(error)?;
+
+                        if !(1..=12).contains(&n) {
+                            return Err(error());
+                        }
+
+                        KeyCode::F(n)
+                    },
+                }
+            },
+        };
+
+        Ok(Self { code, ctrl })
+    }
+}
+
+/// The menus in which keys are handled, an action is only triggered in the menus it belongs to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TatKeyContext {
+    MainMenu,
+    Table,
+}
+
+/// Everything which can be done with a key in the main menu or the table
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TatAction {
+    Left,
+    Down,
+    Up,
+    Right,
+    FirstColumn,
+    LastColumn,
+    ScrollTop,
+    ScrollBottom,
+    HalfPageDown,
+    HalfPageUp,
+    PageDown,
+    PageUp,
+    OpenTable,
+    NextSection,
+    PreviousSection,
+    PreviousMenu,
+    Quit,
+    Help,
+    GdalLog,
+    DebugLog,
+    ToggleMap,
+    CrsDetails,
+    Export,
+    CommandLine,
+    ToggleFids,
+    ShowValue,
+    CopyValue,
+    CopyFeatures,
+    ToggleMark,
+    ToggleVisual,
+    ClearSelection,
+    Compare,
+    JumpToColumn,
+    ShowRecord,
+    HideColumn,
+    ShowColumns,
+    MoveColumnLeft,
+    MoveColumnRight,
+    PinColumn,
+    FitColumns,
+    NarrowColumn,
+    WidenColumn,
+    ResetColumns,
+    Search,
+    NextMatch,
+    PreviousMatch,
+    SetMark,
+    JumpToMark,
+    JumpBack,
+    JumpForward,
+}
+
+impl TatAction {
+    pub const ALL: [TatAction; 50] = [
+        TatAction::Left,
+        TatAction::Down,
+        TatAction::Up,
+        TatAction::Right,
+        TatAction::FirstColumn,
+        TatAction::LastColumn,
+        TatAction::ScrollTop,
+        TatAction::ScrollBottom,
+        TatAction::HalfPageDown,
+        TatAction::HalfPageUp,
+        TatAction::PageDown,
+        TatAction::PageUp,
+        TatAction::OpenTable,
+        TatAction::NextSection,
+        TatAction::PreviousSection,
+        TatAction::PreviousMenu,
+        TatAction::Quit,
+        TatAction::Help,
+        TatAction::GdalLog,
+        TatAction::DebugLog,
+        TatAction::ToggleMap,
+        TatAction::CrsDetails,
+        TatAction::Export,
+        TatAction::CommandLine,
+        TatAction::ToggleFids,
+        TatAction::ShowValue,
+        TatAction::CopyValue,
+        TatAction::CopyFeatures,
+        TatAction::ToggleMark,
+        TatAction::ToggleVisual,
+        TatAction::ClearSelection,
+        TatAction::Compare,
+        TatAction::JumpToColumn,
+        TatAction::ShowRecord,
+        TatAction::HideColumn,
+        TatAction::ShowColumns,
+        TatAction::MoveColumnLeft,
+        TatAction::MoveColumnRight,
+        TatAction::PinColumn,
+        TatAction::FitColumns,
+        TatAction::NarrowColumn,
+        TatAction::WidenColumn,
+        TatAction::ResetColumns,
+        TatAction::Search,
+        TatAction::NextMatch,
+        TatAction::PreviousMatch,
+        TatAction::SetMark,
+        TatAction::JumpToMark,
+        TatAction::JumpBack,
+        TatAction::JumpForward,
+    ];
+
+    /// The name of the action in the configuration file
+    pub fn name(&self) -> &'static str {
+        match self {
+            TatAction::Left => "left",
+            TatAction::Down => "down",
+            TatAction::Up => "up",
+            TatAction::Right => "right",
+            TatAction::FirstColumn => "first_column",
+            TatAction::LastColumn => "last_column",
+            TatAction::ScrollTop => "scroll_top",
+            TatAction::ScrollBottom => "scroll_bottom",
+            TatAction::HalfPageDown => "half_page_down",
+            TatAction::HalfPageUp => "half_page_up",
+            TatAction::PageDown => "page_down",
+            TatAction::PageUp => "page_up",
+            TatAction::OpenTable => "open_table",
+            TatAction::NextSection => "next_section",
+            TatAction::PreviousSection => "previous_section",
+            TatAction::PreviousMenu => "previous_menu",
+            TatAction::Quit => "quit",
+            TatAction::Help => "help",
+            TatAction::GdalLog => "gdal_log",
+            TatAction::DebugLog => "debug_log",
+            TatAction::ToggleMap => "toggle_map",
+            TatAction::CrsDetails => "crs_details",
+            TatAction::Export => "export",
+            TatAction::CommandLine => "command_line",
+            TatAction::ToggleFids => "toggle_fids",
+            TatAction::ShowValue => "show_value",
+            TatAction::CopyValue => "copy_value",
+            TatAction::CopyFeatures => "copy_features",
+            TatAction::ToggleMark => "toggle_mark",
+            TatAction::ToggleVisual => "toggle_visual",
+            TatAction::ClearSelection => "clear_selection",
+            TatAction::Compare => "compare",
+            TatAction::JumpToColumn => "jump_to_column",
+            TatAction::ShowRecord => "show_record",
+            TatAction::HideColumn => "hide_column",
+            TatAction::ShowColumns => "show_columns",
+            TatAction::MoveColumnLeft => "move_column_left",
+            TatAction::MoveColumnRight => "move_column_right",
+            TatAction::PinColumn => "pin_column",
+            TatAction::FitColumns => "fit_columns",
+            TatAction::NarrowColumn => "narrow_column",
+            TatAction::WidenColumn => "widen_column",
+            TatAction::ResetColumns => "reset_columns",
+            TatAction::Search => "search",
+            TatAction::NextMatch => "next_match",
+            TatAction::PreviousMatch => "previous_match",
+            TatAction::SetMark => "set_mark",
+            TatAction::JumpToMark => "jump_to_mark",
+            TatAction::JumpBack => "jump_back",
+            TatAction::JumpForward => "jump_forward",
+        }
+    }
+
+    /// The keys bound to the action unless configured otherwise
+    pub fn default_keys(&self) -> Vec<TatKey> {
+        match self {
+            TatAction::Left => vec![TatKey::new(KeyCode::Left), TatKey::char('h')],
+            TatAction::Down => vec![TatKey::new(KeyCode::Down), TatKey::char('j')],
+            TatAction::Up => vec![TatKey::new(KeyCode::Up), TatKey::char('k')],
+            TatAction::Right => vec![TatKey::new(KeyCode::Right), TatKey::char('l')],
+            TatAction::FirstColumn => vec![TatKey::new(KeyCode::Home), TatKey::char('0')],
+            TatAction::LastColumn => vec![TatKey::new(KeyCode::End), TatKey::char('$')],
+            TatAction::ScrollTop => vec![TatKey::char('g')],
+            TatAction::ScrollBottom => vec![TatKey::char('G')],
+            TatAction::HalfPageDown => vec![TatKey::ctrl('d')],
+            TatAction::HalfPageUp => vec![TatKey::ctrl('u')],
+            TatAction::PageDown => vec![TatKey::ctrl('f'), TatKey::new(KeyCode::PageDown)],
+            TatAction::PageUp => vec![TatKey::ctrl('b'), TatKey::new(KeyCode::PageUp)],
+            TatAction::OpenTable => vec![TatKey::new(KeyCode::Enter)],
+            TatAction::NextSection => vec![TatKey::new(KeyCode::Tab)],
+            TatAction::PreviousSection => vec![TatKey::new(KeyCode::BackTab)],
+            TatAction::PreviousMenu => vec![TatKey::char('q')],
+            TatAction::Quit => vec![TatKey::ctrl('q')],
+            TatAction::Help => vec![TatKey::char('?')],
+            TatAction::GdalLog => vec![TatKey::char('L')],
+            TatAction::DebugLog => vec![TatKey::char('D')],
+            TatAction::ToggleMap => vec![TatKey::char('M')],
+            TatAction::CrsDetails => vec![TatKey::char('C')],
+            TatAction::Export => vec![TatKey::char('E')],
+            TatAction::CommandLine => vec![TatKey::char(':')],
+            TatAction::ToggleFids => vec![TatKey::char('F')],
+            TatAction::ShowValue => vec![TatKey::new(KeyCode::Enter)],
+            TatAction::CopyValue => vec![TatKey::char('y'), TatKey::ctrl('c')],
+            TatAction::CopyFeatures => vec![TatKey::char('Y')],
+            TatAction::ToggleMark => vec![TatKey::char(' ')],
+            TatAction::ToggleVisual => vec![TatKey::char('v')],
+            TatAction::ClearSelection => vec![TatKey::char('U')],
+            TatAction::Compare => vec![TatKey::char('X')],
+            TatAction::JumpToColumn => vec![TatKey::char('f')],
+            TatAction::ShowRecord => vec![TatKey::char('R')],
+            TatAction::HideColumn => vec![TatKey::char('-')],
+            TatAction::ShowColumns => vec![TatKey::char('+')],
+            TatAction::MoveColumnLeft => vec![TatKey::char('<')],
+            TatAction::MoveColumnRight => vec![TatKey::char('>')],
+            TatAction::PinColumn => vec![TatKey::char('P')],
+            TatAction::FitColumns => vec![TatKey::char('W')],
+            TatAction::NarrowColumn => vec![TatKey::char('[')],
+            TatAction::WidenColumn => vec![TatKey::char(']')],
+            TatAction::ResetColumns => vec![TatKey::char('=')],
+            TatAction::Search => vec![TatKey::char('/')],
+            TatAction::NextMatch => vec![TatKey::char('n')],
+            TatAction::PreviousMatch => vec![TatKey::char('N')],
+            TatAction::SetMark => vec![TatKey::char('m')],
+            TatAction::JumpToMark => vec![TatKey::char('\'')],
+            TatAction::JumpBack => vec![TatKey::ctrl('o')],
+            // most terminals send Tab for Ctrl+I
+            TatAction::JumpForward => vec![TatKey::ctrl('i'), TatKey::new(KeyCode::Tab)],
+        }
+    }
+
+    /// What the action does, as shown in the help text
+    pub fn description(&self) -> &'static str {
+        match self {
+            TatAction::Left => "Left",
+            TatAction::Down => "Down",
+            TatAction::Up => "Up",
+            TatAction::Right => "Right",
+            TatAction::FirstColumn => "Scroll to First Column",
+            TatAction::LastColumn => "Scroll to Last Column",
+            TatAction::ScrollTop => "Scroll to Top",
+            TatAction::ScrollBottom => "Scroll to Bottom",
+            TatAction::HalfPageDown => "Scroll Down (half page)",
+            TatAction::HalfPageUp => "Scroll Up (half page)",
+            TatAction::PageDown => "Scroll Down (full page)",
+            TatAction::PageUp => "Scroll Up (full page)",
+            TatAction::OpenTable => "Open Table",
+            TatAction::NextSection => "Move to Next Section",
+            TatAction::PreviousSection => "Move to Previous Section",
+            TatAction::PreviousMenu => "Previous Menu",
+            TatAction::Quit => "Quit",
+            TatAction::Help => "Show Help",
+            TatAction::GdalLog => "Open GDAL Log",
+            TatAction::DebugLog => "Open Debug Log",
+            TatAction::ToggleMap => "Toggle Map Panel",
+            TatAction::CrsDetails => "Show CRS Details of Selected Layer",
+            TatAction::Export => "Export Selected Layer or All Layers",
+            TatAction::CommandLine => "Open Command Line (e.g. :123 to jump to a row, :filter, :sort, :goto-fid)",
+            TatAction::ToggleFids => "Toggle Showing FIDs Instead of Row Numbers",
+            TatAction::ShowValue => "Display Selected Value in Pop-Up",
+            TatAction::CopyValue => "Copy Selected Value to Clipboard",
+            TatAction::CopyFeatures => "Copy Selected Feature(s) as GeoJSON, CSV or JSON",
+            TatAction::ToggleMark => "Mark or Unmark Current Feature",
+            TatAction::ToggleVisual => "Start or End Visual Selection",
+            TatAction::ClearSelection => "Clear Selection",
+            TatAction::Compare => "Compare Marked Feature with Current One (or Two Marked Features)",
+            TatAction::JumpToColumn => "Jump to Column by Name",
+            TatAction::ShowRecord => "Show Current Feature as a Record (one row per field)",
+            TatAction::HideColumn => "Hide Column",
+            TatAction::ShowColumns => "Show Hidden Columns",
+            TatAction::MoveColumnLeft => "Move Column Left",
+            TatAction::MoveColumnRight => "Move Column Right",
+            TatAction::PinColumn => "Pin or Unpin Column",
+            TatAction::FitColumns => "Toggle Fitting Column Widths to Content",
+            TatAction::NarrowColumn => "Narrow Column",
+            TatAction::WidenColumn => "Widen Column",
+            TatAction::ResetColumns => "Reset Columns",
+            TatAction::Search => "Search Values (substring or regex)",
+            TatAction::NextMatch => "Jump to Next Match",
+            TatAction::PreviousMatch => "Jump to Previous Match",
+            TatAction::SetMark => "Mark Current Cell (followed by a letter a-z)",
+            TatAction::JumpToMark => "Jump to Marked Cell (followed by a letter a-z)",
+            TatAction::JumpBack => "Jump Back to Where the Previous Jump Started",
+            TatAction::JumpForward => "Jump Forward (undo Jump Back)",
+        }
+    }
+
+    /// Returns whether the action can be triggered in the given menu
+    pub fn available_in(&self, context: TatKeyContext) -> bool {
+        match self {
+            TatAction::OpenTable
+            | TatAction::NextSection
+            | TatAction::PreviousSection => context == TatKeyContext::MainMenu,
+            TatAction::CommandLine
+            | TatAction::ToggleFids
+            | TatAction::ShowValue
+            | TatAction::CopyValue
+            | TatAction::CopyFeatures
+            | TatAction::ToggleMark
+            | TatAction::ToggleVisual
+            | TatAction::ClearSelection
+            | TatAction::Compare
+            | TatAction::JumpToColumn
+            | TatAction::ShowRecord
+            | TatAction::HideColumn
+            | TatAction::ShowColumns
+            | TatAction::MoveColumnLeft
+            | TatAction::MoveColumnRight
+            | TatAction::PinColumn
+            | TatAction::FitColumns
+            | TatAction::NarrowColumn
+            | TatAction::WidenColumn
+            | TatAction::ResetColumns
+            | TatAction::Search
+            | TatAction::NextMatch
+            | TatAction::PreviousMatch
+            | TatAction::SetMark
+            | TatAction::JumpToMark
+            | TatAction::JumpBack
+            | TatAction::JumpForward => context == TatKeyContext::Table,
+            _ => true,
+        }
+    }
+}
+
+impl FromStr for TatAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TatAction::ALL.into_iter()
+            .find(|action| action.name() == s)
+            .ok_or(format!("unknown action \"{s}\""))
+    }
+}
+
+/// A line in the help text
+enum TatHelpLine {
+    /// The keys of the action followed by its description
+    Action(TatAction),
+    /// A template in which {name} is replaced by the keys of the action with that name, followed
+    /// by the description. Used to describe related actions on one line.
+    Combined(&'static str, &'static str),
+}
+
+type TatHelpSection = (&'static str, &'static [TatHelpLine]);
+
+const HELP_MAINMENU: [TatHelpSection; 4] = [
+    ("Basic Navigation", &[
+        TatHelpLine::Action(TatAction::Left),
+        TatHelpLine::Action(TatAction::Down),
+        TatHelpLine::Action(TatAction::Up),
+        TatHelpLine::Action(TatAction::Right),
+        TatHelpLine::Action(TatAction::OpenTable),
+        TatHelpLine::Combined("{next_section} and {previous_section}", "Move between sections"),
+    ]),
+    ("General", &[
+        TatHelpLine::Action(TatAction::PreviousMenu),
+        TatHelpLine::Combined("{quit} or {previous_menu} when in Main Menu", "Quit"),
+        TatHelpLine::Action(TatAction::Help),
+    ]),
+    ("Advanced Navigation", &[
+        TatHelpLine::Action(TatAction::ScrollTop),
+        TatHelpLine::Action(TatAction::ScrollBottom),
+        TatHelpLine::Action(TatAction::HalfPageDown),
+        TatHelpLine::Action(TatAction::HalfPageUp),
+        TatHelpLine::Action(TatAction::PageDown),
+        TatHelpLine::Action(TatAction::PageUp),
+    ]),
+    ("Miscellaneous", &[
+        TatHelpLine::Action(TatAction::GdalLog),
+        TatHelpLine::Action(TatAction::ToggleMap),
+        TatHelpLine::Action(TatAction::CrsDetails),
+        TatHelpLine::Action(TatAction::Export),
+    ]),
+];
+
+const HELP_TABLE: [TatHelpSection; 5] = [
+    ("Basic Navigation", &[
+        TatHelpLine::Action(TatAction::Left),
+        TatHelpLine::Action(TatAction::Down),
+        TatHelpLine::Action(TatAction::Up),
+        TatHelpLine::Action(TatAction::Right),
+    ]),
+    ("Table", &[
+        TatHelpLine::Action(TatAction::CommandLine),
+        TatHelpLine::Action(TatAction::ToggleFids),
+        TatHelpLine::Action(TatAction::ShowValue),
+        TatHelpLine::Action(TatAction::CopyValue),
+        TatHelpLine::Action(TatAction::CopyFeatures),
+        TatHelpLine::Action(TatAction::ToggleMark),
+        TatHelpLine::Action(TatAction::ToggleVisual),
+        TatHelpLine::Action(TatAction::ClearSelection),
+        TatHelpLine::Action(TatAction::Compare),
+        TatHelpLine::Action(TatAction::JumpToColumn),
+        TatHelpLine::Action(TatAction::ShowRecord),
+        TatHelpLine::Action(TatAction::HideColumn),
+        TatHelpLine::Action(TatAction::ShowColumns),
+        TatHelpLine::Combined("{move_column_left} / {move_column_right}", "Move Column Left / Right"),
+        TatHelpLine::Action(TatAction::PinColumn),
+        TatHelpLine::Action(TatAction::FitColumns),
+        TatHelpLine::Combined("{narrow_column} / {widen_column}", "Narrow / Widen Column"),
+        TatHelpLine::Action(TatAction::ResetColumns),
+        TatHelpLine::Action(TatAction::Search),
+        TatHelpLine::Combined("{next_match} / {previous_match}", "Jump to Next / Previous Match"),
+        TatHelpLine::Combined("{set_mark} + a-z / {jump_to_mark} + a-z", "Mark Current Cell / Jump to Marked Cell"),
+        TatHelpLine::Combined("{jump_back} / {jump_forward}", "Jump Back / Forward in Jump List (top, bottom, row, mark and match jumps)"),
+    ]),
+    ("General", &[
+        TatHelpLine::Action(TatAction::PreviousMenu),
+        TatHelpLine::Action(TatAction::Quit),
+        TatHelpLine::Action(TatAction::Help),
+    ]),
+    ("Advanced Navigation", &[
+        TatHelpLine::Action(TatAction::FirstColumn),
+        TatHelpLine::Action(TatAction::LastColumn),
+        TatHelpLine::Action(TatAction::ScrollTop),
+        TatHelpLine::Action(TatAction::ScrollBottom),
+        TatHelpLine::Action(TatAction::HalfPageDown),
+        TatHelpLine::Action(TatAction::HalfPageUp),
+        TatHelpLine::Action(TatAction::PageDown),
+        TatHelpLine::Action(TatAction::PageUp),
+    ]),
+    ("Miscellaneous", &[
+        TatHelpLine::Action(TatAction::GdalLog),
+        TatHelpLine::Action(TatAction::ToggleMap),
+        TatHelpLine::Action(TatAction::CrsDetails),
+        TatHelpLine::Action(TatAction::Export),
+    ]),
+];
+
+/// The keys bound to each action
+#[derive(Debug, Clone, PartialEq)]
+pub struct TatKeymap {
+    bindings: Vec<(TatAction, Vec<TatKey>)>,
+}
+
+impl Default for TatKeymap {
+    fn default() -> Self {
+        Self {
+            bindings: TatAction::ALL.iter().map(|action| (*action, action.default_keys())).collect(),
+        }
+    }
+}
+
+impl TatKeymap {
+    /// Returns the keys bound to the action, empty if the action has been unbound
+    pub fn keys(&self, action: TatAction) -> &[TatKey] {
+        self.bindings.iter()
+            .find(|(bound_action, _)| *bound_action == action)
+            .map(|(_, keys)| keys.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Replaces the keys bound to the action
+    pub fn bind(&mut self, action: TatAction, keys: Vec<TatKey>) {
+        if let Some((_, bound_keys)) = self.bindings.iter_mut().find(|(bound_action, _)| *bound_action == action) {
+            *bound_keys = keys;
+        }
+    }
+
+    /// Returns the action the key event triggers in the given menu
+    pub fn action(&self, event: &KeyEvent, context: TatKeyContext) -> Option<TatAction> {
+        self.bindings.iter()
+            .filter(|(action, _)| action.available_in(context))
+            .find(|(_, keys)| keys.iter().any(|key| key.matches(event)))
+            .map(|(action, _)| *action)
+    }
+
+    /// Returns the first key which is bound to two actions in the same menu, and the actions
+    pub fn conflict(&self) -> Option<(TatKey, TatAction, TatAction)> {
+        for (i, (action, keys)) in self.bindings.iter().enumerate() {
+            for (other_action, other_keys) in &self.bindings[i + 1..] {
+                let shared_context = [TatKeyContext::MainMenu, TatKeyContext::Table].into_iter()
+                    .any(|context| action.available_in(context) && other_action.available_in(context));
+
+                if !shared_context {
+                    continue;
+                }
+
+                if let Some(key) = keys.iter().find(|key| other_keys.contains(key)) {
+                    return Some((*key, *action, *other_action));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Returns which of the given actions the key event triggers in a pop-up view. Esc always
+    /// closes the view (previous_menu) and Home and End move to the top and the bottom, whatever
+    /// the bindings are.
+    pub fn view_action(&self, event: &KeyEvent, actions: &[TatAction]) -> Option<TatAction> {
+        let fixed = match event.code {
+            KeyCode::Esc => Some(TatAction::PreviousMenu),
+            KeyCode::Home => Some(TatAction::ScrollTop),
+            KeyCode::End => Some(TatAction::ScrollBottom),
+            _ => None,
+        };
+
+        actions.iter()
+            .copied()
+            .find(|action| self.keys(*action).iter().any(|key| key.matches(event)))
+            .or(fixed.filter(|action| actions.contains(action)))
+    }
+
+    /// Returns the key of the action as it is shown in the hints of the pop-up views. Plain
+    /// characters are preferred since they are the shortest.
+    pub fn hint_key(&self, action: TatAction) -> String {
+        let keys = self.keys(action);
+
+        keys.iter()
+            .find(|key| !key.ctrl && matches!(key.code, KeyCode::Char(_)))
+            .or(keys.first())
+            .map_or("(unbound)".to_string(), |key| key.hint_label())
+    }
+
+    /// Returns a hint of a pop-up view listing the keys of the actions and what they do, e.g.
+    /// "j/k: scroll, q: close"
+    pub fn hint(&self, items: &[(&[TatAction], &str)]) -> String {
+        items.iter()
+            .map(|(actions, description)| {
+                let keys: Vec<String> = actions.iter().map(|action| self.hint_key(*action)).collect();
+
+                format!("{}: {}", keys.join("/"), description)
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// Returns the keys of the action as shown in the help text e.g. ← or 'h'
+    fn label(&self, action: TatAction) -> String {
+        let keys = self.keys(action);
+
+        if keys.is_empty() {
+            return "(unbound)".to_string();
+        }
+
+        keys.iter().map(|key| key.label()).collect::<Vec<String>>().join(" or ")
+    }
+
+    /// Returns the help text of the menu with the keys currently bound to the actions
+    pub fn help_text(&self, context: TatKeyContext) -> String {
+        let (title, sections, remarks) = match context {
+            TatKeyContext::MainMenu => ("Keybinds for Main Menu", HELP_MAINMENU.as_slice(), crate::shared::HELP_REMARKS_MAINMENU),
+            TatKeyContext::Table => ("Keybinds for Attribute Table", HELP_TABLE.as_slice(), crate::shared::HELP_REMARKS_TABLE),
+        };
+
+        let mut text = format!("{}\n{}\n", title, "-".repeat(title.len()));
+
+        for (section, lines) in sections {
+            text.push_str(&format!("{section}:\n"));
+
+            for line in lines.iter() {
+                let (keys, description) = match line {
+                    TatHelpLine::Action(action) => (self.label(*action), action.description()),
+                    TatHelpLine::Combined(template, description) => {
+                        let keys = TatAction::ALL.iter().fold(template.to_string(), |keys, action| {
+                            keys.replace(&format!("{{{}}}", action.name()), &self.label(*action))
+                        });
+
+                        (keys, *description)
+                    },
+                };
+
+                text.push_str(&format!("    {keys}: {description}\n"));
+            }
+
+            text.push('\n');
+        }
+
+        text.push_str("Remarks\n-------\n");
+        text.push_str(remarks);
+
+        text
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[allow(unused)]
+    use super::*;
+
+    use crossterm::event::{KeyEventKind, KeyEventState};
+
+    fn event(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent { code, modifiers, kind: KeyEventKind::Press, state: KeyEventState::NONE }
+    }
+
+    #[test]
+    fn test_key_from_str() {
+        assert_eq!("q".parse(), Ok(TatKey::char('q')));
+        assert_eq!("Q".parse(), Ok(TatKey::char('Q')));
+        assert_eq!("ctrl+Q".parse(), Ok(TatKey::ctrl('q')));
+        assert_eq!("PageDown".parse(), Ok(TatKey::new(KeyCode::PageDown)));
+        assert_eq!("ctrl+left".parse(), Ok(TatKey { code: KeyCode::Left, ctrl: true }));
+        assert_eq!("f5".parse(), Ok(TatKey::new(KeyCode::F(5))));
+        assert_eq!("+".parse(), Ok(TatKey::char('+')));
+        assert!("f13".parse::<TatKey>().is_err());
+        assert!("pgdn".parse::<TatKey>().is_err());
+
+        for action in TatAction::ALL {
+            for key in action.default_keys() {
+                assert_eq!(key.to_string().parse(), Ok(key));
+            }
+        }
+    }
+
+    #[test]
+    fn test_key_label() {
+        assert_eq!(TatKey::char('q').label(), "'q'");
+        assert_eq!(TatKey::ctrl('q').label(), "CTRL + Q");
+        assert_eq!(TatKey::new(KeyCode::BackTab).label(), "SHIFT+Tab");
+        assert_eq!(TatKey::char(' ').label(), "Space");
+    }
+
+    #[test]
+    fn test_action() {
+        let keymap = TatKeymap::default();
+        assert_eq!(keymap.conflict(), None);
+
+        assert_eq!(keymap.action(&event(KeyCode::Char('j'), KeyModifiers::NONE), TatKeyContext::Table), Some(TatAction::Down));
+        assert_eq!(keymap.action(&event(KeyCode::Char('G'), KeyModifiers::SHIFT), TatKeyContext::Table), Some(TatAction::ScrollBottom));
+        assert_eq!(keymap.action(&event(KeyCode::Char('f'), KeyModifiers::CONTROL), TatKeyContext::Table), Some(TatAction::PageDown));
+        assert_eq!(keymap.action(&event(KeyCode::Char('f'), KeyModifiers::NONE), TatKeyContext::Table), Some(TatAction::JumpToColumn));
+        assert_eq!(keymap.action(&event(KeyCode::Char('f'), KeyModifiers::NONE), TatKeyContext::MainMenu), None);
+        assert_eq!(keymap.action(&event(KeyCode::Enter, KeyModifiers::NONE), TatKeyContext::MainMenu), Some(TatAction::OpenTable));
+        assert_eq!(keymap.action(&event(KeyCode::Enter, KeyModifiers::NONE), TatKeyContext::Table), Some(TatAction::ShowValue));
+        assert_eq!(keymap.action(&event(KeyCode::Tab, KeyModifiers::NONE), TatKeyContext::MainMenu), Some(TatAction::NextSection));
+        assert_eq!(keymap.action(&event(KeyCode::Tab, KeyModifiers::NONE), TatKeyContext::Table), Some(TatAction::JumpForward));
+        assert_eq!(keymap.action(&event(KeyCode::Char('o'), KeyModifiers::CONTROL), TatKeyContext::Table), Some(TatAction::JumpBack));
+
+        let mut keymap = keymap;
+        keymap.bind(TatAction::Down, vec![TatKey::char('n')]);
+        assert_eq!(keymap.action(&event(KeyCode::Char('j'), KeyModifiers::NONE), TatKeyContext::Table), None);
+        assert_eq!(keymap.conflict(), Some((TatKey::char('n'), TatAction::Down, TatAction::NextMatch)));
+
+        // open_table and show_value are never available in the same menu
+        keymap.bind(TatAction::NextMatch, vec![]);
+        keymap.bind(TatAction::OpenTable, vec![TatKey::char('o')]);
+        keymap.bind(TatAction::ShowValue, vec![TatKey::char('o')]);
+        assert_eq!(keymap.conflict(), None);
+    }
+
+    #[test]
+    fn test_view_action() {
+        let mut keymap = TatKeymap::default();
+        let actions = [TatAction::PreviousMenu, TatAction::Down, TatAction::ScrollTop, TatAction::CopyValue];
+
+        assert_eq!(keymap.view_action(&event(KeyCode::Char('j'), KeyModifiers::NONE), &actions), Some(TatAction::Down));
+        assert_eq!(keymap.view_action(&event(KeyCode::Char('c'), KeyModifiers::CONTROL), &actions), Some(TatAction::CopyValue));
+        assert_eq!(keymap.view_action(&event(KeyCode::Char('k'), KeyModifiers::NONE), &actions), None);
+        assert_eq!(keymap.view_action(&event(KeyCode::Home, KeyModifiers::NONE), &actions), Some(TatAction::ScrollTop));
+        assert_eq!(keymap.view_action(&event(KeyCode::End, KeyModifiers::NONE), &actions), None);
+
+        keymap.bind(TatAction::Down, vec![TatKey::char('s')]);
+        keymap.bind(TatAction::PreviousMenu, vec![TatKey::char('x')]);
+        assert_eq!(keymap.view_action(&event(KeyCode::Char('j'), KeyModifiers::NONE), &actions), None);
+        assert_eq!(keymap.view_action(&event(KeyCode::Char('s'), KeyModifiers::NONE), &actions), Some(TatAction::Down));
+        assert_eq!(keymap.view_action(&event(KeyCode::Esc, KeyModifiers::NONE), &actions), Some(TatAction::PreviousMenu));
+    }
+
+    #[test]
+    fn test_hint() {
+        let mut keymap = TatKeymap::default();
+        assert_eq!(
+            keymap.hint(&[(&[TatAction::Down, TatAction::Up], "scroll"), (&[TatAction::ShowValue], "full value"), (&[TatAction::PreviousMenu], "close")]),
+            "j/k: scroll, Enter: full value, q: close",
+        );
+
+        keymap.bind(TatAction::Down, vec![TatKey::new(KeyCode::Down), TatKey::ctrl('n')]);
+        keymap.bind(TatAction::Up, vec![]);
+        assert_eq!(keymap.hint(&[(&[TatAction::Down, TatAction::Up], "scroll")]), "Down/(unbound): scroll");
+        assert_eq!(TatKey::ctrl('n').hint_label(), "Ctrl+N");
+        assert_eq!(TatKey::char(' ').hint_label(), "Space");
+    }
+
+    #[test]
+    fn test_help_text() {
+        let mut keymap = TatKeymap::default();
+
+        let text = keymap.help_text(TatKeyContext::MainMenu);
+        assert!(text.starts_with("Keybinds for Main Menu\n----------------------\nBasic Navigation:\n"));
+        assert!(text.contains("\n    CTRL + F or PageDown: Scroll Down (full page)\n"));
+        assert!(text.contains("\n    Tab and SHIFT+Tab: Move between sections\n"));
+        assert!(text.contains("\n    CTRL + Q or 'q' when in Main Menu: Quit\n"));
+        assert!(text.ends_with(crate::shared::HELP_REMARKS_MAINMENU));
+
+        keymap.bind(TatAction::Down, vec![TatKey::char('n')]);
+        keymap.bind(TatAction::Search, vec![]);
+
+        let text = keymap.help_text(TatKeyContext::Table);
+        assert!(text.starts_with("Keybinds for Attribute Table\n"));
+        assert!(text.contains("\n    'n': Down\n"));
+        assert!(text.contains("\n    (unbound): Search Values (substring or regex)\n"));
+        assert!(text.contains("\n    '[' / ']': Narrow / Widen Column\n"));
+    }
+}
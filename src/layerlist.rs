@@ -1,4 +1,4 @@
-use ratatui::{layout::{Constraint, Layout, Margin}, style::Style, symbols::{self, scrollbar::DOUBLE_VERTICAL}, text::Line, widgets::{Block, List, ListItem, ListState, Scrollbar, ScrollbarOrientation, ScrollbarState}, Frame};
+use ratatui::{layout::{Constraint, Layout, Margin}, symbols::{self, scrollbar::DOUBLE_VERTICAL}, text::Line, widgets::{Block, List, ListItem, ListState, Scrollbar, ScrollbarOrientation, ScrollbarState}, Frame};
 use ratatui::widgets::HighlightSpacing;
 use ratatui::prelude::Stylize;
 use std::sync::mpsc::Sender;
@@ -83,6 +83,10 @@ impl TatLayerList {
             TatNavVertical::MouseScrollDown => self.state.scroll_down_by(self.available_rows as u16 / 3),
             TatNavVertical::MouseScrollUp => self.state.scroll_up_by(self.available_rows as u16 / 3),
             TatNavVertical::Specific(row) => self.state.select(Some(row as usize)),
+            TatNavVertical::Fraction(fraction) => {
+                let last = self.layer_infos.len().saturating_sub(1);
+                self.state.select(Some((last as f64 * fraction.clamp(0.0, 1.0)).round() as usize));
+            },
         }
 
         self.update_scrollbar();
@@ -103,19 +107,33 @@ impl TatLayerList {
         Some(i)
     }
 
+    /// Selects the layer on the given row of the list, counted from the first visible row.
+    /// Returns whether there was a layer on the row.
+    pub fn select_visible_row(&mut self, row: usize) -> bool {
+        let i = self.state.offset() + row;
+
+        if row >= self.available_rows || i >= self.layer_infos.len() {
+            return false;
+        }
+
+        self.state.select(Some(i));
+        self.update_scrollbar();
+
+        true
+    }
+
     /// Renders the current state of the widget
     pub fn render(&mut self, area: ratatui::prelude::Rect, frame: &mut Frame, highlight: bool) {
-        let border_color = if highlight {
-            crate::shared::palette::DEFAULT.highlighted_fg
+        let border_style = if highlight {
+            crate::shared::palette::current().highlighted_style()
         } else {
-            crate::shared::palette::DEFAULT.default_fg
+            crate::shared::palette::current().default_style()
         };
 
         let block = Block::bordered()
             .title(Line::raw(crate::shared::TITLE_LAYER_LIST).underlined().bold())
             .border_set(BORDER_LAYER_LIST)
-            .border_style(Style::default().fg(border_color)
-        );
+            .border_style(border_style);
 
         let mut items: Vec<ListItem> = vec![];
 
@@ -130,7 +148,7 @@ impl TatLayerList {
 
         let list = List::new(items)
             .block(block)
-            .highlight_style(crate::shared::palette::DEFAULT.selected_style())
+            .highlight_style(crate::shared::palette::current().selected_style())
             .highlight_spacing(HighlightSpacing::WhenSelected);
 
 
@@ -177,6 +195,11 @@ impl TatLayerList {
         self.available_rows = available_rows;
     }
 
+    /// Returns the number of layers in the list
+    pub fn layer_count(&self) -> usize {
+        self.layer_infos.len()
+    }
+
     pub fn available_rows(&self) -> usize {
         self.available_rows
     }
@@ -240,5 +263,23 @@ mod test {
 
         ll.nav(TatNavVertical::Specific(2));
         assert_eq!(ll.layer_index(), Some(2));
+
+        ll.nav(TatNavVertical::Fraction(1.0));
+        assert_eq!(ll.layer_index(), Some(4));
+        ll.nav(TatNavVertical::Fraction(0.5));
+        assert_eq!(ll.layer_index(), Some(2));
+    }
+
+    #[rstest]
+    fn test_select_visible_row(layer_infos: Vec<TatLayerInfo>) {
+        let mut ll = TatLayerList::default();
+        ll.set_infos(layer_infos);
+        ll.available_rows = 3;
+
+        assert!(ll.select_visible_row(1));
+        assert_eq!(ll.layer_index(), Some(1));
+
+        assert!(!ll.select_visible_row(3));
+        assert_eq!(ll.layer_index(), Some(1));
     }
 }
@@ -1,7 +1,9 @@
 #![allow(unused_imports)]
 use cli_log::*;
 
-use crate::types::{TatCrs, TatField, TatGeomField};
+use gdal::vector::field_type_to_name;
+
+use crate::types::{TatCrs, TatField, TatGeomField, TatSortOrder};
 
 /// A struct which holds information about a layer in a GDAL Dataset and can also fetch infromation
 /// about features in the layer.
@@ -13,6 +15,11 @@ pub struct TatLayerSchema {
     attribute_fields: Vec<TatField>,
     index: usize,
     feature_count: u64,
+    /// The widest value (in terminal cells) of each column among the sampled features
+    content_widths: Vec<u16>,
+    /// The attribute filter set with :filter (if any)
+    attribute_filter: Option<String>,
+    sort_order: Option<TatSortOrder>,
 }
 
 impl TatLayerSchema {
@@ -32,6 +39,9 @@ impl TatLayerSchema {
             attribute_fields,
             geom_fields,
             index,
+            content_widths: vec![],
+            attribute_filter: None,
+            sort_order: None,
         }
     }
 
@@ -72,6 +82,28 @@ impl TatLayerSchema {
         }
     }
 
+    /// Returns the name of a field's type based on its index. For geometry fields this is the
+    /// geometry type.
+    pub fn field_type_by_id(&self, field_idx: i32) -> Option<String> {
+        let total_geom_fields: i32 = self.geom_fields().len() as i32;
+
+        if field_idx < total_geom_fields {
+            self.geom_fields.get(field_idx as usize).map(|field| field.geom_type().to_string())
+        } else {
+            let attribute_field_idx = field_idx - total_geom_fields;
+            self.attribute_fields.get(attribute_field_idx as usize).map(|field| field_type_to_name(field.dtype()))
+        }
+    }
+
+    /// Returns whether the values of a field are lists or JSON based on its index. Geometry fields
+    /// are never nested.
+    pub fn field_is_nested(&self, field_idx: i32) -> bool {
+        let attribute_field_idx = field_idx - self.geom_fields().len() as i32;
+
+        attribute_field_idx >= 0
+            && self.attribute_fields.get(attribute_field_idx as usize).is_some_and(|field| field.is_nested())
+    }
+
     /// Returns the layer's attribute fields
     pub fn attribute_fields(&self) -> &[TatField] {
         &self.attribute_fields
@@ -90,6 +122,34 @@ impl TatLayerSchema {
     pub fn index(&self) -> usize {
         self.index
     }
+
+    /// Sets the widths of the widest sampled values of each column
+    pub fn set_content_widths(&mut self, widths: Vec<u16>) {
+        self.content_widths = widths;
+    }
+
+    /// Returns the width of the widest sampled value in a column, or 0 if the column has not been
+    /// sampled. This includes the geometry fields.
+    pub fn content_width(&self, field_idx: u64) -> u16 {
+        self.content_widths.get(field_idx as usize).copied().unwrap_or(0)
+    }
+
+    /// Sets the attribute filter and the sort order which the layer's features are currently read
+    /// with
+    pub fn set_filter_and_sort(&mut self, filter: Option<String>, order: Option<TatSortOrder>) {
+        self.attribute_filter = filter;
+        self.sort_order = order;
+    }
+
+    /// Returns the attribute filter set with :filter (if any)
+    pub fn attribute_filter(&self) -> Option<&str> {
+        self.attribute_filter.as_deref()
+    }
+
+    /// Returns the order the layer's features are sorted in (if any)
+    pub fn sort_order(&self) -> Option<&TatSortOrder> {
+        self.sort_order.as_ref()
+    }
 }
 
 #[cfg(test)]
@@ -101,6 +161,8 @@ mod test {
     use crate::fixtures::datasets::basic_gpkg;
     use crate::fixtures::{layer_schema, layer_schema_no_geom, layer_schema_one_geom};
 
+    use gdal::vector::OGRFieldType;
+
     use rstest::*;
 
     #[rstest]
@@ -138,4 +200,30 @@ mod test {
         assert_eq!(layer_schema_no_geom.field_name_by_id(2), Some("Field3"));
         assert_eq!(layer_schema_no_geom.field_name_by_id(3), None);
     }
+
+    #[rstest]
+    fn test_field_is_nested(layer_schema: TatLayerSchema) {
+        assert!(!layer_schema.field_is_nested(0));
+        assert!(!layer_schema.field_is_nested(2));
+        assert!(!layer_schema.field_is_nested(5));
+
+        let schema = TatLayerSchema::new(
+            "nested".to_string(),
+            None,
+            vec![],
+            vec![TatField::new("tags".to_string(), OGRFieldType::OFTStringList, 0)],
+            0,
+            0,
+        );
+        assert!(schema.field_is_nested(0));
+    }
+
+    #[rstest]
+    fn test_field_type_by_id(layer_schema: TatLayerSchema) {
+        assert_eq!(layer_schema.field_type_by_id(0), Some("Polygon".to_string()));
+        assert_eq!(layer_schema.field_type_by_id(1), Some("Point".to_string()));
+        assert_eq!(layer_schema.field_type_by_id(2), Some("Integer".to_string()));
+        assert_eq!(layer_schema.field_type_by_id(4), Some("Real".to_string()));
+        assert_eq!(layer_schema.field_type_by_id(5), None);
+    }
 }
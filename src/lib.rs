@@ -1,11 +1,30 @@
 pub mod app;
+pub mod columnpicker;
+pub mod commandline;
+pub mod compare;
+pub mod config;
+pub mod copymenu;
+pub mod diff;
+pub mod dump;
+pub mod export;
+pub mod exportdialog;
 pub mod fixtures;
+pub mod info;
+pub mod jumps;
+pub mod keys;
 pub mod layerlist;
 pub mod layerschema;
+pub mod map;
 pub mod navparagraph;
-pub mod numberinput;
+pub mod recordview;
+pub mod search;
+pub mod selection;
+pub mod serialize;
+pub mod session;
 pub mod shared;
 pub mod table;
+pub mod textinput;
 pub mod types;
 pub mod utils;
+pub mod valuetree;
 pub mod dataset;
@@ -3,21 +3,44 @@ use cli_log::*;
 
 use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
 use tat::dataset::{DatasetRequest, DatasetResponse, TatDataset};
+use tat::config::{config, set_config, TatConfig};
+use tat::diff::TatDatasetDiff;
+use tat::dump::{dump_layer, TatDumpFormat, TatDumpOptions};
+use tat::export::TatExportRequest;
+use tat::session::TatSession;
 use std::sync::mpsc::{self, SendError, TryRecvError};
-use std::thread::{self};
+use std::thread::{self, JoinHandle};
 use std::time::Duration;
-use std::{env::temp_dir, fs::File};
-use clap::{CommandFactory, Parser};
+use std::{fs::File, io::BufWriter};
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use gdal::vector::LayerAccess;
 
 use tat::app::{TatApp, TatEvent};
 use tat::utils::error_handler;
 
 #[derive(Parser)]
-#[command(arg_required_else_help = true)]
+#[command(arg_required_else_help = true, args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 #[command(version, about, long_about = None)]
 struct Cli {
-    uri: String,
+    #[command(subcommand)]
+    command: Option<TatCommand>,
 
+    #[arg(required = true)]
+    uri: Option<String>,
+
+    #[command(flatten)]
+    open_args: OpenArgs,
+
+    #[arg(long = "no-restore", help = "Start from the beginning instead of where the dataset was left off the last time", long_help = "Start from the beginning instead of restoring the layer, position, filter, columns and marks from the last time the dataset was opened. The session is still stored when quitting.")]
+    no_restore: bool,
+
+    #[arg(long = "config-file", value_name = "PATH", global = true, help = "Read the configuration from PATH instead of ~/.config/tat/config.toml")]
+    config_file: Option<String>,
+}
+
+/// Arguments which affect how the dataset is opened
+#[derive(Args, Default)]
+struct OpenArgs {
     #[arg(long = "where", value_name = "WHERE", help = "Filter feature based on attributes", long_help = "Filter which features are shown based on their attributes. Given in the format of a SQL WHERE clause e.g. --where=\"field_1 = 12\"")]
     where_sql: Option<String>,
 
@@ -28,6 +51,95 @@ struct Cli {
     all_drivers: bool,
 }
 
+impl OpenArgs {
+    /// Fills in the arguments which were not given from the configuration
+    fn with_defaults(self, config: &TatConfig) -> Self {
+        Self {
+            where_sql: self.where_sql.or(config.where_sql.clone()),
+            layers: self.layers.or(config.layers.as_ref().map(|layers| layers.join(","))),
+            all_drivers: self.all_drivers || config.allow_untested_drivers,
+        }
+    }
+
+    /// Returns the layers given with --layers (if any)
+    fn layer_filter(&self) -> Option<Vec<String>> {
+        self.layers.as_ref().map(|lyrs| {
+            lyrs.split(',').map(|lyr| lyr.to_string()).collect()
+        })
+    }
+}
+
+#[derive(Subcommand)]
+enum TatCommand {
+    /// Export the dataset's layers into a new dataset without opening the UI
+    Export {
+        uri: String,
+
+        #[arg(help = "Path of the new dataset")]
+        output: String,
+
+        #[arg(long = "driver", short = 'd', value_name = "DRIVER", help = "Short name of the output driver e.g. GPKG (guessed from OUTPUT if not given)")]
+        driver: Option<String>,
+
+        #[arg(long = "overwrite", help = "Replace OUTPUT if it already exists")]
+        overwrite: bool,
+
+        #[command(flatten)]
+        open_args: OpenArgs,
+    },
+    /// Print the dataset's layers and their schemas without opening the UI
+    Info {
+        uri: String,
+
+        #[arg(long = "json", help = "Print the information as JSON")]
+        json: bool,
+
+        #[command(flatten)]
+        open_args: OpenArgs,
+    },
+    /// Print the features of a layer to stdout without opening the UI
+    Dump {
+        uri: String,
+
+        #[arg(long = "layer", short = 'l', value_name = "LAYER", help = "Name of the dumped layer (required if the dataset has several layers)")]
+        layer: Option<String>,
+
+        #[arg(long = "format", short = 'f', value_name = "FORMAT", default_value = "csv", help = "Output format: csv, tsv, jsonl, markdown or pretty")]
+        format: TatDumpFormat,
+
+        #[arg(long = "where", value_name = "WHERE", help = "Filter features based on attributes, given as a SQL WHERE clause")]
+        where_sql: Option<String>,
+
+        #[arg(long = "limit", value_name = "LIMIT", help = "Print at most LIMIT features")]
+        limit: Option<u64>,
+
+        #[arg(long = "columns", value_name = "COLUMNS", value_delimiter = ',', help = "Comma-separated list of the printed columns (all columns if not given)")]
+        columns: Option<Vec<String>>,
+
+        #[arg(long = "allow-untested-drivers", help = "Allow attempting to open dataset of any type which has a GDAL-supported vector driver. Use with caution.")]
+        all_drivers: bool,
+    },
+    /// Show the configuration
+    Config {
+        #[arg(long = "print-default", help = "Print the effective configuration (the defaults overridden by the configuration file) as TOML")]
+        print_default: bool,
+    },
+    /// Compare the layers of two datasets and browse the differences
+    Diff {
+        #[arg(help = "The original dataset")]
+        old: String,
+
+        #[arg(help = "The changed dataset")]
+        new: String,
+
+        #[arg(long = "key", short = 'k', value_name = "FIELD", help = "Match features by the values of FIELD instead of their FIDs")]
+        key: Option<String>,
+    },
+}
+
+/// Where the differences are written for browsing them
+const DIFF_URI: &str = "/vsimem/tat_diff.gpkg";
+
 fn handle_events(tx: mpsc::Sender<TatEvent>, rx: mpsc::Receiver<bool>) -> Result<(), SendError<TatEvent>> {
     loop {
         let poll_result = crossterm::event::poll(Duration::from_millis(50));
@@ -55,51 +167,203 @@ fn handle_events(tx: mpsc::Sender<TatEvent>, rx: mpsc::Receiver<bool>) -> Result
     }
 }
 
-fn main() {
-    let cli = Cli::parse();
-    let uri = cli.uri;
-    let where_clause = cli.where_sql;
-
-    let layer_filter = if let Some(lyrs) = cli.layers {
-        let layers = lyrs.split(',');
-        let mut _layers = vec![];
-        for _lyr in layers {
-            _layers.push(_lyr.to_string());
-        }
-
-        Some(_layers)
-    } else {
-        None
-    };
-
-    let _ = File::create(format!("{}/tat_gdal.log", temp_dir().display())).unwrap();
-    gdal::config::set_error_handler(error_handler);
-
-    let (dataset_request_tx, dataset_request_rx) = mpsc::channel::<DatasetRequest>();
-    let (tatevent_tx, tatevent_rx) = mpsc::channel::<TatEvent>();
-    let (event_thread_tx, event_thread_rx) = mpsc::channel();
-
-    // has to be cloned here because it's used later and moved to a closure here
-    let cp_tatevent_tx = tatevent_tx.clone();
-
-    let ds_handle = thread::spawn(move || {
+/// Spawns the thread which handles the dataset. If the dataset could not be opened,
+/// DatasetResponse::InvalidDataset is sent.
+fn spawn_dataset(uri: String, open_args: OpenArgs, tatevent_tx: mpsc::Sender<TatEvent>, dataset_request_rx: mpsc::Receiver<DatasetRequest>) -> JoinHandle<()> {
+    thread::spawn(move || {
         if let Some(mut ds) = TatDataset::new(
-            cp_tatevent_tx.clone(),
+            tatevent_tx.clone(),
             dataset_request_rx,
-            uri.to_string(),
-            cli.all_drivers,
-            where_clause,
-            layer_filter,
+            uri,
+            open_args.all_drivers,
+            open_args.where_sql.clone(),
+            open_args.layer_filter(),
         ) {
             ds.handle_requests();
         } else {
-            cp_tatevent_tx.send(
+            tatevent_tx.send(
                 TatEvent::Dataset(
                     DatasetResponse::InvalidDataset,
                 )
             ).unwrap();
         }
-    });
+    })
+}
+
+/// Exports the dataset without starting the UI, printing the progress to stderr. Returns whether
+/// the export succeeded.
+fn export(uri: String, output: String, driver: Option<String>, overwrite: bool, open_args: OpenArgs) -> bool {
+    let (dataset_request_tx, dataset_request_rx) = mpsc::channel::<DatasetRequest>();
+    let (tatevent_tx, tatevent_rx) = mpsc::channel::<TatEvent>();
+
+    let ds_handle = spawn_dataset(uri, open_args, tatevent_tx, dataset_request_rx);
+    dataset_request_tx.send(DatasetRequest::BuildLayers).unwrap();
+    dataset_request_tx.send(DatasetRequest::LayerSchemas).unwrap();
+
+    let mut success = false;
+    while let Ok(TatEvent::Dataset(response)) = tatevent_rx.recv() {
+        match response {
+            DatasetResponse::InvalidDataset => break,
+            DatasetResponse::LayerSchemas(schemas) => {
+                dataset_request_tx.send(
+                    DatasetRequest::Export(
+                        TatExportRequest {
+                            layers: schemas.iter().map(|schema| schema.index()).collect(),
+                            rows: None,
+                            columns: None,
+                            driver: driver.clone().unwrap_or_default(),
+                            path: output.clone(),
+                            overwrite,
+                        }
+                    )
+                ).unwrap();
+            },
+            DatasetResponse::ExportProgress(progress) => {
+                eprint!("\rExporting… {:>3.0}% ({}/{})", progress.ratio() * 100.0, progress.exported, progress.total);
+            },
+            DatasetResponse::ExportFinished(result) => {
+                eprintln!();
+
+                match result {
+                    Ok(count) => {
+                        eprintln!("Exported {} feature(s) to \"{}\"", count, output);
+                        success = true;
+                    },
+                    Err(e) => eprintln!("ERROR! Could not export: {}", e),
+                }
+
+                break;
+            },
+            _ => (),
+        }
+    }
+
+    let _ = dataset_request_tx.send(DatasetRequest::Terminate);
+    ds_handle.join().unwrap();
+
+    success
+}
+
+/// Prints the information about the dataset and its layers to stdout without starting the UI.
+/// Returns whether the dataset could be read.
+fn info(uri: String, as_json: bool, open_args: OpenArgs) -> bool {
+    let (dataset_request_tx, dataset_request_rx) = mpsc::channel::<DatasetRequest>();
+    let (tatevent_tx, tatevent_rx) = mpsc::channel::<TatEvent>();
+
+    let ds_handle = spawn_dataset(uri, open_args, tatevent_tx, dataset_request_rx);
+    dataset_request_tx.send(DatasetRequest::BuildLayers).unwrap();
+    dataset_request_tx.send(DatasetRequest::DatasetReport).unwrap();
+
+    let mut success = false;
+    while let Ok(TatEvent::Dataset(response)) = tatevent_rx.recv() {
+        match response {
+            DatasetResponse::InvalidDataset => break,
+            DatasetResponse::DatasetReport(report) => {
+                if as_json {
+                    println!("{}", serde_json::to_string_pretty(&report.to_json()).unwrap());
+                } else {
+                    print!("{}", report.text());
+                }
+
+                success = true;
+                break;
+            },
+            _ => (),
+        }
+    }
+
+    let _ = dataset_request_tx.send(DatasetRequest::Terminate);
+    ds_handle.join().unwrap();
+
+    success
+}
+
+/// Prints the features of a layer to stdout without starting the UI. The dataset is opened the
+/// same way as in the UI. Returns whether the layer could be dumped.
+fn dump(uri: String, layer_name: Option<String>, where_sql: Option<String>, all_drivers: bool, options: TatDumpOptions) -> bool {
+    let ds = match TatDataset::open_gdal_dataset(&uri, all_drivers) {
+        Ok(ds) => ds,
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        },
+    };
+
+    let layer = match layer_name.as_deref() {
+        Some(name) => ds.layer_by_name(name).map_err(|_| format!("No layer \"{}\" in the dataset", name)),
+        None if ds.layer_count() == 1 => ds.layer(0).map_err(|e| e.to_string()),
+        None => Err("The dataset has several layers, choose one with --layer".to_string()),
+    };
+
+    let mut layer = match layer {
+        Ok(layer) => layer,
+        Err(e) => {
+            eprintln!("ERROR! {}", e);
+            return false;
+        },
+    };
+
+    if let Some(where_sql) = where_sql.as_deref()
+        && let Err(e) = layer.set_attribute_filter(where_sql) {
+        eprintln!("ERROR! Invalid --where clause: {}", e);
+        return false;
+    }
+
+    let mut out = BufWriter::new(std::io::stdout().lock());
+
+    match dump_layer(&mut layer, &options, &mut out) {
+        Ok(_) => true,
+        Err(e) => {
+            eprintln!("ERROR! Could not dump the layer: {}", e);
+            false
+        },
+    }
+}
+
+/// Compares two datasets and writes the differences into DIFF_URI. Returns the summary of the
+/// differences, or None if the comparison failed.
+fn diff(old: String, new: String, key: Option<String>) -> Option<String> {
+    let open = |uri: &str| {
+        let flags = gdal::GdalOpenFlags::GDAL_OF_VECTOR | gdal::GdalOpenFlags::GDAL_OF_READONLY;
+
+        gdal::Dataset::open_ex(uri, gdal::DatasetOptions { open_flags: flags, ..Default::default() })
+            .map_err(|e| eprintln!("ERROR! Could not open \"{}\": {}", uri, e))
+            .ok()
+    };
+
+    let old_ds = open(&old)?;
+    let new_ds = open(&new)?;
+
+    eprintln!("Comparing \"{}\" to \"{}\"…", old, new);
+
+    let diff = match TatDatasetDiff::new(&old_ds, &new_ds, key.as_deref()) {
+        Ok(diff) => diff,
+        Err(e) => {
+            eprintln!("ERROR! Could not compare the datasets: {}", e);
+            return None;
+        },
+    };
+
+    if let Err(e) = diff.write(DIFF_URI) {
+        eprintln!("ERROR! Could not write the differences: {}", e);
+        return None;
+    }
+
+    Some(diff.summary())
+}
+
+/// Opens the dataset and runs the UI until the user quits. The session (if any) is restored and
+/// stored when quitting.
+fn run_ui(uri: String, open_args: OpenArgs, session: Option<TatSession>) {
+    let _ = File::create(&config().gdal_log).unwrap();
+    gdal::config::set_error_handler(error_handler);
+
+    let (dataset_request_tx, dataset_request_rx) = mpsc::channel::<DatasetRequest>();
+    let (tatevent_tx, tatevent_rx) = mpsc::channel::<TatEvent>();
+    let (event_thread_tx, event_thread_rx) = mpsc::channel();
+
+    // has to be cloned here because it's used later by the event thread
+    let ds_handle = spawn_dataset(uri, open_args, tatevent_tx.clone(), dataset_request_rx);
 
     let mut ds_okay = false;
     while !ds_okay {
@@ -133,8 +397,12 @@ fn main() {
         }
     });
 
-    let _result = TatApp::new(dataset_request_tx.clone())
-        .run(&mut terminal, tatevent_rx);
+    let mut app = TatApp::new(dataset_request_tx.clone());
+    if let Some(session) = session {
+        app.set_session(session);
+    }
+
+    let _result = app.run(&mut terminal, tatevent_rx);
 
     match event_thread_tx.send(true) {
         Ok(_) => (),
@@ -148,3 +416,75 @@ fn main() {
     crossterm::execute!(std::io::stdout(), DisableMouseCapture).unwrap();
     ratatui::restore();
 }
+
+fn main() {
+    let cli = Cli::parse();
+
+    match TatConfig::load(cli.config_file.as_deref()) {
+        Ok(config) => set_config(config),
+        Err(e) => {
+            eprintln!("ERROR! {}", e);
+            std::process::exit(1);
+        },
+    }
+
+    if let Some(command) = cli.command {
+        match command {
+            TatCommand::Export { uri, output, driver, overwrite, open_args } => {
+                if !export(uri, output, driver, overwrite, open_args.with_defaults(config())) {
+                    std::process::exit(1);
+                }
+            },
+            TatCommand::Info { uri, json, open_args } => {
+                if !info(uri, json, open_args.with_defaults(config())) {
+                    std::process::exit(1);
+                }
+            },
+            TatCommand::Dump { uri, layer, format, where_sql, limit, columns, all_drivers } => {
+                let options = TatDumpOptions { format, columns, limit, geometry_format: config().geometry_format };
+
+                if !dump(uri, layer, where_sql, all_drivers || config().allow_untested_drivers, options) {
+                    std::process::exit(1);
+                }
+            },
+            TatCommand::Config { print_default } => {
+                if print_default {
+                    print!("{}", config().to_toml());
+                } else {
+                    Cli::command().find_subcommand_mut("config").unwrap().print_help().unwrap();
+                }
+            },
+            TatCommand::Diff { old, new, key } => {
+                match diff(old, new, key) {
+                    Some(summary) => {
+                        run_ui(DIFF_URI.to_string(), OpenArgs::default().with_defaults(config()), None);
+                        print!("{}", summary);
+                    },
+                    None => std::process::exit(1),
+                }
+            },
+        }
+
+        return;
+    }
+
+    // uri is required when no subcommand is given
+    let uri = cli.uri.unwrap();
+    let mut open_args = cli.open_args;
+
+    let restored = if cli.no_restore { None } else { TatSession::load(&uri) };
+    let session = match restored {
+        Some(mut session) => {
+            // a filter given on the command line replaces the restored ones
+            if let Some(where_sql) = open_args.where_sql.clone() {
+                session.replace_where_sql(where_sql);
+            }
+            open_args.where_sql = session.where_sql.clone();
+
+            session
+        },
+        None => TatSession::new(&uri, open_args.where_sql.clone()),
+    };
+
+    run_ui(uri, open_args.with_defaults(config()), Some(session));
+}
@@ -0,0 +1,436 @@
+use std::{collections::HashMap, sync::mpsc::Sender};
+
+#[allow(unused_imports)]
+use cli_log::*;
+use gdal::vector::Geometry;
+use ratatui::{
+    layout::Rect,
+    style::{Color, Stylize},
+    symbols::{self, Marker},
+    text::Line,
+    widgets::{canvas::{Canvas, Context, Line as CanvasLine, Points}, Block, Borders, Paragraph},
+    Frame,
+};
+
+use crate::dataset::DatasetRequest;
+
+/// The maximum number of features which are drawn on the map. Layers with more features are
+/// sampled evenly.
+pub const MAX_MAP_FEATURES: usize = 5000;
+
+/// A feature's geometry split into drawable parts. Each part is a sequence of coordinates: a
+/// single coordinate is drawn as a point, more than one as a line string.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TatMapFeature {
+    row: u64,
+    parts: Vec<Vec<(f64, f64)>>,
+}
+
+impl TatMapFeature {
+    /// Constructs a new object
+    pub fn new(row: u64, parts: Vec<Vec<(f64, f64)>>) -> Self {
+        Self {
+            row,
+            parts,
+        }
+    }
+
+    /// Constructs a new object from a GDAL geometry
+    pub fn from_geometry(row: u64, geom: &Geometry) -> Self {
+        let mut parts = vec![];
+        TatMapFeature::collect_parts(geom, &mut parts);
+
+        Self::new(row, parts)
+    }
+
+    /// Returns the (1-based) row of the feature in the attribute table
+    pub fn row(&self) -> u64 {
+        self.row
+    }
+
+    /// Returns the drawable parts of the feature
+    pub fn parts(&self) -> &[Vec<(f64, f64)>] {
+        &self.parts
+    }
+
+    /// Recursively collects the coordinates of a geometry and its sub-geometries
+    fn collect_parts(geom: &Geometry, parts: &mut Vec<Vec<(f64, f64)>>) {
+        if geom.geometry_count() > 0 {
+            for i in 0..geom.geometry_count() {
+                TatMapFeature::collect_parts(&geom.get_geometry(i), parts);
+            }
+
+            return;
+        }
+
+        let mut points = vec![];
+        geom.get_points(&mut points);
+
+        if !points.is_empty() {
+            parts.push(points.iter().map(|(x, y, _)| (*x, *y)).collect());
+        }
+    }
+}
+
+/// The (possibly sampled) geometries of a single layer
+#[derive(Debug, Clone, PartialEq)]
+pub struct TatLayerGeometries {
+    layer_index: usize,
+    features: Vec<TatMapFeature>,
+    total_features: u64,
+}
+
+impl TatLayerGeometries {
+    /// Constructs a new object
+    pub fn new(layer_index: usize, features: Vec<TatMapFeature>, total_features: u64) -> Self {
+        Self {
+            layer_index,
+            features,
+            total_features,
+        }
+    }
+
+    /// Returns the index of the layer the geometries belong to
+    pub fn layer_index(&self) -> usize {
+        self.layer_index
+    }
+
+    /// Returns the features which will be drawn
+    pub fn features(&self) -> &[TatMapFeature] {
+        &self.features
+    }
+
+    /// Returns whether only a subset of the layer's features is included
+    pub fn sampled(&self) -> bool {
+        (self.features.len() as u64) < self.total_features
+    }
+
+    /// Returns the bounding box of all the features as [min_x, min_y, max_x, max_y]
+    pub fn bounds(&self) -> Option<[f64; 4]> {
+        let mut bounds: Option<[f64; 4]> = None;
+
+        for (x, y) in self.features.iter().flat_map(|f| f.parts.iter().flatten()) {
+            if !x.is_finite() || !y.is_finite() {
+                continue;
+            }
+
+            bounds = Some(match bounds {
+                Some([min_x, min_y, max_x, max_y]) => [min_x.min(*x), min_y.min(*y), max_x.max(*x), max_y.max(*y)],
+                None => [*x, *y, *x, *y],
+            });
+        }
+
+        bounds
+    }
+}
+
+/// Widget which draws the features of a layer on a braille canvas
+pub struct TatMap {
+    layer_geometries: HashMap<usize, TatLayerGeometries>,
+    requested_layers: Vec<usize>,
+    layer_index: Option<usize>,
+    /// The layer index and the row of the highlighted feature
+    highlighted_row: Option<(usize, u64)>,
+    /// The highlighted feature, which is read separately since it may not be among the sampled
+    /// features
+    highlighted: Option<(usize, TatMapFeature)>,
+    dataset_request_tx: Sender<DatasetRequest>,
+}
+
+impl TatMap {
+    /// Constructs new widget
+    pub fn new(dataset_request_tx: Sender<DatasetRequest>) -> Self {
+        Self {
+            layer_geometries: HashMap::new(),
+            requested_layers: vec![],
+            layer_index: None,
+            highlighted_row: None,
+            highlighted: None,
+            dataset_request_tx,
+        }
+    }
+
+    /// Sets the layer which is drawn, requesting its geometries if they have not been fetched yet
+    pub fn set_layer_index(&mut self, idx: usize) {
+        self.layer_index = Some(idx);
+
+        if self.layer_geometries.contains_key(&idx) || self.requested_layers.contains(&idx) {
+            return;
+        }
+
+        self.requested_layers.push(idx);
+        self.dataset_request_tx.send(
+            DatasetRequest::LayerGeometries(idx),
+        ).unwrap();
+    }
+
+    /// Sets the row of the highlighted feature of the current layer, requesting its geometry if
+    /// the row has changed
+    pub fn set_highlighted_row(&mut self, row: Option<u64>) {
+        let highlighted_row = self.layer_index.zip(row);
+
+        if highlighted_row == self.highlighted_row {
+            return;
+        }
+
+        self.highlighted_row = highlighted_row;
+
+        if let Some((idx, row)) = highlighted_row {
+            self.dataset_request_tx.send(
+                DatasetRequest::MapFeature(idx, row),
+            ).unwrap();
+        }
+    }
+
+    /// Stores the highlighted feature received from the dataset, unless another one has been
+    /// highlighted since
+    pub fn set_highlighted_feature(&mut self, idx: usize, feature: Option<TatMapFeature>) {
+        self.highlighted = feature
+            .filter(|feature| self.highlighted_row == Some((idx, feature.row())))
+            .map(|feature| (idx, feature));
+    }
+
+    /// Returns the highlighted feature (if it has been received)
+    fn highlighted_feature(&self) -> Option<&TatMapFeature> {
+        self.highlighted.as_ref()
+            .filter(|(idx, feature)| self.highlighted_row == Some((*idx, feature.row())))
+            .map(|(_, feature)| feature)
+    }
+
+    /// Stores the geometries received from the dataset
+    pub fn set_geometries(&mut self, geometries: TatLayerGeometries) {
+        self.requested_layers.retain(|idx| *idx != geometries.layer_index());
+        self.layer_geometries.insert(geometries.layer_index(), geometries);
+    }
+
+    /// Drops the geometries of a layer so that they are fetched again when the layer is drawn,
+    /// e.g. after its features have been filtered
+    pub fn reload_layer(&mut self, idx: usize) {
+        self.layer_geometries.remove(&idx);
+        self.requested_layers.retain(|i| *i != idx);
+
+        // the rows have changed as well
+        if self.highlighted_row.is_some_and(|(i, _)| i == idx) {
+            self.highlighted_row = None;
+            self.highlighted = None;
+        }
+    }
+
+    /// Returns the geometries of the current layer (if they have been received)
+    pub fn current_geometries(&self) -> Option<&TatLayerGeometries> {
+        self.layer_geometries.get(&self.layer_index?)
+    }
+
+    /// Renders the map. The highlighted feature (if any) is drawn on top of the others with the
+    /// selection color.
+    pub fn render(&self, area: Rect, frame: &mut Frame) {
+        let mut block = Block::new()
+            .title(Line::raw(crate::shared::TITLE_MAP).bold().underlined())
+            .borders(Borders::ALL)
+            .border_set(symbols::border::ROUNDED)
+            .border_style(crate::shared::palette::current().default_style());
+
+        let geometries = match self.current_geometries() {
+            Some(geometries) => geometries,
+            None => {
+                let text = if self.layer_index.is_some() { "Loading…" } else { "No layer selected" };
+                frame.render_widget(
+                    Paragraph::new(text)
+                        .fg(crate::shared::palette::current().default_fg)
+                        .block(block),
+                    area,
+                );
+                return;
+            },
+        };
+
+        let bounds = match geometries.bounds() {
+            Some(bounds) => bounds,
+            None => {
+                frame.render_widget(
+                    Paragraph::new("No geometries to draw")
+                        .fg(crate::shared::palette::current().default_fg)
+                        .block(block),
+                    area,
+                );
+                return;
+            },
+        };
+
+        if geometries.sampled() {
+            block = block.title_bottom(
+                Line::raw(
+                    format!(" {} of {} features ", geometries.features().len(), geometries.total_features)
+                ).centered()
+            );
+        }
+
+        let [x_bounds, y_bounds] = TatMap::padded_bounds(bounds);
+        let palette = crate::shared::palette::current();
+        let canvas_area = block.inner(area);
+        let highlighted = self.highlighted_feature();
+        let highlighted_row = highlighted.map(|feature| feature.row());
+
+        let canvas = Canvas::default()
+            .block(block)
+            .marker(Marker::Braille)
+            .x_bounds(x_bounds)
+            .y_bounds(y_bounds)
+            .paint(|ctx| {
+                for feature in geometries.features() {
+                    if Some(feature.row()) == highlighted_row {
+                        continue;
+                    }
+
+                    TatMap::draw_feature(ctx, feature, palette.highlighted_darker_fg);
+                }
+
+                if let Some(feature) = highlighted
+                    && !palette.monochrome
+                {
+                    ctx.layer();
+                    TatMap::draw_feature(ctx, feature, palette.selected_bg);
+                }
+            });
+
+        frame.render_widget(canvas, area);
+
+        // without colors the highlighted feature is drawn on top with a different marker
+        if let Some(feature) = highlighted
+            && palette.monochrome
+        {
+            let canvas = Canvas::default()
+                .marker(Marker::Block)
+                .x_bounds(x_bounds)
+                .y_bounds(y_bounds)
+                .paint(|ctx| TatMap::draw_feature(ctx, feature, palette.selected_bg));
+
+            frame.render_widget(canvas, canvas_area);
+        }
+    }
+
+    /// Draws a single feature on the canvas
+    fn draw_feature(ctx: &mut Context, feature: &TatMapFeature, color: Color) {
+        for part in feature.parts() {
+            if part.len() == 1 {
+                ctx.draw(&Points {
+                    coords: part,
+                    color,
+                });
+                continue;
+            }
+
+            for pair in part.windows(2) {
+                let (x1, y1) = pair[0];
+                let (x2, y2) = pair[1];
+                ctx.draw(&CanvasLine::new(x1, y1, x2, y2, color));
+            }
+        }
+    }
+
+    /// Returns the x and y bounds for the canvas with a small margin so that features on the
+    /// edges remain visible. Degenerate bounds (e.g. a single point) are expanded.
+    fn padded_bounds([min_x, min_y, max_x, max_y]: [f64; 4]) -> [[f64; 2]; 2] {
+        let pad = |min: f64, max: f64| {
+            let margin = if max - min > 0.0 { (max - min) * 0.05 } else { 1.0 };
+            [min - margin, max + margin]
+        };
+
+        [pad(min_x, max_x), pad(min_y, max_y)]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[allow(unused)]
+    use super::*;
+
+    use crate::{app::TatEvent, dataset::DatasetResponse, fixtures::{datasets::basic_gpkg, init_table, TatTestStructure}};
+
+    use rstest::*;
+
+    #[test]
+    fn test_bounds() {
+        let geometries = TatLayerGeometries::new(
+            0,
+            vec![
+                TatMapFeature::new(1, vec![vec![(0.0, 0.0)]]),
+                TatMapFeature::new(2, vec![vec![(-1.0, 2.0), (3.0, 4.0)]]),
+            ],
+            2,
+        );
+
+        assert_eq!(geometries.bounds(), Some([-1.0, 0.0, 3.0, 4.0]));
+        assert!(!geometries.sampled());
+
+        let empty = TatLayerGeometries::new(0, vec![], 10);
+        assert_eq!(empty.bounds(), None);
+        assert!(empty.sampled());
+    }
+
+    #[test]
+    fn test_padded_bounds() {
+        assert_eq!(TatMap::padded_bounds([0.0, 0.0, 10.0, 20.0]), [[-0.5, 10.5], [-1.0, 21.0]]);
+        assert_eq!(TatMap::padded_bounds([5.0, 5.0, 5.0, 5.0]), [[4.0, 6.0], [4.0, 6.0]]);
+    }
+
+    #[rstest]
+    fn test_layer_geometries(basic_gpkg: TatTestStructure) {
+        let (test, _) = init_table(basic_gpkg);
+        let mut map = TatMap::new(test.ds_request_tx.clone());
+
+        map.set_layer_index(0);
+        // requesting the same layer again should not send another request
+        map.set_layer_index(0);
+
+        match test.tatevent_rx.recv().unwrap() {
+            TatEvent::Dataset(DatasetResponse::LayerGeometries(geometries)) => map.set_geometries(geometries),
+            _ => panic!(),
+        }
+
+        let geometries = map.current_geometries().unwrap();
+        assert_eq!(geometries.layer_index(), 0);
+        assert_eq!(geometries.features().len(), 4);
+        assert_eq!(geometries.features()[0], TatMapFeature::new(1, vec![vec![(0.0, 0.0)]]));
+        assert_eq!(geometries.features()[3], TatMapFeature::new(4, vec![vec![(0.0, 1.0)]]));
+        assert_eq!(geometries.bounds(), Some([0.0, 0.0, 1.0, 1.0]));
+
+        test.terminate();
+    }
+
+    #[rstest]
+    fn test_highlighted_feature(basic_gpkg: TatTestStructure) {
+        let (test, _) = init_table(basic_gpkg);
+        let mut map = TatMap::new(test.ds_request_tx.clone());
+
+        map.set_layer_index(0);
+        let all = match test.tatevent_rx.recv().unwrap() {
+            TatEvent::Dataset(DatasetResponse::LayerGeometries(geometries)) => geometries,
+            _ => panic!(),
+        };
+
+        // a sample which does not include the highlighted feature
+        let sample = all.features().iter().filter(|feature| feature.row() != 2).cloned().collect();
+        map.set_geometries(TatLayerGeometries::new(0, sample, 4));
+        assert!(map.current_geometries().unwrap().sampled());
+
+        map.set_highlighted_row(Some(3));
+        map.set_highlighted_row(Some(2));
+        assert_eq!(map.highlighted_feature(), None);
+
+        for _ in 0..2 {
+            match test.tatevent_rx.recv().unwrap() {
+                TatEvent::Dataset(DatasetResponse::MapFeature(idx, feature)) => map.set_highlighted_feature(idx, feature),
+                _ => panic!(),
+            }
+        }
+
+        // the feature of the row which was highlighted first is not kept
+        assert_eq!(map.highlighted_feature(), Some(&all.features()[1]));
+
+        map.set_highlighted_row(None);
+        assert_eq!(map.highlighted_feature(), None);
+
+        test.terminate();
+    }
+}
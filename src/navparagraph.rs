@@ -79,7 +79,13 @@ impl TatNavigableParagraph {
                 }
 
                 self.scroll_offset_h -=1;
-            }
+            },
+            TatNavHorizontal::Specific(col) => {
+                self.scroll_offset_h = (col as usize).min(self.last_scrollable_col());
+            },
+            TatNavHorizontal::Fraction(fraction) => {
+                self.scroll_offset_h = (self.last_scrollable_col() as f64 * fraction.clamp(0.0, 1.0)).round() as usize;
+            },
         }
     }
 
@@ -134,6 +140,9 @@ impl TatNavigableParagraph {
             TatNavVertical::Specific(row) => {
                 unimplemented!("Cannot nav to row {}", row);
             },
+            TatNavVertical::Fraction(fraction) => {
+                self.scroll_offset_v = (self.last_scrollable_row() as f64 * fraction.clamp(0.0, 1.0)).round() as usize;
+            },
         }
     }
 
@@ -342,6 +351,12 @@ mod test {
 
             np.nav_h(TatNavHorizontal::RightOne);
             assert_eq!(np.scroll_offset_h, 10);
+
+            np.nav_h(TatNavHorizontal::Fraction(0.5));
+            assert_eq!(np.scroll_offset_h, 5);
+
+            np.nav_h(TatNavHorizontal::Fraction(2.0));
+            assert_eq!(np.scroll_offset_h, 10);
         }
     }
 
@@ -423,5 +438,11 @@ mod test {
         np.nav_v(TatNavVertical::DownHalfParagraph);
         np.nav_v(TatNavVertical::DownHalfParagraph);
         assert_eq!(np.scroll_offset_v, 12);
+
+        np.nav_v(TatNavVertical::Fraction(0.0));
+        assert_eq!(np.scroll_offset_v, 0);
+
+        np.nav_v(TatNavVertical::Fraction(0.25));
+        assert_eq!(np.scroll_offset_v, 3);
     }
 }
@@ -0,0 +1,305 @@
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::{Constraint, Margin, Rect},
+    style::Stylize,
+    text::Line,
+    widgets::{Block, BorderType, Borders, Cell, Clear, Row, Table, TableState},
+    Frame,
+};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{config::config, keys::TatAction, layerschema::TatLayerSchema, valuetree::summary};
+
+/// The actions which can be used in the record view
+const ACTIONS: [TatAction; 10] = [
+    TatAction::PreviousMenu,
+    TatAction::ShowRecord,
+    TatAction::Up,
+    TatAction::Down,
+    TatAction::ScrollTop,
+    TatAction::ScrollBottom,
+    TatAction::Left,
+    TatAction::Right,
+    TatAction::ShowValue,
+    TatAction::CopyValue,
+];
+
+/// All values of a single feature
+#[derive(Debug, Clone, PartialEq)]
+pub struct TatRecord {
+    pub layer_index: usize,
+    /// The 1-based row of the feature like in the table
+    pub row: u64,
+    pub fid: u64,
+    /// The values of all fields, including the geometry fields
+    pub values: Vec<Option<String>>,
+}
+
+/// Result of handling a key press in the record view
+#[derive(Debug, PartialEq)]
+pub enum TatRecordViewResult {
+    RejectedKey,
+    AcceptedKey,
+    Close,
+    PreviousFeature,
+    NextFeature,
+    /// Show the full value of the selected field
+    ShowValue,
+    /// Copy the value of the selected field to the clipboard
+    CopyValue,
+}
+
+/// Shows a single feature transposed, i.e. one row per field
+#[derive(Debug)]
+pub struct TatRecordView {
+    layer_index: usize,
+    /// The names and types of all fields, including the geometry fields, and whether their values
+    /// are lists or JSON
+    fields: Vec<(String, String, bool)>,
+    /// None until the dataset has sent the feature
+    record: Option<TatRecord>,
+    /// The summaries of the list and JSON values of the record by field, shown instead of the
+    /// values
+    summaries: Vec<Option<String>>,
+    state: TableState,
+}
+
+impl TatRecordView {
+    /// Constructs a new view for the fields of the given layer
+    pub fn new(schema: &TatLayerSchema) -> Self {
+        let fields = (0..schema.field_count() as i32).map(|i| {
+            (
+                schema.field_name_by_id(i).unwrap_or_default().to_string(),
+                schema.field_type_by_id(i).unwrap_or_default(),
+                schema.field_is_nested(i),
+            )
+        }).collect();
+
+        Self {
+            layer_index: schema.index(),
+            fields,
+            record: None,
+            summaries: vec![],
+            state: TableState::default().with_selected(Some(0)),
+        }
+    }
+
+    /// Sets the shown feature, the selected field stays the same
+    pub fn set_record(&mut self, record: TatRecord) {
+        if record.layer_index == self.layer_index {
+            self.summaries = record.values.iter().zip(&self.fields).map(|(value, (_, _, nested))| {
+                value.as_deref().filter(|_| *nested).map(summary)
+            }).collect();
+            self.record = Some(record);
+        }
+    }
+
+    /// Returns the shown feature (if any)
+    pub fn record(&self) -> Option<&TatRecord> {
+        self.record.as_ref()
+    }
+
+    /// Returns the name of the selected field
+    pub fn selected_field_name(&self) -> Option<&str> {
+        self.fields.get(self.selected_field()).map(|(name, _, _)| name.as_str())
+    }
+
+    /// Returns whether the value of the selected field is a list or JSON
+    pub fn selected_field_nested(&self) -> bool {
+        self.fields.get(self.selected_field()).is_some_and(|(_, _, nested)| *nested)
+    }
+
+    /// Returns the value of the selected field (if any)
+    pub fn selected_value(&self) -> Option<String> {
+        self.record.as_ref()?.values.get(self.selected_field())?.clone()
+    }
+
+    /// Handles the incoming key event. Up and down move between fields, left and right between
+    /// features.
+    pub fn key_press(&mut self, key: KeyEvent) -> TatRecordViewResult {
+        match config().keys.view_action(&key, &ACTIONS) {
+            Some(TatAction::PreviousMenu | TatAction::ShowRecord) => TatRecordViewResult::Close,
+            Some(TatAction::Up) => {
+                self.select_field(self.selected_field().saturating_sub(1));
+
+                TatRecordViewResult::AcceptedKey
+            },
+            Some(TatAction::Down) => {
+                self.select_field(self.selected_field() + 1);
+
+                TatRecordViewResult::AcceptedKey
+            },
+            Some(TatAction::ScrollTop) => {
+                self.select_field(0);
+
+                TatRecordViewResult::AcceptedKey
+            },
+            Some(TatAction::ScrollBottom) => {
+                self.select_field(self.fields.len().saturating_sub(1));
+
+                TatRecordViewResult::AcceptedKey
+            },
+            Some(TatAction::Left) => TatRecordViewResult::PreviousFeature,
+            Some(TatAction::Right) => TatRecordViewResult::NextFeature,
+            Some(TatAction::ShowValue) => TatRecordViewResult::ShowValue,
+            Some(TatAction::CopyValue) => TatRecordViewResult::CopyValue,
+            _ => TatRecordViewResult::RejectedKey,
+        }
+    }
+
+    /// Renders the view into the given area
+    pub fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let title = match self.record.as_ref() {
+            Some(record) => format!(" Feature {} (FID {}) ", record.row, record.fid),
+            None => " Feature ".to_string(),
+        };
+
+        let block = Block::default()
+            .title(Line::raw(title).bold().underlined().centered())
+            .borders(Borders::ALL)
+            .border_style(crate::shared::palette::current().highlighted_style())
+            .border_type(BorderType::Rounded)
+            .title_bottom(Line::raw(format!(" <{}> ", TatRecordView::hint())).centered());
+
+        let name_width = self.fields.iter().map(|(name, _, _)| name.graphemes(true).count()).max().unwrap_or(0);
+        let type_width = self.fields.iter().map(|(_, dtype, _)| dtype.graphemes(true).count()).max().unwrap_or(0);
+
+        let header = Row::new(["Field", "Type", "Value"]).bold().underlined();
+
+        let rows: Vec<Row> = self.fields.iter().enumerate().map(|(i, (name, dtype, _))| {
+            let value = match self.record.as_ref() {
+                Some(record) => {
+                    match (self.summaries.get(i), record.values.get(i)) {
+                        (Some(Some(summary)), _) => summary.clone(),
+                        (_, Some(Some(value))) => value.replace('\n', " "),
+                        _ => crate::shared::MISSING_VALUE.to_string(),
+                    }
+                },
+                None => String::new(),
+            };
+
+            Row::new([Cell::from(name.as_str()).bold(), Cell::from(dtype.as_str()), Cell::from(value)])
+        }).collect();
+
+        let widths = [
+            Constraint::Length(name_width.clamp(5, 30) as u16),
+            Constraint::Length(type_width.max(4) as u16),
+            Constraint::Fill(1),
+        ];
+
+        let table = Table::new(rows, widths)
+            .header(header)
+            .style(crate::shared::palette::current().default_style())
+            .row_highlight_style(crate::shared::palette::current().selected_style())
+            .column_spacing(2);
+
+        let inner = area.inner(Margin { horizontal: 1, vertical: 1 });
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        frame.render_stateful_widget(table, inner, &mut self.state);
+    }
+
+    /// Returns the hint listing the keys of the view
+    fn hint() -> String {
+        config().keys.hint(&[
+            (&[TatAction::Down, TatAction::Up], "field"),
+            (&[TatAction::Left, TatAction::Right], "previous/next feature"),
+            (&[TatAction::ShowValue], "full value"),
+            (&[TatAction::CopyValue], "copy"),
+            (&[TatAction::PreviousMenu], "close"),
+        ])
+    }
+
+    /// Returns the index of the selected field
+    fn selected_field(&self) -> usize {
+        self.state.selected().unwrap_or(0)
+    }
+
+    /// Selects a field, the index is clamped to the fields
+    fn select_field(&mut self, field: usize) {
+        self.state.select(Some(field.min(self.fields.len().saturating_sub(1))));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[allow(unused)]
+    use super::*;
+
+    use crate::{app::TatEvent, dataset::{DatasetRequest, DatasetResponse}, fixtures::{datasets::basic_gpkg, init_table, layer_schema, TatTestStructure}};
+
+    use crossterm::event::KeyCode;
+    use rstest::*;
+
+    #[rstest]
+    fn test_key_press(layer_schema: TatLayerSchema) {
+        let mut view = TatRecordView::new(&layer_schema);
+
+        assert_eq!(view.selected_field_name(), Some("geom1"));
+        assert_eq!(view.selected_value(), None);
+
+        view.set_record(
+            TatRecord {
+                layer_index: 0,
+                row: 3,
+                fid: 7,
+                values: vec![None, Some("POINT (1 2)".to_string()), Some("1".to_string()), None, Some("1.5".to_string())],
+            }
+        );
+
+        assert_eq!(view.key_press(KeyCode::Char('k').into()), TatRecordViewResult::AcceptedKey);
+        assert_eq!(view.selected_field_name(), Some("geom1"));
+
+        view.key_press(KeyCode::Char('j').into());
+        assert_eq!(view.selected_value(), Some("POINT (1 2)".to_string()));
+
+        view.key_press(KeyCode::Char('G').into());
+        view.key_press(KeyCode::Down.into());
+        assert_eq!(view.selected_field_name(), Some("Field3"));
+        assert_eq!(view.selected_value(), Some("1.5".to_string()));
+
+        assert_eq!(view.key_press(KeyCode::Char('l').into()), TatRecordViewResult::NextFeature);
+        assert_eq!(view.key_press(KeyCode::Left.into()), TatRecordViewResult::PreviousFeature);
+        assert_eq!(view.key_press(KeyCode::Enter.into()), TatRecordViewResult::ShowValue);
+        assert_eq!(view.key_press(KeyCode::Char('y').into()), TatRecordViewResult::CopyValue);
+        assert_eq!(view.key_press(KeyCode::Char('x').into()), TatRecordViewResult::RejectedKey);
+        assert_eq!(view.key_press(KeyCode::Esc.into()), TatRecordViewResult::Close);
+
+        // features of other layers are ignored
+        view.set_record(
+            TatRecord {
+                layer_index: 1,
+                row: 1,
+                fid: 1,
+                values: vec![],
+            }
+        );
+        assert_eq!(view.record().unwrap().row, 3);
+    }
+
+    #[rstest]
+    fn test_read_record(basic_gpkg: TatTestStructure) {
+        let (test, t) = init_table(basic_gpkg);
+
+        let read = |row: u64| {
+            test.ds_request_tx.send(DatasetRequest::Record(4, row)).unwrap();
+
+            loop {
+                if let TatEvent::Dataset(DatasetResponse::Record(record)) = test.tatevent_rx.recv().unwrap() {
+                    return record;
+                }
+            }
+        };
+
+        let record = read(1).unwrap();
+        assert_eq!(record.layer_index, 4);
+        assert_eq!(record.row, 1);
+        assert_eq!(record.values.len() as u64, t.layer_schemas()[4].field_count());
+        assert_eq!(record.values[3], Some("1.541".to_string()));
+
+        assert_eq!(read(100000), None);
+
+        test.terminate();
+    }
+}
@@ -0,0 +1,299 @@
+use std::fmt::Display;
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Layout, Margin, Rect},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear},
+    Frame,
+};
+use regex::Regex;
+
+use crate::textinput::{TatTextInput, TatTextInputResult};
+
+/// How the search text is matched against the values
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TatSearchMode {
+    /// Case-insensitive substring
+    IgnoreCase,
+    /// Case-sensitive substring
+    Substring,
+    Regex,
+}
+
+impl Display for TatSearchMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TatSearchMode::IgnoreCase => write!(f, "ignore case"),
+            TatSearchMode::Substring => write!(f, "match case"),
+            TatSearchMode::Regex => write!(f, "regex"),
+        }
+    }
+}
+
+/// A search entered by the user
+#[derive(Debug, Clone, PartialEq)]
+pub struct TatSearchQuery {
+    pub text: String,
+    pub mode: TatSearchMode,
+}
+
+impl TatSearchQuery {
+    /// Returns a matcher for the query, fails if the regular expression is invalid
+    pub fn matcher(&self) -> Result<TatSearchMatcher, String> {
+        match self.mode {
+            TatSearchMode::IgnoreCase => Ok(TatSearchMatcher::IgnoreCase(self.text.to_lowercase())),
+            TatSearchMode::Substring => Ok(TatSearchMatcher::Substring(self.text.clone())),
+            TatSearchMode::Regex => {
+                Regex::new(&self.text)
+                    .map(TatSearchMatcher::Regex)
+                    .map_err(|e| e.to_string())
+            },
+        }
+    }
+}
+
+/// Compiled form of a query
+pub enum TatSearchMatcher {
+    /// The lowercased search text
+    IgnoreCase(String),
+    Substring(String),
+    Regex(Regex),
+}
+
+impl TatSearchMatcher {
+    /// Returns whether the value matches
+    pub fn is_match(&self, value: &str) -> bool {
+        match self {
+            TatSearchMatcher::IgnoreCase(text) => value.to_lowercase().contains(text.as_str()),
+            TatSearchMatcher::Substring(text) => value.contains(text.as_str()),
+            TatSearchMatcher::Regex(regex) => regex.is_match(value),
+        }
+    }
+}
+
+/// A cell containing a match. The row is 1-based like in the table and the column includes
+/// the geometry fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TatSearchMatch {
+    pub row: u64,
+    pub column: u64,
+}
+
+/// All matches of a search within a layer, ordered by row and column
+#[derive(Debug, Clone, PartialEq)]
+pub struct TatSearchResults {
+    pub layer_index: usize,
+    pub query: TatSearchQuery,
+    pub matches: Vec<TatSearchMatch>,
+}
+
+impl TatSearchResults {
+    /// Returns whether the cell contains a match
+    pub fn contains(&self, row: u64, column: u64) -> bool {
+        self.matches.binary_search(&TatSearchMatch { row, column }).is_ok()
+    }
+
+    /// Returns the next match after the given cell (or the previous one if going backwards).
+    /// The search wraps around at the ends of the layer.
+    pub fn next_match(&self, row: u64, column: u64, backwards: bool) -> Option<TatSearchMatch> {
+        let cell = TatSearchMatch { row, column };
+
+        if backwards {
+            self.matches.iter().rev().find(|m| **m < cell).or(self.matches.last()).copied()
+        } else {
+            self.matches.iter().find(|m| **m > cell).or(self.matches.first()).copied()
+        }
+    }
+}
+
+/// Result of handling a key press in the search input
+#[derive(Debug, PartialEq)]
+pub enum TatSearchInputResult {
+    RejectedKey,
+    AcceptedKey,
+    Close,
+    Accept(TatSearchQuery),
+}
+
+/// Dialog for entering a search
+#[derive(Debug, PartialEq)]
+pub struct TatSearchInput {
+    input: TatTextInput,
+    mode: TatSearchMode,
+}
+
+impl TatSearchInput {
+    /// Constructs a new dialog with the earlier searches as its history, case-insensitive search
+    /// is used by default
+    pub fn new(history: Vec<String>) -> Self {
+        Self {
+            input: TatTextInput::new().with_history(history),
+            mode: TatSearchMode::IgnoreCase,
+        }
+    }
+
+    /// Handles the incoming key code. Tab cycles through the search modes. An invalid regular
+    /// expression is not accepted.
+    pub fn key_press(&mut self, key: KeyCode, ctrl_down: bool) -> TatSearchInputResult {
+        if key == KeyCode::Tab {
+            self.mode = match self.mode {
+                TatSearchMode::IgnoreCase => TatSearchMode::Substring,
+                TatSearchMode::Substring => TatSearchMode::Regex,
+                TatSearchMode::Regex => TatSearchMode::IgnoreCase,
+            };
+
+            return TatSearchInputResult::AcceptedKey;
+        }
+
+        match self.input.key_press(key, ctrl_down) {
+            TatTextInputResult::RejectedKey => TatSearchInputResult::RejectedKey,
+            TatTextInputResult::AcceptedKey => TatSearchInputResult::AcceptedKey,
+            TatTextInputResult::Close => TatSearchInputResult::Close,
+            TatTextInputResult::Accept(text) if text.is_empty() => TatSearchInputResult::Close,
+            TatTextInputResult::Accept(text) => {
+                let query = TatSearchQuery {
+                    text,
+                    mode: self.mode,
+                };
+
+                match query.matcher() {
+                    Ok(_) => TatSearchInputResult::Accept(query),
+                    Err(e) => {
+                        // the regex errors span several lines and point to the error with a caret,
+                        // the last line describes it
+                        self.input.set_error(e.lines().next_back().unwrap_or_default().to_string());
+
+                        TatSearchInputResult::AcceptedKey
+                    },
+                }
+            },
+        }
+    }
+
+    /// Renders the dialog in the given area
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let block_area = area.inner(Margin { horizontal: 1, vertical: 1 });
+
+        let block = Block::default()
+                    .title(Line::raw(format!(" Search ({}) ", self.mode)).bold().underlined().centered())
+                    .borders(Borders::ALL)
+                    .border_style(crate::shared::palette::current().highlighted_style())
+                    .border_type(BorderType::Rounded)
+                    .title_bottom(Line::raw(" <Enter to search, Tab to change mode, Esc to cancel> ").centered());
+
+        let input_area = block_area.inner(Margin { horizontal: 1, vertical: 1 });
+        let [prompt_area, text_area] = Layout::horizontal([Constraint::Length(2), Constraint::Fill(1)]).areas(input_area);
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, block_area);
+        frame.render_widget(Span::styled("/", crate::shared::palette::current().highlighted_style()), prompt_area);
+        self.input.render(frame, text_area, true);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[allow(unused)]
+    use super::*;
+
+    use crate::{app::TatEvent, dataset::{DatasetRequest, DatasetResponse}, fixtures::{datasets::basic_gpkg, init_table, TatTestStructure}};
+
+    use rstest::*;
+
+    #[test]
+    fn test_matcher() {
+        let query = |text: &str, mode: TatSearchMode| TatSearchQuery { text: text.to_string(), mode };
+
+        let matcher = query("TeXt", TatSearchMode::IgnoreCase).matcher().unwrap();
+        assert!(matcher.is_match("some text here"));
+        assert!(!matcher.is_match("tex"));
+
+        let matcher = query("TeXt", TatSearchMode::Substring).matcher().unwrap();
+        assert!(matcher.is_match("TeXt"));
+        assert!(!matcher.is_match("text"));
+
+        let matcher = query("^1\\.5\\d+$", TatSearchMode::Regex).matcher().unwrap();
+        assert!(matcher.is_match("1.541"));
+        assert!(!matcher.is_match("11.541"));
+
+        assert!(query("(", TatSearchMode::Regex).matcher().is_err());
+    }
+
+    #[test]
+    fn test_next_match() {
+        let results = TatSearchResults {
+            layer_index: 0,
+            query: TatSearchQuery { text: "a".to_string(), mode: TatSearchMode::IgnoreCase },
+            matches: vec![
+                TatSearchMatch { row: 2, column: 1 },
+                TatSearchMatch { row: 2, column: 3 },
+                TatSearchMatch { row: 5, column: 0 },
+            ],
+        };
+
+        assert!(results.contains(2, 3));
+        assert!(!results.contains(2, 2));
+
+        assert_eq!(results.next_match(1, 0, false), Some(TatSearchMatch { row: 2, column: 1 }));
+        assert_eq!(results.next_match(2, 1, false), Some(TatSearchMatch { row: 2, column: 3 }));
+        assert_eq!(results.next_match(5, 0, false), Some(TatSearchMatch { row: 2, column: 1 }));
+        assert_eq!(results.next_match(2, 3, true), Some(TatSearchMatch { row: 2, column: 1 }));
+        assert_eq!(results.next_match(2, 1, true), Some(TatSearchMatch { row: 5, column: 0 }));
+    }
+
+    #[test]
+    fn test_key_press() {
+        let mut input = TatSearchInput::new(vec!["b".to_string()]);
+
+        assert_eq!(input.key_press(KeyCode::Enter, false), TatSearchInputResult::Close);
+
+        input.key_press(KeyCode::Char('a'), false);
+        input.key_press(KeyCode::Tab, false);
+        input.key_press(KeyCode::Tab, false);
+        assert_eq!(
+            input.key_press(KeyCode::Enter, false),
+            TatSearchInputResult::Accept(TatSearchQuery { text: "a".to_string(), mode: TatSearchMode::Regex }),
+        );
+
+        input.key_press(KeyCode::Char('('), false);
+        assert_eq!(input.key_press(KeyCode::Enter, false), TatSearchInputResult::AcceptedKey);
+        assert_eq!(input.input.error(), Some("error: unclosed group"));
+
+        input.key_press(KeyCode::Up, false);
+        assert_eq!(input.input.text(), "b");
+
+        assert_eq!(input.key_press(KeyCode::Esc, false), TatSearchInputResult::Close);
+    }
+
+    #[rstest]
+    fn test_search_layer(basic_gpkg: TatTestStructure) {
+        let (test, _) = init_table(basic_gpkg);
+
+        let search = |text: &str, mode: TatSearchMode| {
+            test.ds_request_tx.send(
+                DatasetRequest::Search(4, TatSearchQuery { text: text.to_string(), mode })
+            ).unwrap();
+
+            loop {
+                if let TatEvent::Dataset(DatasetResponse::SearchFinished(result)) = test.tatevent_rx.recv().unwrap() {
+                    return result;
+                }
+            }
+        };
+
+        let results = search("PART", TatSearchMode::IgnoreCase).unwrap();
+        assert_eq!(results.layer_index, 4);
+        assert_eq!(
+            results.matches,
+            [5, 12, 22, 28].map(|row| TatSearchMatch { row, column: 0 }),
+        );
+
+        assert!(search("PART", TatSearchMode::Substring).unwrap().matches.is_empty());
+        assert_eq!(search("^1\\.541$", TatSearchMode::Regex).unwrap().matches, vec![TatSearchMatch { row: 1, column: 3 }]);
+        assert!(search("(", TatSearchMode::Regex).is_err());
+
+        test.terminate();
+    }
+}
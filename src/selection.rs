@@ -0,0 +1,156 @@
+use std::collections::BTreeMap;
+
+/// A set of rows stored as disjoint ranges, so that marking a large range of rows does not store
+/// each of them
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TatRowSelection {
+    /// The first row of each range mapped to its last row (inclusive). The ranges neither overlap
+    /// nor touch each other.
+    ranges: BTreeMap<u64, u64>,
+}
+
+impl TatRowSelection {
+    /// Returns whether the row is in the selection
+    pub fn contains(&self, row: u64) -> bool {
+        self.ranges.range(..=row).next_back().is_some_and(|(_, last)| row <= *last)
+    }
+
+    /// Returns whether no rows are selected
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Returns the number of selected rows
+    pub fn len(&self) -> u64 {
+        self.ranges.iter().map(|(first, last)| last - first + 1).sum()
+    }
+
+    /// Returns the number of rows which are selected or within the given range (inclusive, in
+    /// either order)
+    pub fn len_with(&self, start: u64, end: u64) -> u64 {
+        let (first, last) = (start.min(end), start.max(end));
+        let overlap: u64 = self.ranges.range(..=last).rev()
+            .take_while(|(_, range_last)| **range_last >= first)
+            .map(|(range_first, range_last)| (*range_last).min(last) - (*range_first).max(first) + 1)
+            .sum();
+
+        self.len() + (last - first + 1) - overlap
+    }
+
+    /// Adds the rows within the range (inclusive, in either order) to the selection
+    pub fn insert_range(&mut self, start: u64, end: u64) {
+        let (mut first, mut last) = (start.min(end), start.max(end));
+
+        // the ranges which overlap or touch the new one are merged into it
+        let merged: Vec<(u64, u64)> = self.ranges.range(..=last.saturating_add(1)).rev()
+            .take_while(|(_, range_last)| range_last.saturating_add(1) >= first)
+            .map(|(range_first, range_last)| (*range_first, *range_last))
+            .collect();
+
+        for (range_first, range_last) in merged {
+            self.ranges.remove(&range_first);
+            first = first.min(range_first);
+            last = last.max(range_last);
+        }
+
+        self.ranges.insert(first, last);
+    }
+
+    /// Adds the row to the selection
+    pub fn insert(&mut self, row: u64) {
+        self.insert_range(row, row);
+    }
+
+    /// Removes the row from the selection. Returns false if it was not selected.
+    pub fn remove(&mut self, row: u64) -> bool {
+        let Some((first, last)) = self.ranges.range(..=row).next_back().map(|(first, last)| (*first, *last)) else {
+            return false;
+        };
+
+        if row > last {
+            return false;
+        }
+
+        self.ranges.remove(&first);
+
+        if first < row {
+            self.ranges.insert(first, row - 1);
+        }
+
+        if row < last {
+            self.ranges.insert(row + 1, last);
+        }
+
+        true
+    }
+
+    /// Removes all rows from the selection
+    pub fn clear(&mut self) {
+        self.ranges.clear();
+    }
+
+    /// Returns the selected rows in ascending order
+    pub fn rows(&self) -> impl Iterator<Item = u64> + '_ {
+        self.ranges.iter().flat_map(|(first, last)| *first..=*last)
+    }
+}
+
+impl FromIterator<u64> for TatRowSelection {
+    fn from_iter<T: IntoIterator<Item = u64>>(rows: T) -> Self {
+        let mut selection = TatRowSelection::default();
+
+        for row in rows {
+            selection.insert(row);
+        }
+
+        selection
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[allow(unused)]
+    use super::*;
+
+    #[test]
+    fn test_insert_and_remove() {
+        let mut selection = TatRowSelection::default();
+        assert!(selection.is_empty());
+        assert!(!selection.remove(1));
+
+        selection.insert_range(10, 5);
+        selection.insert(1);
+        selection.insert(11);
+        assert_eq!(selection.rows().collect::<Vec<_>>(), vec![1, 5, 6, 7, 8, 9, 10, 11]);
+        assert_eq!(selection.ranges.len(), 2);
+
+        // the ranges in between are merged
+        selection.insert_range(2, 4);
+        assert_eq!(selection.ranges, BTreeMap::from([(1, 11)]));
+
+        assert!(selection.remove(6));
+        assert!(!selection.remove(6));
+        assert!(selection.remove(1));
+        assert!(selection.remove(11));
+        assert_eq!(selection.rows().collect::<Vec<_>>(), vec![2, 3, 4, 5, 7, 8, 9, 10]);
+        assert!(selection.contains(2) && selection.contains(10));
+        assert!(!selection.contains(1) && !selection.contains(6) && !selection.contains(11));
+
+        selection.clear();
+        assert!(selection.is_empty());
+    }
+
+    #[test]
+    fn test_len() {
+        let mut selection: TatRowSelection = [3, 4, 5, 20].into_iter().collect();
+        assert_eq!(selection.len(), 4);
+        assert_eq!(selection.len_with(1, 1), 5);
+        assert_eq!(selection.len_with(4, 4), 4);
+        assert_eq!(selection.len_with(21, 4), 19);
+        assert_eq!(selection.len_with(1, 100), 100);
+
+        selection.insert_range(1, 1_000_000_000);
+        assert_eq!(selection.len(), 1_000_000_000);
+        assert_eq!(selection.len_with(999_999_999, 1_000_000_001), 1_000_000_001);
+    }
+}
@@ -0,0 +1,276 @@
+use std::fmt::Display;
+
+use gdal::{spatial_ref::{AxisMappingStrategy, CoordTransform, SpatialRef}, vector::{Feature, FieldValue, Layer, LayerAccess}};
+use serde_json::{json, Map, Number, Value};
+
+use crate::config::TatGeometryFormat;
+
+/// Formats which whole features can be serialized into
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TatFeatureFormat {
+    GeoJson,
+    Csv,
+    Json,
+}
+
+impl TatFeatureFormat {
+    pub const ALL: [TatFeatureFormat; 3] = [
+        TatFeatureFormat::GeoJson,
+        TatFeatureFormat::Csv,
+        TatFeatureFormat::Json,
+    ];
+}
+
+impl Display for TatFeatureFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TatFeatureFormat::GeoJson => write!(f, "GeoJSON"),
+            TatFeatureFormat::Csv => write!(f, "CSV"),
+            TatFeatureFormat::Json => write!(f, "JSON"),
+        }
+    }
+}
+
+/// Serializes the given features of a layer. A single feature is written as a GeoJSON Feature or
+/// a JSON object, several features as a FeatureCollection or an array. GeoJSON geometries are
+/// transformed into WGS 84 as the specification requires. CSV always has a header.
+pub fn features_to_string(layer: &Layer, fids: &[u64], format: TatFeatureFormat) -> Result<String, String> {
+    let geom_names: Vec<String> = layer.defn().geom_fields().map(|field| {
+        if field.name().is_empty() { "geometry".to_string() } else { field.name() }
+    }).collect();
+    let field_names: Vec<String> = layer.defn().fields().map(|field| field.name()).collect();
+
+    let mut features = vec![];
+    for fid in fids {
+        features.push(
+            layer.feature(*fid).ok_or(format!("Could not read feature {}", fid))?
+        );
+    }
+
+    match format {
+        TatFeatureFormat::GeoJson => {
+            let transform = wgs84_transform(layer)?;
+
+            let mut values = vec![];
+            for feature in &features {
+                values.push(geojson_feature(feature, &geom_names, &field_names, transform.as_ref())?);
+            }
+
+            let value = if values.len() == 1 {
+                values.remove(0)
+            } else {
+                json!({
+                    "type": "FeatureCollection",
+                    "features": values,
+                })
+            };
+
+            serde_json::to_string_pretty(&value).map_err(|e| e.to_string())
+        },
+        TatFeatureFormat::Json => {
+            let mut values = vec![];
+            for feature in &features {
+                values.push(json_object(feature, &geom_names, &field_names)?);
+            }
+
+            let value = if values.len() == 1 { values.remove(0) } else { Value::Array(values) };
+
+            serde_json::to_string_pretty(&value).map_err(|e| e.to_string())
+        },
+        TatFeatureFormat::Csv => {
+            let header: Vec<String> = geom_names.iter().chain(field_names.iter()).map(|name| csv_escape(name)).collect();
+            let mut lines = vec![header.join(",")];
+
+            for feature in &features {
+                let mut values = vec![];
+                for i in 0..geom_names.len() {
+                    values.push(csv_escape(&geometry_wkt(feature, i).unwrap_or_default()));
+                }
+
+                for i in 0..field_names.len() {
+                    let value = feature.field_as_string(i).map_err(|e| e.to_string())?;
+                    values.push(csv_escape(&value.unwrap_or_default()));
+                }
+
+                lines.push(values.join(","));
+            }
+
+            Ok(lines.join("\n"))
+        },
+    }
+}
+
+/// Returns the transformation of the layer's first geometry field into WGS 84 with longitude before
+/// latitude, which GeoJSON requires (RFC 7946). None if the field has no CRS.
+fn wgs84_transform(layer: &Layer) -> Result<Option<CoordTransform>, String> {
+    let Some(mut source) = layer.defn().geom_fields().next().and_then(|field| field.spatial_ref().ok()) else {
+        return Ok(None);
+    };
+
+    let mut target = SpatialRef::from_epsg(4326).map_err(|e| e.to_string())?;
+    source.set_axis_mapping_strategy(AxisMappingStrategy::TraditionalGisOrder);
+    target.set_axis_mapping_strategy(AxisMappingStrategy::TraditionalGisOrder);
+
+    CoordTransform::new(&source, &target).map(Some).map_err(|e| e.to_string())
+}
+
+/// Returns the feature as a GeoJSON Feature object. Only the first geometry field is used as the
+/// geometry, transformed into WGS 84 if a transformation is given. Any others are included in the
+/// properties as WKT in their own CRS.
+fn geojson_feature(feature: &Feature, geom_names: &[String], field_names: &[String], transform: Option<&CoordTransform>) -> Result<Value, String> {
+    let mut properties = attributes_to_json(feature, field_names)?;
+
+    for (i, name) in geom_names.iter().enumerate().skip(1) {
+        properties.insert(name.clone(), geometry_wkt(feature, i).map_or(Value::Null, Value::String));
+    }
+
+    let geometry = match feature.geometry_by_index(0) {
+        Ok(geom) => {
+            let geojson = match transform {
+                Some(transform) => geom.transform(transform).and_then(|geom| geom.json()),
+                None => geom.json(),
+            }.map_err(|e| e.to_string())?;
+            serde_json::from_str(&geojson).map_err(|e| e.to_string())?
+        },
+        Err(_) => Value::Null,
+    };
+
+    Ok(json!({
+        "type": "Feature",
+        "id": feature.fid(),
+        "geometry": geometry,
+        "properties": properties,
+    }))
+}
+
+/// Returns the feature as a flat JSON object in which geometries are given as WKT
+fn json_object(feature: &Feature, geom_names: &[String], field_names: &[String]) -> Result<Value, String> {
+    let mut object = Map::new();
+
+    for (i, name) in geom_names.iter().enumerate() {
+        object.insert(name.clone(), geometry_wkt(feature, i).map_or(Value::Null, Value::String));
+    }
+
+    object.extend(attributes_to_json(feature, field_names)?);
+
+    Ok(Value::Object(object))
+}
+
+/// Returns the attributes of the feature with their JSON types
+fn attributes_to_json(feature: &Feature, field_names: &[String]) -> Result<Map<String, Value>, String> {
+    let mut attributes = Map::new();
+
+    for (i, name) in field_names.iter().enumerate() {
+        let value = feature.field(i).map_err(|e| e.to_string())?;
+        attributes.insert(name.clone(), field_value_to_json(value));
+    }
+
+    Ok(attributes)
+}
+
+/// Converts a GDAL field value into JSON
+pub fn field_value_to_json(value: Option<FieldValue>) -> Value {
+    let real = |r: f64| Number::from_f64(r).map_or(Value::Null, Value::Number);
+
+    match value {
+        None => Value::Null,
+        Some(FieldValue::IntegerValue(i)) => json!(i),
+        Some(FieldValue::IntegerListValue(list)) => json!(list),
+        Some(FieldValue::Integer64Value(i)) => json!(i),
+        Some(FieldValue::Integer64ListValue(list)) => json!(list),
+        Some(FieldValue::StringValue(s)) => json!(s),
+        Some(FieldValue::StringListValue(list)) => json!(list),
+        Some(FieldValue::RealValue(r)) => real(r),
+        Some(FieldValue::RealListValue(list)) => Value::Array(list.into_iter().map(real).collect()),
+        Some(FieldValue::DateValue(date)) => json!(date.to_string()),
+        Some(FieldValue::DateTimeValue(datetime)) => json!(datetime.to_rfc3339()),
+    }
+}
+
+/// Returns the geometry in the given geometry field as WKT (if any)
+pub fn geometry_wkt(feature: &Feature, geom_field_idx: usize) -> Option<String> {
+    feature.geometry_by_index(geom_field_idx).ok()?.wkt().ok()
+}
+
+/// Returns the geometry in the given geometry field in the given format (if any)
+pub fn geometry_string(feature: &Feature, geom_field_idx: usize, format: TatGeometryFormat) -> Option<String> {
+    let geom = feature.geometry_by_index(geom_field_idx).ok()?;
+
+    match format {
+        TatGeometryFormat::Wkt => geom.wkt(),
+        TatGeometryFormat::GeoJson => geom.json(),
+        TatGeometryFormat::Type => Ok(geom.geometry_name()),
+        TatGeometryFormat::Wkb => geom.wkb().map(|wkb| wkb.iter().map(|byte| format!("{:02X}", byte)).collect()),
+    }.ok()
+}
+
+/// Quotes a CSV value if necessary
+pub fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[allow(unused)]
+    use super::*;
+
+    use gdal::Dataset;
+
+    #[test]
+    fn test_csv_escape() {
+        assert_eq!(csv_escape("value"), "value");
+        assert_eq!(csv_escape("POLYGON ((0 0,1 0,0 0))"), "\"POLYGON ((0 0,1 0,0 0))\"");
+        assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn test_field_value_to_json() {
+        assert_eq!(field_value_to_json(None), Value::Null);
+        assert_eq!(field_value_to_json(Some(FieldValue::IntegerValue(3))), json!(3));
+        assert_eq!(field_value_to_json(Some(FieldValue::RealValue(f64::NAN))), Value::Null);
+        assert_eq!(field_value_to_json(Some(FieldValue::StringListValue(vec!["a".to_string()]))), json!(["a"]));
+    }
+
+    #[test]
+    fn test_features_to_string() {
+        let ds = Dataset::open("./testdata/basic.gpkg").unwrap();
+        let layer = ds.layer_by_name("point").unwrap();
+
+        assert_eq!(
+            features_to_string(&layer, &[1], TatFeatureFormat::Csv).unwrap(),
+            "geom,field\nPOINT (0 0),",
+        );
+
+        assert_eq!(
+            serde_json::from_str::<Value>(&features_to_string(&layer, &[1], TatFeatureFormat::Json).unwrap()).unwrap(),
+            json!({"geom": "POINT (0 0)", "field": null}),
+        );
+
+        // the layer is in EPSG:3857, GeoJSON is always in WGS 84
+        let mut feature: Value = serde_json::from_str(&features_to_string(&layer, &[2], TatFeatureFormat::GeoJson).unwrap()).unwrap();
+        let coordinates = feature["geometry"]["coordinates"].take();
+        assert_eq!(
+            feature,
+            json!({
+                "type": "Feature",
+                "id": 2,
+                "geometry": {"type": "Point", "coordinates": null},
+                "properties": {"field": null},
+            }),
+        );
+
+        let (lon, lat) = (coordinates[0].as_f64().unwrap(), coordinates[1].as_f64().unwrap());
+        assert!((lon - 1.0 / 111_319.490_793_273_58).abs() < 1e-12);
+        assert_eq!(lat, 0.0);
+
+        let collection: Value = serde_json::from_str(
+            &features_to_string(&layer, &[1, 2, 3], TatFeatureFormat::GeoJson).unwrap()
+        ).unwrap();
+        assert_eq!(collection["type"], "FeatureCollection");
+        assert_eq!(collection["features"].as_array().unwrap().len(), 3);
+    }
+}
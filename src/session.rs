@@ -0,0 +1,326 @@
+use std::{collections::{BTreeSet, HashMap}, path::{Path, PathBuf}};
+
+use serde_json::{json, Map, Value};
+
+use crate::types::TatSortOrder;
+
+/// The view of a layer as it was when the program was closed
+#[derive(Debug, Clone, PartialEq)]
+pub struct TatLayerSession {
+    pub name: String,
+    pub top_row: u64,
+    pub first_column: u64,
+    /// The highlighted row and column relative to the visible ones
+    pub selected: (usize, usize),
+    /// The shown columns (as indices which include the geometry fields) in display order
+    pub columns: Vec<u64>,
+    pub pinned: usize,
+    pub column_widths: HashMap<u64, u64>,
+    /// The marked rows in the current order of the features, which are not stored as such since
+    /// the order may change
+    pub marked_rows: BTreeSet<u64>,
+    /// The FIDs of the features on the marked rows, which are stored instead of the rows
+    pub marked_fids: BTreeSet<u64>,
+    /// The attribute filter set with :filter (if any)
+    pub filter: Option<String>,
+    pub sort: Option<TatSortOrder>,
+}
+
+impl TatLayerSession {
+    fn to_json(&self) -> Value {
+        let column_widths: Map<String, Value> = self.column_widths.iter()
+            .map(|(column, width)| (column.to_string(), json!(width)))
+            .collect();
+
+        json!({
+            "top_row": self.top_row,
+            "first_column": self.first_column,
+            "selected_row": self.selected.0,
+            "selected_column": self.selected.1,
+            "columns": self.columns,
+            "pinned": self.pinned,
+            "column_widths": column_widths,
+            "marked_fids": self.marked_fids,
+            "filter": self.filter,
+            "sort": self.sort.as_ref().map(|order| json!({
+                "field": order.field,
+                "descending": order.descending,
+            })),
+        })
+    }
+
+    /// Returns None if the value is not a valid layer session
+    fn from_json(name: &str, value: &Value) -> Option<Self> {
+        let integer = |key: &str| value.get(key)?.as_u64();
+        let integers = |key: &str| -> Option<Vec<u64>> {
+            value.get(key)?.as_array()?.iter().map(|value| value.as_u64()).collect()
+        };
+
+        let column_widths = value.get("column_widths")?.as_object()?.iter()
+            .map(|(column, width)| Some((column.parse().ok()?, width.as_u64()?)))
+            .collect::<Option<HashMap<u64, u64>>>()?;
+
+        let sort = match value.get("sort") {
+            None | Some(Value::Null) => None,
+            Some(sort) => Some(
+                TatSortOrder {
+                    field: sort.get("field")?.as_str()?.to_string(),
+                    descending: sort.get("descending")?.as_bool()?,
+                }
+            ),
+        };
+
+        Some(
+            Self {
+                name: name.to_string(),
+                top_row: integer("top_row")?,
+                first_column: integer("first_column")?,
+                selected: (integer("selected_row")? as usize, integer("selected_column")? as usize),
+                columns: integers("columns")?,
+                pinned: integer("pinned")? as usize,
+                column_widths,
+                marked_rows: BTreeSet::new(),
+                marked_fids: integers("marked_fids")?.into_iter().collect(),
+                filter: value.get("filter").and_then(|filter| filter.as_str()).map(|filter| filter.to_string()),
+                sort,
+            }
+        )
+    }
+}
+
+/// The state of a dataset which is stored when the program is closed and restored when the same
+/// dataset is opened again
+#[derive(Debug, Clone, PartialEq)]
+pub struct TatSession {
+    /// The canonical URI of the dataset, which the session is stored by
+    pub uri: String,
+    /// The attribute filter which was given with --where
+    pub where_sql: Option<String>,
+    /// The name of the selected layer
+    pub layer: Option<String>,
+    /// The views of the layers which have been shown
+    pub layers: Vec<TatLayerSession>,
+}
+
+impl TatSession {
+    /// Constructs an empty session for the dataset
+    pub fn new(uri: &str, where_sql: Option<String>) -> Self {
+        Self {
+            uri: canonical_uri(uri),
+            where_sql,
+            layer: None,
+            layers: vec![],
+        }
+    }
+
+    /// Replaces the restored attribute filter with one given with --where, which also replaces
+    /// the filters set with :filter
+    pub fn replace_where_sql(&mut self, where_sql: String) {
+        self.where_sql = Some(where_sql);
+
+        for layer in &mut self.layers {
+            layer.filter = None;
+        }
+    }
+
+    /// Reads the stored session of the dataset. Returns None if there is no session or it could
+    /// not be read.
+    pub fn load(uri: &str) -> Option<Self> {
+        TatSession::load_from(&default_state_path()?, uri)
+    }
+
+    /// Stores the session, replacing any earlier session of the same dataset
+    pub fn save(&self) -> Result<(), String> {
+        let path = default_state_path().ok_or("Could not determine the state directory".to_string())?;
+
+        self.save_to(&path)
+    }
+
+    fn load_from(path: &Path, uri: &str) -> Option<Self> {
+        let uri = canonical_uri(uri);
+        let sessions: Value = serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+
+        TatSession::from_json(&uri, sessions.get(&uri)?)
+    }
+
+    fn save_to(&self, path: &Path) -> Result<(), String> {
+        // the sessions of other datasets are kept, but an unreadable file is simply replaced
+        let mut sessions = std::fs::read_to_string(path).ok()
+            .and_then(|text| serde_json::from_str::<Map<String, Value>>(&text).ok())
+            .unwrap_or_default();
+
+        sessions.insert(self.uri.clone(), self.to_json());
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Could not create directory \"{}\": {}", dir.display(), e))?;
+        }
+
+        std::fs::write(path, Value::Object(sessions).to_string())
+            .map_err(|e| format!("Could not write session file \"{}\": {}", path.display(), e))
+    }
+
+    fn to_json(&self) -> Value {
+        let layers: Map<String, Value> = self.layers.iter()
+            .map(|layer| (layer.name.clone(), layer.to_json()))
+            .collect();
+
+        json!({
+            "where": self.where_sql,
+            "layer": self.layer,
+            "layers": layers,
+        })
+    }
+
+    /// Returns None if the value is not a valid session. Invalid layer sessions are skipped.
+    fn from_json(uri: &str, value: &Value) -> Option<Self> {
+        let layers = value.get("layers")?.as_object()?.iter()
+            .filter_map(|(name, layer)| TatLayerSession::from_json(name, layer))
+            .collect();
+
+        Some(
+            Self {
+                uri: uri.to_string(),
+                where_sql: value.get("where").and_then(|where_sql| where_sql.as_str()).map(|where_sql| where_sql.to_string()),
+                layer: value.get("layer").and_then(|layer| layer.as_str()).map(|layer| layer.to_string()),
+                layers,
+            }
+        )
+    }
+}
+
+/// Returns the URI as an absolute path without symbolic links if it is a path to an existing
+/// file, otherwise (e.g. for database connection strings) a hash of the URI, since the sessions
+/// are stored by it in plain text and the URI may contain a password
+pub fn canonical_uri(uri: &str) -> String {
+    match std::fs::canonicalize(uri) {
+        Ok(path) => path.display().to_string(),
+        Err(_) => format!("fnv1a:{:016x}", fnv1a(uri.as_bytes())),
+    }
+}
+
+/// The 64-bit FNV-1a hash, which unlike the hasher of the standard library stays the same
+/// between Rust versions
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Returns the path of the file in which the sessions are stored, i.e.
+/// $XDG_STATE_HOME/tat/sessions.json or ~/.local/state/tat/sessions.json
+pub fn default_state_path() -> Option<PathBuf> {
+    let state_dir = match std::env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".local").join("state"),
+    };
+
+    Some(state_dir.join("tat").join("sessions.json"))
+}
+
+#[cfg(test)]
+mod test {
+    #[allow(unused)]
+    use super::*;
+
+    fn session(uri: &str) -> TatSession {
+        TatSession {
+            uri: uri.to_string(),
+            where_sql: Some("field = 1".to_string()),
+            layer: Some("point".to_string()),
+            layers: vec![
+                TatLayerSession {
+                    name: "point".to_string(),
+                    top_row: 12,
+                    first_column: 1,
+                    selected: (3, 2),
+                    columns: vec![2, 0, 1],
+                    pinned: 1,
+                    column_widths: HashMap::from([(2, 40)]),
+                    marked_rows: BTreeSet::new(),
+                    marked_fids: BTreeSet::from([12, 15]),
+                    filter: Some("field > 1".to_string()),
+                    sort: Some(TatSortOrder { field: "field".to_string(), descending: true }),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_json() {
+        let session = session("test.gpkg");
+        assert_eq!(TatSession::from_json("test.gpkg", &session.to_json()), Some(session));
+
+        let value = json!({
+            "where": null,
+            "layer": "point",
+            "layers": {
+                "point": { "top_row": 1 },
+                "nogeom": {
+                    "top_row": 5,
+                    "first_column": 0,
+                    "selected_row": 0,
+                    "selected_column": 0,
+                    "columns": [0, 1],
+                    "pinned": 0,
+                    "column_widths": {},
+                    "marked_fids": [],
+                },
+            },
+        });
+
+        let restored = TatSession::from_json("test.gpkg", &value).unwrap();
+        assert_eq!(restored.where_sql, None);
+        assert_eq!(restored.layers.len(), 1);
+        assert_eq!(restored.layers[0].name, "nogeom");
+        assert_eq!(restored.layers[0].top_row, 5);
+        assert_eq!(restored.layers[0].filter, None);
+        assert!(restored.layers[0].marked_fids.is_empty());
+        assert_eq!(restored.layers[0].sort, None);
+
+        assert_eq!(TatSession::from_json("test.gpkg", &json!({})), None);
+    }
+
+    #[test]
+    fn test_replace_where_sql() {
+        let mut session = session("test.gpkg");
+        session.replace_where_sql("field = 2".to_string());
+
+        assert_eq!(session.where_sql.as_deref(), Some("field = 2"));
+        assert_eq!(session.layers[0].filter, None);
+        assert!(session.layers[0].sort.is_some());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join("tat_test_session").join("sessions.json");
+        let _ = std::fs::remove_file(&path);
+
+        let first = session(&canonical_uri("PG:dbname=first password=secret"));
+        let mut second = session(&canonical_uri("PG:dbname=second"));
+        second.layer = None;
+
+        assert_eq!(TatSession::load_from(&path, "PG:dbname=first password=secret"), None);
+
+        first.save_to(&path).unwrap();
+        second.save_to(&path).unwrap();
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("secret"));
+        assert_eq!(TatSession::load_from(&path, "PG:dbname=first password=secret"), Some(first));
+        assert_eq!(TatSession::load_from(&path, "PG:dbname=second"), Some(second));
+
+        std::fs::write(&path, "not json").unwrap();
+        assert_eq!(TatSession::load_from(&path, "PG:dbname=first"), None);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_canonical_uri() {
+        let uri = canonical_uri("PG:dbname=test password=secret");
+        assert!(!uri.contains("secret"));
+        assert_eq!(uri, canonical_uri("PG:dbname=test password=secret"));
+        assert_ne!(uri, canonical_uri("PG:dbname=test password=other"));
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(canonical_uri("./testdata/../testdata/basic.gpkg"), canonical_uri("testdata/basic.gpkg"));
+        assert!(canonical_uri("testdata/basic.gpkg").starts_with('/'));
+    }
+}
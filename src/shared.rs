@@ -1,106 +1,128 @@
-use ratatui::style::{Color, Style};
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::{config::config, keys::TatAction};
 
 pub const MISSING_VALUE: &str = "NULL";
-pub const SHOW_HELP: &str = " <type ? to show help> ";
 pub const TITLE_PROGRAM: &str = " Terminal Attribute Table ";
 pub const TITLE_DATASET_INFO: &str = " Dataset ";
 pub const TITLE_LAYER_INFO: &str = " Layer Information ";
 pub const TITLE_GDAL_LOG: &str = " GDAL Log ";
 pub const TITLE_DEBUG_LOG: &str = " Debug Log ";
 pub const TITLE_LAYER_LIST: &str = " Layers ";
+pub const TITLE_MAP: &str = " Map ";
 pub const TITLE_HELP: &str = " Help ";
-pub const POPUP_HINT: &str = " <press q to close> ";
-pub const HELP_TEXT_MAINMENU: &str = "Keybinds for Main Menu
-----------------------
-Basic Navigation:
-     or 'h': Left
-     or 'j': Down
-     or 'k': Up
-     or 'l': Right
-    Enter: Open Table
-    Tab and SHIFT+Tab: Move between sections
-
-General:
-    'q': Previous Menu
-    CTRL + Q or 'q' when in Main Menu: Quit
-    '?': Show Help
-
-Advanced Navigation:
-    'g': Scroll to Top
-    'G': Scroll to Bottom
-    CTRL + D: Scroll Down (half page)
-    CTRL + U: Scroll Up (half page)
-    CTRL + F or PageDown: Scroll Down (full page)
-    CTRL + B or PageUp: Scroll Up (full page)
-
-Miscellaneous:
-    'L': Open GDAL Log
-
-Remarks
--------
-This is the main menu of tat. You may go through and inspect the available layers
+pub const HELP_REMARKS_MAINMENU: &str = "This is the main menu of tat. You may go through and inspect the available layers
 in the selected dataset. You can also select a layer and enter into its attribute
 table.
 ";
-pub const HELP_TEXT_TABLE: &str = "Keybinds for Attribute Table
-----------------------------
-Basic Navigation:
-     or 'h': Left
-     or 'j': Down
-     or 'k': Up
-     or 'l': Right
-
-Table:
-    ':' : Open Dialog to Jump to Specific Feature
-    Enter: Display Selected Value in Pop-Up 
-    y or CTRL + C: Copy Selected Value to Clipboard
-
-General:
-    'q': Previous Menu
-    CTRL + Q: Quit
-    '?': Show Help
-
-Advanced Navigation:
-    Home or '0': nav to First Column
-    End or '$': nav to Last Column
-    'g': Scroll to Top
-    'G': Scroll to Bottom
-    CTRL + D: Scroll Down (half page)
-    CTRL + U: Scroll Up (half page)
-    CTRL + F or PageDown: Scroll Down (full page)
-    CTRL + B or PageUp: Scroll Up (full page)
-
-Miscellaneous:
-    'L': Open GDAL Log
-
-Remarks
--------
-This is the attribute table itself. You can inspect the features in the selected
+pub const HELP_REMARKS_TABLE: &str = "This is the attribute table itself. You can inspect the features in the selected
 layer.
 ";
 
+/// Returns the hint which tells the key for showing the help
+pub fn show_help_hint() -> String {
+    format!(" <type {} to show help> ", config().keys.hint_key(TatAction::Help))
+}
+
+/// Returns the hint which tells the key for closing a pop-up
+pub fn popup_hint() -> String {
+    format!(" <press {} to close> ", config().keys.hint_key(TatAction::PreviousMenu))
+}
+
 /// Common colors
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct TatPalette {
     pub default_fg: Color,
     pub highlighted_fg: Color,
     pub highlighted_darker_fg: Color,
     pub selected_bg: Color,
     pub selected_fg: Color,
+    /// Whether selections and highlights are shown with modifiers (reversed, bold etc.) instead
+    /// of colors
+    pub monochrome: bool,
 }
 
 impl TatPalette {
+    /// The names of the colors as used in the configuration file
+    pub const COLOR_NAMES: [&str; 5] = [
+        "default_fg",
+        "highlighted_fg",
+        "highlighted_darker_fg",
+        "selected_bg",
+        "selected_fg",
+    ];
+
+    /// Returns the color with the given name (see COLOR_NAMES)
+    pub fn color(&self, name: &str) -> Option<Color> {
+        match name {
+            "default_fg" => Some(self.default_fg),
+            "highlighted_fg" => Some(self.highlighted_fg),
+            "highlighted_darker_fg" => Some(self.highlighted_darker_fg),
+            "selected_bg" => Some(self.selected_bg),
+            "selected_fg" => Some(self.selected_fg),
+            _ => None,
+        }
+    }
+
+    /// Sets the color with the given name (see COLOR_NAMES), returns false if there is no such
+    /// color
+    pub fn set_color(&mut self, name: &str, color: Color) -> bool {
+        match name {
+            "default_fg" => self.default_fg = color,
+            "highlighted_fg" => self.highlighted_fg = color,
+            "highlighted_darker_fg" => self.highlighted_darker_fg = color,
+            "selected_bg" => self.selected_bg = color,
+            "selected_fg" => self.selected_fg = color,
+            _ => return false,
+        }
+
+        true
+    }
+
     pub fn selected_style(&self) -> Style {
+        if self.monochrome {
+            return Style::default().add_modifier(Modifier::REVERSED);
+        }
+
         Style::default()
         .fg(self.selected_fg)
         .bg(self.selected_bg)
     }
 
+    pub fn marked_style(&self) -> Style {
+        if self.monochrome {
+            return Style::default().add_modifier(Modifier::UNDERLINED);
+        }
+
+        Style::default()
+        .fg(self.selected_fg)
+        .bg(self.highlighted_darker_fg)
+    }
+
+    pub fn search_match_style(&self) -> Style {
+        if self.monochrome {
+            return Style::default().add_modifier(Modifier::BOLD | Modifier::ITALIC);
+        }
+
+        Style::default()
+        .fg(self.selected_fg)
+        .bg(self.default_fg)
+    }
+
     pub fn highlighted_style(&self) -> Style {
+        if self.monochrome {
+            return Style::default().add_modifier(Modifier::BOLD);
+        }
+
         Style::default()
         .fg(self.highlighted_fg)
     }
 
     pub fn highlighted_darker_fg(&self) -> Style {
+        if self.monochrome {
+            return Style::default().add_modifier(Modifier::DIM);
+        }
+
         Style::default()
         .fg(self.highlighted_darker_fg)
     }
@@ -112,15 +134,73 @@ impl TatPalette {
 }
 
 pub mod palette {
-    use ratatui::style::palette::tailwind;
+    use std::sync::OnceLock;
+
+    use ratatui::style::{palette::tailwind, Color};
 
     use super::TatPalette;
 
+    /// The palette for dark terminals
     pub const DEFAULT: TatPalette = TatPalette {
         default_fg: tailwind::SLATE.c200,
         highlighted_fg: tailwind::SLATE.c400,
         highlighted_darker_fg: tailwind::SLATE.c500,
         selected_bg: tailwind::SLATE.c400,
         selected_fg: tailwind::SLATE.c950,
+        monochrome: false,
+    };
+
+    /// The palette for light terminals
+    pub const LIGHT: TatPalette = TatPalette {
+        default_fg: tailwind::SLATE.c800,
+        highlighted_fg: tailwind::SLATE.c600,
+        highlighted_darker_fg: tailwind::SLATE.c400,
+        selected_bg: tailwind::SLATE.c600,
+        selected_fg: tailwind::SLATE.c50,
+        monochrome: false,
+    };
+
+    /// Uses only the basic terminal colors
+    pub const HIGH_CONTRAST: TatPalette = TatPalette {
+        default_fg: Color::White,
+        highlighted_fg: Color::Yellow,
+        highlighted_darker_fg: Color::Cyan,
+        selected_bg: Color::Yellow,
+        selected_fg: Color::Black,
+        monochrome: false,
     };
+
+    /// Uses the terminal's own colors, used when NO_COLOR is set
+    pub const MONOCHROME: TatPalette = TatPalette {
+        default_fg: Color::Reset,
+        highlighted_fg: Color::Reset,
+        highlighted_darker_fg: Color::Reset,
+        selected_bg: Color::Reset,
+        selected_fg: Color::Reset,
+        monochrome: true,
+    };
+
+    /// The built-in themes and their names
+    pub const THEMES: [(&str, TatPalette); 4] = [
+        ("dark", DEFAULT),
+        ("light", LIGHT),
+        ("high-contrast", HIGH_CONTRAST),
+        ("monochrome", MONOCHROME),
+    ];
+
+    /// Returns the palette of the configured theme, or the monochrome palette if NO_COLOR is set
+    pub fn current() -> &'static TatPalette {
+        static CURRENT: OnceLock<TatPalette> = OnceLock::new();
+
+        CURRENT.get_or_init(|| {
+            let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+
+            crate::config::config().palette(no_color)
+        })
+    }
+
+    /// Returns the built-in theme with the given name
+    pub fn built_in(name: &str) -> Option<TatPalette> {
+        THEMES.iter().find(|(theme_name, _)| *theme_name == name).map(|(_, palette)| *palette)
+    }
 }
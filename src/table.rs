@@ -1,10 +1,10 @@
-use std::{fmt::Display, sync::{mpsc::Sender, Arc, Mutex}};
+use std::{collections::{BTreeSet, HashMap}, fmt::Display, sync::{mpsc::Sender, Arc, Mutex}};
 
 #[allow(unused_imports)]
 use cli_log::*;
 use ratatui::{
     layout::{
-        Constraint, Rect
+        Constraint, Flex, Layout, Position, Rect
     },
     style::Stylize,
     symbols::{self, scrollbar::{
@@ -13,14 +13,16 @@ use ratatui::{
     }},
     text::Line,
     widgets::{
-        Block, Borders, Row, Scrollbar, ScrollbarOrientation, ScrollbarState, Table, TableState
+        Block, Borders, Cell, Row, Scrollbar, ScrollbarOrientation, ScrollbarState, Table, TableState
     }, Frame,
 };
 use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
-use crate::{dataset::{DatasetRequest, TatAttributeView, TatAttributeViewRequest}, types::{
+use crate::{dataset::{DatasetRequest, TatAttributeView, TatAttributeViewRequest}, jumps::{TatJumps, TatPosition}, search::{TatSearchMatch, TatSearchResults}, selection::TatRowSelection, session::TatLayerSession, types::{
     TatNavHorizontal, TatNavVertical
 }};
+use crate::config::config;
 use crate::layerschema::TatLayerSchema;
 
 pub const FEATURE_COLUMN_BORDER_FULL: symbols::border::Set = symbols::border::Set {
@@ -33,9 +35,6 @@ pub const FEATURE_COLUMN_BORDER_PREVIEW: symbols::border::Set = symbols::border:
     ..FEATURE_COLUMN_BORDER_FULL
 };
 
-const MIN_COLUMN_LENGTH: i32 = 30;
-const THEORETICAL_MAX_COLUMN_UTF8_BYTE_SIZE: i32 = MIN_COLUMN_LENGTH * 4;
-
 impl Display for TatTable {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let _view = self.attribute_view.as_ref().unwrap();
@@ -55,7 +54,7 @@ impl Display for TatTable {
 
         writeln!(f)?;
 
-        for row in view.iter() {
+        for row in view.rows.iter() {
             for _attr in 0..row.len() {
                 let attr = row.get(_attr).unwrap();
                 let value = match attr {
@@ -79,6 +78,21 @@ impl Display for TatTable {
 
 pub type TableRects = (Rect, Rect, Rect, Rect);
 
+/// The view of a layer which is restored when the layer is shown again
+#[derive(Debug)]
+struct TatLayerView {
+    top_row: u64,
+    first_column: u64,
+    /// The highlighted row and column relative to the visible ones
+    selected: (Option<usize>, Option<usize>),
+    columns: Vec<u64>,
+    pinned: usize,
+    column_widths: HashMap<u64, u64>,
+    marked_rows: TatRowSelection,
+    search_results: Option<TatSearchResults>,
+    jumps: TatJumps,
+}
+
 /// Widget for displaying the attribute table
 pub struct TatTable {
     table_state: TableState,
@@ -96,6 +110,27 @@ pub struct TatTable {
     dataset_request_tx: Sender<DatasetRequest>,
     layer_schemas: Vec<TatLayerSchema>,
     attribute_view: Option<Arc<Mutex<TatAttributeView>>>,
+    /// The shown columns (as indices which include the geometry fields) in the order in which
+    /// they are displayed. Pinned columns are always first.
+    columns: Vec<u64>,
+    /// The number of pinned columns which stay visible when scrolling horizontally
+    pinned: usize,
+    /// Whether the widths of the columns are based on their names and content instead of being
+    /// equal
+    fit_to_content: bool,
+    /// Widths of the columns which have been resized by the user
+    column_widths: HashMap<u64, u64>,
+    /// Rows which have been marked as selected
+    marked_rows: TatRowSelection,
+    /// The row in which visual mode was started (if active)
+    visual_anchor: Option<u64>,
+    search_results: Option<TatSearchResults>,
+    /// Whether the feature column shows the FIDs of the features instead of the row numbers
+    show_fids: bool,
+    /// The marks and the jump list of the current layer
+    jumps: TatJumps,
+    /// The views of the layers which have been shown before, by layer index
+    layer_views: HashMap<usize, TatLayerView>,
 }
 
 impl TatTable {
@@ -126,20 +161,240 @@ impl TatTable {
             dataset_request_tx,
             layer_schemas: vec![],
             attribute_view: None,
+            marked_rows: TatRowSelection::default(),
+            visual_anchor: None,
+            search_results: None,
+            show_fids: false,
+            columns: vec![],
+            pinned: 0,
+            fit_to_content: false,
+            column_widths: HashMap::new(),
+            jumps: TatJumps::default(),
+            layer_views: HashMap::new(),
         }
     }
 
 
     pub fn set_layer_schemas(&mut self, schemas: Vec<TatLayerSchema>) {
         self.layer_schemas = schemas;
+        self.layer_views.clear();
+        self.init_columns();
     }
 
-    /// Sets currently selected layer's index
+    /// Sets currently selected layer's index. The view of the previous layer is stored and the
+    /// view of the new layer is restored if it has been shown before.
     pub fn set_layer_index(&mut self, idx: usize) {
-        self.layer_index = idx;
+        if idx != self.layer_index {
+            self.store_layer_view();
+            self.layer_index = idx;
+            self.restore_layer_view();
+        }
+
         self.on_visible_attributes_changed();
     }
 
+    /// Replaces the schema of a layer after its features have been filtered or sorted. The
+    /// layer is shown from its first row and its marked rows, search results, marks and jumps
+    /// are cleared since the rows no longer hold the same features.
+    pub fn update_layer_schema(&mut self, schema: TatLayerSchema) {
+        let layer_index = schema.index();
+        let Some(old_schema) = self.layer_schemas.get_mut(layer_index) else {
+            return;
+        };
+
+        *old_schema = schema;
+
+        let current = layer_index == self.layer_index;
+        if current {
+            self.store_layer_view();
+        }
+
+        if let Some(view) = self.layer_views.get_mut(&layer_index) {
+            view.top_row = 1;
+            view.selected.0 = Some(0);
+            view.marked_rows.clear();
+            view.search_results = None;
+            view.jumps = TatJumps::default();
+        }
+
+        if current {
+            self.restore_layer_view();
+            self.on_visible_attributes_changed();
+        }
+    }
+
+    /// Hides the highlighted column. The last shown column cannot be hidden.
+    pub fn hide_current_column(&mut self) {
+        self.hide_column(self.current_column());
+    }
+
+    /// Hides the given column (an index which includes the geometry fields). Returns false if
+    /// the column is not shown or is the last shown column.
+    pub fn hide_column(&mut self, col: u64) -> bool {
+        if self.columns.len() <= 1 {
+            return false;
+        }
+
+        let Some(display_col) = self.columns.iter().position(|c| *c == col) else {
+            return false;
+        };
+
+        let current = self.current_column();
+        self.columns.remove(display_col);
+
+        if display_col < self.pinned {
+            self.pinned -= 1;
+        }
+
+        let focus = if current == col {
+            self.columns.get(display_col).or(self.columns.last()).copied()
+        } else {
+            Some(current)
+        };
+
+        self.on_columns_changed(focus);
+
+        true
+    }
+
+    /// Shows all hidden columns, they are added to the end in their original order
+    pub fn show_all_columns(&mut self) {
+        let focus = self.columns.get(self.current_display_column() as usize).copied();
+
+        if let Some(schema) = self.layer_schema() {
+            let hidden: Vec<u64> = (0..schema.field_count()).filter(|col| !self.columns.contains(col)).collect();
+            self.columns.extend(hidden);
+        }
+
+        self.on_columns_changed(focus);
+    }
+
+    /// Returns the number of hidden columns
+    pub fn hidden_column_count(&self) -> u64 {
+        match self.layer_schema() {
+            Some(schema) => schema.field_count() - self.columns.len() as u64,
+            None => 0,
+        }
+    }
+
+    /// Moves the highlighted column one step to the left or right. Pinned columns can only be
+    /// reordered among each other, as can unpinned columns.
+    pub fn move_current_column(&mut self, left: bool) {
+        let display_col = self.current_display_column() as usize;
+        let (start, end) = if display_col < self.pinned { (0, self.pinned) } else { (self.pinned, self.columns.len()) };
+
+        let target = if left {
+            if display_col == start {
+                return;
+            }
+            display_col - 1
+        } else {
+            if display_col + 1 >= end {
+                return;
+            }
+            display_col + 1
+        };
+
+        self.columns.swap(display_col, target);
+        self.on_columns_changed(Some(self.columns[target]));
+    }
+
+    /// Pins the highlighted column so that it stays visible on the left, or unpins it if it is
+    /// already pinned. Returns false if there is no room to pin more columns.
+    pub fn toggle_pin_current_column(&mut self) -> bool {
+        let display_col = self.current_display_column() as usize;
+        let col = match self.columns.get(display_col) {
+            Some(col) => *col,
+            None => return true,
+        };
+
+        if display_col < self.pinned {
+            self.columns.remove(display_col);
+            self.pinned -= 1;
+            self.columns.insert(self.pinned, col);
+        } else {
+            // at least one column has to be left for scrolling
+            let pinned_width = self.pinned_width();
+            if self.pinned + 1 >= self.columns.len()
+                || pinned_width + self.column_width(col) + config().min_column_width > self.table_rect.width as u64
+            {
+                return false;
+            }
+
+            self.columns.remove(display_col);
+            self.columns.insert(self.pinned, col);
+            self.pinned += 1;
+        }
+
+        self.on_columns_changed(Some(col));
+
+        true
+    }
+
+    /// Returns whether the column is pinned
+    pub fn column_pinned(&self, col: u64) -> bool {
+        self.columns[..self.pinned].contains(&col)
+    }
+
+    /// Shows all columns in their original order and unpins them
+    pub fn reset_columns(&mut self) {
+        let focus = self.columns.get(self.current_display_column() as usize).copied();
+
+        self.init_columns();
+        self.on_columns_changed(focus);
+    }
+
+    /// Returns the shown columns in display order
+    pub fn columns(&self) -> &[u64] {
+        &self.columns
+    }
+
+    /// Switches between fitting the columns to their names and content and equally wide columns
+    pub fn toggle_fit_to_content(&mut self) {
+        self.fit_to_content = !self.fit_to_content;
+
+        let focus = self.columns.get(self.current_display_column() as usize).copied();
+        self.on_columns_changed(focus);
+    }
+
+    /// Switches the feature column between showing row numbers and FIDs
+    pub fn toggle_show_fids(&mut self) {
+        self.show_fids = !self.show_fids;
+    }
+
+    /// Returns whether the feature column shows FIDs
+    pub fn show_fids(&self) -> bool {
+        self.show_fids
+    }
+
+    /// Returns the FIDs of the visible rows
+    pub fn visible_fids(&self) -> Vec<u64> {
+        match self.attribute_view.as_ref() {
+            Some(view) => view.lock().unwrap().fids.clone(),
+            None => vec![],
+        }
+    }
+
+    /// Returns whether the columns are fitted to their content
+    pub fn fit_to_content(&self) -> bool {
+        self.fit_to_content
+    }
+
+    /// Widens or narrows the highlighted column
+    pub fn resize_current_column(&mut self, widen: bool) {
+        let col = match self.columns.get(self.current_display_column() as usize) {
+            Some(col) => *col,
+            None => return,
+        };
+
+        let width = self.column_width(col);
+        let width = if widen { width + config().column_resize_step } else { width.saturating_sub(config().column_resize_step) };
+        let max_width = (self.table_rect.width as u64).max(config().min_fitted_column_width + 1);
+
+        self.column_widths.insert(col, width.clamp(config().min_fitted_column_width + 1, max_width));
+        self.on_columns_changed(Some(col));
+    }
+
     /// Returns currently selected row's index
     pub fn current_row(&self) -> u64 {
         self.top_row + self.relative_highlighted_row()
@@ -153,6 +408,11 @@ impl TatTable {
         )
     }
 
+    /// Returns whether the values of the current column are lists or JSON
+    pub fn current_column_nested(&self) -> bool {
+        self.layer_schema().is_some_and(|schema| schema.field_is_nested(self.current_column() as i32))
+    }
+
     /// Returns the index of the highlighted column from the current visible column
     pub fn relative_highlighted_column(&self) -> u64 {
         // see above (relative_highlighted_row)
@@ -176,41 +436,45 @@ impl TatTable {
                 self.update_h_scrollbar();
             },
             TatNavHorizontal::End => {
-                self.set_first_column(self.layer_schema().unwrap().field_count() as i64 - self.visible_columns() as i64);
+                self.set_first_column(self.max_first_column());
                 self.table_state.select_column(Some(self.visible_columns() as usize - 1));
                 self.update_h_scrollbar();
             },
             TatNavHorizontal::RightOne => {
-                let relative_col = self.relative_highlighted_column();
-                let real_col = self.current_column();
-
-                if relative_col == self.visible_columns() - 1 {
-                    let cols =  self.layer_schema().unwrap().field_count();
+                let display_col = self.current_display_column();
 
-                    if real_col == cols {
-                        self.update_h_scrollbar();
-                        return;
-                    } else {
-                        self.set_first_column(self.first_column as i64 + 1);
-                    }
-                    self.update_h_scrollbar();
-                    return;
+                if display_col + 1 < self.columns.len() as u64 {
+                    self.select_display_column(display_col + 1);
                 }
-                self.table_state.select_next_column();
+                self.update_h_scrollbar();
             }
             TatNavHorizontal::LeftOne => {
-                let relative_col = self.relative_highlighted_column();
-                if relative_col == 0 {
-                    if self.first_column == 0 {
-                        self.update_h_scrollbar();
-                        return;
-                    } else {
-                        self.set_first_column(self.first_column as i64 - 1);
-                    }
-                    self.update_h_scrollbar();
-                    return;
+                let display_col = self.current_display_column();
+
+                if display_col > 0 {
+                    self.select_display_column(display_col - 1);
+                }
+                self.update_h_scrollbar();
+            }
+            TatNavHorizontal::Specific(col) => {
+                // hidden columns cannot be navigated to
+                if let Some(display_col) = self.columns.iter().position(|c| *c == col) {
+                    self.select_display_column(display_col as u64);
+                }
+                self.update_h_scrollbar();
+            }
+            TatNavHorizontal::Fraction(fraction) => {
+                let pinned = self.pinned_count() as i64;
+                let scrollable = (self.max_first_column() - pinned).max(0) as f64;
+
+                self.set_first_column(pinned + (scrollable * fraction.clamp(0.0, 1.0)).round() as i64);
+
+                // fewer columns may fit after scrolling
+                let visible = self.visible_columns();
+                if self.relative_highlighted_column() >= visible {
+                    self.table_state.select_column(Some(visible.saturating_sub(1) as usize));
                 }
-                self.table_state.select_previous_column();
+                self.update_h_scrollbar();
             }
         }
     }
@@ -283,6 +547,11 @@ impl TatTable {
             TatNavVertical::MouseScrollUp => {
                 nav_by(-(visible_rows / 3));
             },
+            TatNavVertical::Fraction(fraction) => {
+                let scrollable = (self.max_top_row() - 1).max(0) as f64;
+
+                self.set_top_row(1 + (scrollable * fraction.clamp(0.0, 1.0)).round() as i64);
+            },
             TatNavVertical::Specific(row) => {
                 if row >= self.layer_schema().unwrap().feature_count() as i64 {
                     self.nav_v(TatNavVertical::Last);
@@ -291,7 +560,9 @@ impl TatTable {
                 if self.row_visible(row as i64) {
                     self.table_state.select(Some(self.feature_relative_row(row).unwrap() as usize));
                 } else {
-                    self.set_top_row(row as i64 - self.relative_highlighted_row() as i64);
+                    // the highlighted row may be out of the visible rows if the table was shrunk
+                    let relative_row = self.relative_highlighted_row().min(visible_rows.saturating_sub(1) as u64);
+                    self.set_top_row(row as i64 - relative_row as i64);
                     self.table_state.select(Some(self.feature_relative_row(row).unwrap() as usize));
                 }
             },
@@ -300,34 +571,266 @@ impl TatTable {
         self.update_v_scrollbar();
     }
 
+    /// Selects the cell at the given position on the screen. Clicking the header only selects the
+    /// column and clicking the feature column only selects the row. Returns whether a cell, a
+    /// header or a row number was clicked.
+    pub fn click(&mut self, column: u16, row: u16, preview: bool) -> bool {
+        if self.layer_schema().is_none() || self.table_rect.is_empty() {
+            return false;
+        }
+
+        // see render() and render_preview() for where the header and rows are drawn
+        let header_y = self.table_rect.y + if preview { 1 } else { 2 };
+        let first_row_y = header_y + 1;
+
+        let clicked_row = if row >= first_row_y && ((row - first_row_y) as u64) < self.visible_rows() {
+            Some((row - first_row_y) as usize)
+        } else {
+            None
+        };
+
+        let clicked_column = if row == header_y || clicked_row.is_some() {
+            self.column_at(column, preview)
+        } else {
+            None
+        };
+
+        let in_feature_column = self.feature_col_rect.contains(Position { x: column, y: row });
+
+        match (clicked_row, clicked_column) {
+            (Some(relative_row), Some(relative_col)) => {
+                self.table_state.select(Some(relative_row));
+                self.table_state.select_column(Some(relative_col));
+            },
+            (Some(relative_row), None) if in_feature_column => self.table_state.select(Some(relative_row)),
+            (None, Some(relative_col)) => self.table_state.select_column(Some(relative_col)),
+            _ => return false,
+        }
+
+        self.update_v_scrollbar();
+        self.update_h_scrollbar();
+
+        true
+    }
+
+    /// Returns the visible column (counted from the first pinned column) at the given x
+    /// coordinate. The spacing after a column belongs to the column.
+    fn column_at(&self, x: u16, preview: bool) -> Option<usize> {
+        let width = if preview { self.table_rect.width.saturating_sub(1) } else { self.table_rect.width };
+
+        if x < self.table_rect.x || x >= self.table_rect.x + width {
+            return None;
+        }
+
+        let visible_columns = self.visible_column_indices();
+
+        // the same layout as the one ratatui uses for the table
+        let areas = Layout::horizontal(self.column_constraints(&visible_columns))
+            .flex(Flex::Start)
+            .spacing(1)
+            .split(Rect::new(self.table_rect.x, 0, width, 1));
+
+        let column = areas.iter().rposition(|area| area.x <= x)?;
+        let area = areas[column];
+
+        // clicks past the last column do not select it
+        if column + 1 == areas.len() && x >= area.right() {
+            return None;
+        }
+
+        Some(column)
+    }
+
+    /// Returns the areas of the vertical and horizontal scrollbars, empty in the preview
+    pub fn scrollbar_areas(&self) -> (Rect, Rect) {
+        (self.v_scroll_area, self.h_scroll_area)
+    }
+
     /// Returns the currently selected cell's value as a string (if any)
     pub fn selected_value(&self) -> Option<String> {
-        if let Some(_view) = self.attribute_view.as_ref() {
-            let view = _view.lock().unwrap();
+        let view = self.attribute_view.as_ref()?.lock().unwrap();
+
+        // a filtered layer may have no rows
+        let row = view.rows.get(self.relative_highlighted_row() as usize)?;
+
+        row.get(self.relative_highlighted_column() as usize)?.clone()
+    }
+
+    /// Marks or unmarks the current row
+    pub fn toggle_mark(&mut self) {
+        let row = self.current_row();
 
-            let row = view.get(self.relative_highlighted_row() as usize).unwrap();
-            let value = row.get(self.relative_highlighted_column() as usize).unwrap();
+        if !self.marked_rows.remove(row) {
+            self.marked_rows.insert(row);
+        }
+    }
 
-            return value.clone();
+    /// Starts visual mode from the current row. If visual mode is already active, it is ended and
+    /// the rows in the range are marked.
+    pub fn toggle_visual(&mut self) {
+        match self.visual_anchor.take() {
+            Some(anchor) => self.marked_rows.insert_range(anchor, self.current_row()),
+            None => self.visual_anchor = Some(self.current_row()),
         }
+    }
 
-        None
-        // self.layer_schema()?.get_value_by_row(self.current_row() as usize, self.current_column() as usize)
+    /// Returns whether visual mode is active
+    pub fn visual_active(&self) -> bool {
+        self.visual_anchor.is_some()
     }
 
-    /// Resets the table's state
-    pub fn reset(&mut self) {
-        self.top_row = 1;
-        self.first_column = 0;
-        self.table_state.select_first_column();
-        self.table_state.select_first();
-        self.table_rect = Rect::default();
+    /// Ends visual mode without marking its rows
+    pub fn cancel_visual(&mut self) {
+        self.visual_anchor = None;
+    }
+
+    /// Clears the whole selection
+    pub fn clear_selection(&mut self) {
+        self.marked_rows.clear();
+        self.visual_anchor = None;
+    }
+
+    /// Returns whether the row is selected, i.e. marked or within the visual mode range
+    pub fn row_selected(&self, row: u64) -> bool {
+        if self.marked_rows.contains(row) {
+            return true;
+        }
+
+        match self.visual_anchor {
+            Some(anchor) => {
+                let current = self.current_row();
+                row >= anchor.min(current) && row <= anchor.max(current)
+            },
+            None => false,
+        }
+    }
+
+    /// Returns the number of selected rows
+    pub fn selected_count(&self) -> u64 {
+        match self.visual_anchor {
+            Some(anchor) => self.marked_rows.len_with(anchor, self.current_row()),
+            None => self.marked_rows.len(),
+        }
+    }
+
+    /// Returns all selected rows in ascending order. Each row is listed, so this is only meant for
+    /// acting on the selected features e.g. copying them.
+    pub fn selected_rows(&self) -> Vec<u64> {
+        match self.visual_anchor {
+            Some(anchor) => {
+                let mut rows = self.marked_rows.clone();
+                rows.insert_range(anchor, self.current_row());
+                rows.rows().collect()
+            },
+            None => self.marked_rows.rows().collect(),
+        }
+    }
+
+    /// Returns the selected rows, or the current row if nothing is selected
+    pub fn selected_rows_or_current(&self) -> Vec<u64> {
+        let rows = self.selected_rows();
+
+        if rows.is_empty() {
+            return vec![self.current_row()];
+        }
+
+        rows
+    }
+
+    /// Sets the results of the latest search, the matches are highlighted
+    pub fn set_search_results(&mut self, results: TatSearchResults) {
+        self.search_results = Some(results);
+    }
+
+    /// Returns the results of the latest search in the current layer (if any)
+    pub fn search_results(&self) -> Option<&TatSearchResults> {
+        self.search_results.as_ref().filter(|results| results.layer_index == self.layer_index)
+    }
+
+    /// Moves to the next (or the previous) cell which matches the latest search. Matches in
+    /// hidden columns are skipped. Returns false if there are no matches.
+    pub fn jump_to_match(&mut self, backwards: bool) -> bool {
+        let next = self.search_results().and_then(|results| {
+            let (mut row, mut column) = (self.current_row(), self.current_column());
+
+            for _ in 0..results.matches.len() {
+                let m = results.next_match(row, column, backwards)?;
+
+                if self.columns.contains(&m.column) {
+                    return Some(m);
+                }
+
+                (row, column) = (m.row, m.column);
+            }
+
+            None
+        });
+
+        match next {
+            Some(m) => {
+                self.record_jump();
+                self.go_to((m.row, m.column));
+
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Sets the mark with the given name to the current cell
+    pub fn set_mark(&mut self, name: char) {
+        let position = self.position();
+        self.jumps.set_mark(name, position);
+    }
+
+    /// Jumps to the cell of the mark with the given name. Returns false if the mark has not been
+    /// set in the current layer.
+    pub fn jump_to_mark(&mut self, name: char) -> bool {
+        let Some(position) = self.jumps.mark(name) else {
+            return false;
+        };
+
+        self.record_jump();
+        self.go_to(position);
+
+        true
+    }
+
+    /// Adds the current cell to the jump list, should be called before jumping far away
+    pub fn record_jump(&mut self) {
+        let position = self.position();
+        self.jumps.push(position);
+    }
+
+    /// Moves back (or forward) in the jump list. Returns false if there is nowhere to move.
+    pub fn jump_in_list(&mut self, forward: bool) -> bool {
+        let current = self.position();
+        let target = if forward { self.jumps.forward(current) } else { self.jumps.back(current) };
+
+        match target {
+            Some(position) => {
+                self.go_to(position);
+
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Returns the current cell
+    fn position(&self) -> TatPosition {
+        (self.current_row(), self.current_column())
+    }
+
+    /// Moves to the cell, the column is not changed if it is hidden
+    fn go_to(&mut self, (row, column): TatPosition) {
+        self.nav_v(TatNavVertical::Specific(row as i64));
+        self.nav_h(TatNavHorizontal::Specific(column));
     }
 
     /// Sets the areas which the table renders itself in
     pub fn set_rects(&mut self, (table_rect, feature_col_rect, v_scroll_area, h_scroll_area): TableRects) {
         let old_row = self.current_row();
-        let first_update = self.table_rect.is_empty();
 
         let rect_changed = if self.table_rect != table_rect {
             true
@@ -343,6 +846,7 @@ impl TatTable {
             self.v_scroll_area = v_scroll_area;
             self.h_scroll_area = h_scroll_area;
 
+            self.clamp_first_column();
             self.update_v_scrollbar();
             self.update_h_scrollbar();
 
@@ -352,9 +856,8 @@ impl TatTable {
                 self.on_visible_attributes_changed();
             }
 
-            if !first_update {
-                self.nav_v(TatNavVertical::Specific(old_row as i64));
-            }
+            // keeps the highlighted row visible, e.g. when a restored view is first laid out
+            self.nav_v(TatNavVertical::Specific(old_row as i64));
         }
     }
 
@@ -425,19 +928,42 @@ impl TatTable {
         // HACK: this is really hacky, probably table should only have one rect to begin with and then the table_rect
         // and fid_col_rect are calculated from that in here, not in Tat
         let union = self.table_rect.union(self.feature_col_rect);
+        let mut title = format!("{}", if self.layer_schema().is_some() { self.layer_schema().unwrap().name() } else {"NO LAYER!!!"});
+        let selected = self.selected_count();
+
+        if self.visual_active() {
+            title = format!("{} - VISUAL ({} selected)", title, selected);
+        } else if selected > 0 {
+            title = format!("{} ({} selected)", title, selected);
+        }
+
+        let hidden = self.hidden_column_count();
+        if hidden > 0 {
+            title = format!("{} ({} column(s) hidden)", title, hidden);
+        }
+
+        if let Some(results) = self.search_results() {
+            let current = TatSearchMatch { row: self.current_row(), column: self.current_column() };
+
+            title = match results.matches.binary_search(&current) {
+                Ok(i) => format!("{} - match {}/{} for \"{}\"", title, i + 1, results.matches.len(), results.query.text),
+                Err(_) => format!("{} - {} matches for \"{}\"", title, results.matches.len(), results.query.text),
+            };
+        }
+
         let block = Block::new()
             .title(
                 Line::raw(
-                    format!("{}", if self.layer_schema().is_some() { self.layer_schema().unwrap().name() } else {"NO LAYER!!!"})
+                    title
                 ).centered().bold().underlined(),
             )
             .title_bottom(
                 Line::raw(
-                    crate::shared::SHOW_HELP
+                    crate::shared::show_help_hint()
                 ).centered(),
             )
             .borders(Borders::BOTTOM)
-            .border_style(crate::shared::palette::DEFAULT.default_style());
+            .border_style(crate::shared::palette::current().default_style());
 
         frame.render_widget(block, union);
     }
@@ -452,8 +978,7 @@ impl TatTable {
             layer_index: self.layer_index,
             top_row: self.top_row,
             bottom_row: self.bottom_row(),
-            first_column: self.first_column,
-            last_column: self.first_column + self.visible_columns(),
+            columns: self.visible_column_indices(),
             total_geom_fields: self.layer_schema().unwrap().geom_fields().len(),
         }
     }
@@ -469,7 +994,348 @@ impl TatTable {
 
     /// Returns the currently selected column index which can be used in TatLayer
     fn current_column(&self) -> u64 {
-        self.first_column + self.relative_highlighted_column()
+        self.columns.get(self.current_display_column() as usize).copied().unwrap_or(0)
+    }
+
+    /// Returns the position of the currently selected column in the display order
+    fn current_display_column(&self) -> u64 {
+        let relative_col = self.relative_highlighted_column();
+        let pinned = self.pinned_count();
+
+        if relative_col < pinned {
+            relative_col
+        } else {
+            self.first_column + relative_col - pinned
+        }
+    }
+
+    /// Returns the indices of the currently visible columns in display order, i.e. the pinned
+    /// columns followed by the scrolled ones
+    fn visible_column_indices(&self) -> Vec<u64> {
+        let pinned = self.pinned_count();
+        let scrolled = self.first_column..self.first_column + self.visible_columns() - pinned;
+
+        (0..pinned).chain(scrolled)
+            .filter_map(|display_col| self.columns.get(display_col as usize).copied())
+            .collect()
+    }
+
+    /// Returns the number of pinned columns which are actually displayed as pinned. Room for at
+    /// least one column is always left for scrolling.
+    fn pinned_count(&self) -> u64 {
+        let width = self.table_rect.width as u64;
+        let mut used = 0;
+        let mut count = 0;
+
+        while count < self.pinned && count + 1 < self.columns.len() {
+            used += self.display_column_width(count as u64);
+
+            if used + config().min_column_width > width {
+                break;
+            }
+
+            count += 1;
+        }
+
+        count as u64
+    }
+
+    /// Returns the combined width of the displayed pinned columns
+    fn pinned_width(&self) -> u64 {
+        (0..self.pinned_count()).map(|display_col| self.display_column_width(display_col)).sum()
+    }
+
+    /// Returns the number of scrolled columns which fit in the table if the given column (in
+    /// display order) is the first one. At least one column is counted even if it does not fit.
+    fn scrolled_columns_from(&self, first_column: u64) -> u64 {
+        let available = (self.table_rect.width as u64).saturating_sub(self.pinned_width());
+        let mut used = 0;
+        let mut count = 0;
+
+        for display_col in first_column..self.columns.len() as u64 {
+            used += self.display_column_width(display_col);
+
+            if used > available {
+                break;
+            }
+
+            count += 1;
+        }
+
+        if count == 0 && available > 0 && first_column < self.columns.len() as u64 {
+            return 1;
+        }
+
+        count
+    }
+
+    /// Returns the first scrolled column (in display order) when scrolled all the way right
+    fn max_first_column(&self) -> i64 {
+        let pinned = self.pinned_count();
+        let available = (self.table_rect.width as u64).saturating_sub(self.pinned_width());
+        let mut used = 0;
+        let mut first_column = self.columns.len() as u64;
+
+        while first_column > pinned {
+            used += self.display_column_width(first_column - 1);
+
+            if used > available {
+                break;
+            }
+
+            first_column -= 1;
+        }
+
+        // the last column is shown even if it does not fit
+        if first_column == self.columns.len() as u64 && first_column > pinned {
+            first_column -= 1;
+        }
+
+        first_column as i64
+    }
+
+    /// Returns the width reserved for a column (including the spacing between columns)
+    fn column_width(&self, col: u64) -> u64 {
+        if let Some(width) = self.column_widths.get(&col) {
+            return *width;
+        }
+
+        if !self.fit_to_content {
+            return config().min_column_width;
+        }
+
+        let schema = match self.layer_schema() {
+            Some(schema) => schema,
+            None => return config().min_column_width,
+        };
+
+        let header_width = schema.field_name_by_id(col as i32).map_or(0, |name| name.width()) as u64;
+        let content_width = schema.content_width(col) as u64;
+        let missing_width = crate::shared::MISSING_VALUE.width() as u64;
+
+        header_width.max(content_width).max(missing_width).clamp(config().min_fitted_column_width, config().max_fitted_column_width) + 1
+    }
+
+    /// Returns the width reserved for a column in the given display position
+    fn display_column_width(&self, display_col: u64) -> u64 {
+        self.columns.get(display_col as usize).map_or(0, |col| self.column_width(*col))
+    }
+
+    /// Returns whether the column has a fixed width instead of sharing the space equally
+    fn column_has_fixed_width(&self, col: u64) -> bool {
+        self.fit_to_content || self.column_widths.contains_key(&col)
+    }
+
+    /// Highlights the column in the given display position, scrolling if necessary
+    fn select_display_column(&mut self, display_col: u64) {
+        let pinned = self.pinned_count();
+
+        if display_col < pinned {
+            self.table_state.select_column(Some(display_col as usize));
+            return;
+        }
+
+        let mut first_column = self.first_column.max(pinned);
+
+        if display_col < first_column {
+            first_column = display_col;
+        } else {
+            while display_col >= first_column + self.scrolled_columns_from(first_column) && first_column < display_col {
+                first_column += 1;
+            }
+        }
+
+        self.set_first_column(first_column as i64);
+        self.table_state.select_column(Some((pinned + display_col - self.first_column) as usize));
+    }
+
+    /// Returns the views of the current layer and the layers which have been shown before, for
+    /// storing them in the session
+    pub fn layer_sessions(&self) -> Vec<TatLayerSession> {
+        let current = self.layer_schema().map(|schema| {
+            TatLayerSession {
+                name: schema.name().to_string(),
+                top_row: self.top_row,
+                first_column: self.first_column,
+                selected: (self.relative_highlighted_row() as usize, self.table_state.selected_column().unwrap_or(0)),
+                columns: self.columns.clone(),
+                pinned: self.pinned,
+                column_widths: self.column_widths.clone(),
+                marked_rows: self.marked_rows.rows().collect(),
+                marked_fids: BTreeSet::new(),
+                filter: schema.attribute_filter().map(|filter| filter.to_string()),
+                sort: schema.sort_order().cloned(),
+            }
+        });
+
+        let stored = self.layer_views.iter().filter_map(|(layer_index, view)| {
+            let schema = self.layer_schemas.get(*layer_index)?;
+
+            Some(
+                TatLayerSession {
+                    name: schema.name().to_string(),
+                    top_row: view.top_row,
+                    first_column: view.first_column,
+                    selected: (view.selected.0.unwrap_or(0), view.selected.1.unwrap_or(0)),
+                    columns: view.columns.clone(),
+                    pinned: view.pinned,
+                    column_widths: view.column_widths.clone(),
+                    marked_rows: view.marked_rows.rows().collect(),
+                    marked_fids: BTreeSet::new(),
+                    filter: schema.attribute_filter().map(|filter| filter.to_string()),
+                    sort: schema.sort_order().cloned(),
+                }
+            )
+        });
+
+        current.into_iter().chain(stored).collect()
+    }
+
+    /// Restores the views of the layers from a previous session. The views of layers which no
+    /// longer exist or whose fields have changed are ignored, as are marked rows which no longer
+    /// exist.
+    pub fn restore_layer_sessions(&mut self, sessions: &[TatLayerSession]) {
+        for session in sessions {
+            let Some(layer_index) = self.layer_schemas.iter().position(|schema| schema.name() == session.name) else {
+                continue;
+            };
+
+            let schema = &self.layer_schemas[layer_index];
+            let columns_valid = !session.columns.is_empty()
+                && session.pinned <= session.columns.len()
+                && session.columns.iter().all(|col| *col < schema.field_count())
+                && session.columns.iter().collect::<BTreeSet<_>>().len() == session.columns.len();
+
+            if !columns_valid {
+                continue;
+            }
+
+            let feature_count = schema.feature_count();
+
+            self.layer_views.insert(
+                layer_index,
+                TatLayerView {
+                    top_row: session.top_row,
+                    first_column: session.first_column,
+                    selected: (Some(session.selected.0), Some(session.selected.1)),
+                    columns: session.columns.clone(),
+                    pinned: session.pinned,
+                    column_widths: session.column_widths.iter()
+                        .filter(|(col, _)| session.columns.contains(col))
+                        .map(|(col, width)| (*col, *width))
+                        .collect(),
+                    marked_rows: session.marked_rows.iter().copied().filter(|row| (1..=feature_count).contains(row)).collect(),
+                    search_results: None,
+                    jumps: TatJumps::default(),
+                },
+            );
+        }
+
+        if self.layer_views.contains_key(&self.layer_index) {
+            self.restore_layer_view();
+            self.on_visible_attributes_changed();
+        }
+    }
+
+    /// Stores the view of the current layer so that it can be restored later
+    fn store_layer_view(&mut self) {
+        let view = TatLayerView {
+            top_row: self.top_row,
+            first_column: self.first_column,
+            selected: (self.table_state.selected(), self.table_state.selected_column()),
+            columns: std::mem::take(&mut self.columns),
+            pinned: self.pinned,
+            column_widths: std::mem::take(&mut self.column_widths),
+            marked_rows: std::mem::take(&mut self.marked_rows),
+            search_results: self.search_results.take(),
+            jumps: std::mem::take(&mut self.jumps),
+        };
+
+        self.layer_views.insert(self.layer_index, view);
+    }
+
+    /// Restores the stored view of the current layer, or shows the layer from the beginning if
+    /// it has not been shown before. Visual mode is always ended.
+    fn restore_layer_view(&mut self) {
+        self.visual_anchor = None;
+
+        let Some(view) = self.layer_views.remove(&self.layer_index) else {
+            self.init_columns();
+            self.clear_selection();
+            self.search_results = None;
+            self.jumps = TatJumps::default();
+            self.top_row = 1;
+            self.table_state.select_first();
+            self.update_v_scrollbar();
+            self.update_h_scrollbar();
+            return;
+        };
+
+        self.columns = view.columns;
+        self.pinned = view.pinned;
+        self.column_widths = view.column_widths;
+        self.marked_rows = view.marked_rows;
+        self.search_results = view.search_results;
+        self.jumps = view.jumps;
+        self.first_column = view.first_column;
+        self.top_row = view.top_row.clamp(1, self.max_top_row().max(1) as u64);
+        self.clamp_first_column();
+
+        let (mut row, mut column) = view.selected;
+
+        // the table may have been resized while another layer was shown, unless it has not been
+        // laid out yet
+        if !self.table_rect.is_empty() {
+            row = row.map(|row| row.min(self.visible_rows().saturating_sub(1) as usize));
+            column = column.map(|column| column.min(self.visible_columns().saturating_sub(1) as usize));
+        }
+
+        self.table_state.select(row);
+        self.table_state.select_column(column);
+
+        self.update_v_scrollbar();
+        self.update_h_scrollbar();
+    }
+
+    /// Shows all columns of the current layer in their original order
+    fn init_columns(&mut self) {
+        self.columns = match self.layer_schema() {
+            Some(schema) => (0..schema.field_count()).collect(),
+            None => vec![],
+        };
+        self.pinned = 0;
+        self.column_widths.clear();
+        self.first_column = 0;
+        self.table_state.select_first_column();
+    }
+
+    /// Keeps the first scrolled column within its bounds, which depend on the number of visible
+    /// and pinned columns
+    fn clamp_first_column(&mut self) {
+        let min_first_column = self.pinned_count() as i64;
+
+        self.first_column = (self.first_column as i64).clamp(min_first_column, self.max_first_column().max(min_first_column)) as u64;
+    }
+
+    /// Updates the view after the shown columns or their order has changed and highlights the
+    /// given column (if any)
+    fn on_columns_changed(&mut self, focus: Option<u64>) {
+        self.clamp_first_column();
+
+        let visible = self.visible_columns();
+
+        if let Some(selected) = self.table_state.selected_column()
+            && selected as u64 >= visible
+        {
+            self.table_state.select_column(Some(visible.saturating_sub(1) as usize));
+        }
+
+        if let Some(col) = focus {
+            self.nav_h(TatNavHorizontal::Specific(col));
+        }
+
+        self.update_h_scrollbar();
+        self.on_visible_attributes_changed();
     }
 
     /// Returns the index of the highlighted row from the current visible rows
@@ -501,8 +1367,8 @@ impl TatTable {
             return;
         }
 
-        self.h_scroll = ScrollbarState::new(self.layer_schema().unwrap().field_count() as usize - self.visible_columns() as usize + 1);
-        self.h_scroll = self.h_scroll.position(self.first_column as usize);
+        self.h_scroll = ScrollbarState::new((self.max_first_column() as u64 - self.pinned_count() + 1) as usize);
+        self.h_scroll = self.h_scroll.position(self.first_column.saturating_sub(self.pinned_count()) as usize);
     }
 
     /// Returns the relative row of a feature in the currently visible rows
@@ -532,7 +1398,8 @@ impl TatTable {
             return;
         }
 
-        let max_first_column: i64 = self.layer_schema().unwrap().field_count() as i64 - self.visible_columns() as i64;
+        let max_first_column: i64 = self.max_first_column();
+        let min_first_column: i64 = self.pinned_count() as i64;
 
         if col >= max_first_column {
             self.first_column = max_first_column as u64;
@@ -540,8 +1407,8 @@ impl TatTable {
             return;
         }
 
-        if col <= 0 {
-            self.first_column = 0;
+        if col <= min_first_column {
+            self.first_column = min_first_column as u64;
             self.on_visible_attributes_changed();
             return;
         }
@@ -600,10 +1467,16 @@ impl TatTable {
 
         let schema = self.layer_schema().unwrap();
 
-        let header_items: Vec<&str> = (self.first_column..self.first_column + self.visible_columns())
+        let visible_columns = self.visible_column_indices();
+
+        let header_items: Vec<Cell> = visible_columns.iter()
             .map(|i| {
-                if let Some(field_name) = schema.field_name_by_id(i as i32) {
-                    field_name
+                if let Some(field_name) = schema.field_name_by_id(*i as i32) {
+                    if self.column_pinned(*i) {
+                        Cell::from(field_name).bold()
+                    } else {
+                        Cell::from(field_name)
+                    }
                 } else {
                     panic!();
                 }
@@ -611,7 +1484,7 @@ impl TatTable {
             .collect();
 
         let header = Row::new(header_items);
-        let widths: Vec<Constraint> = (0..self.visible_columns()).map(|_| Constraint::Fill(1)).collect();
+        let widths = self.column_constraints(&visible_columns);
 
 
         if self.attribute_view.is_none() {
@@ -625,36 +1498,63 @@ impl TatTable {
         if let Ok(v) = _v.lock() {
             let mut rows: Vec<Row> = vec![];
 
-            for feature in v.iter() {
-                rows.push(Row::new(feature.iter().map(|attr| {
-                    if let Some(attribute) = attr {
-                        let squish: bool = if attribute.len() > THEORETICAL_MAX_COLUMN_UTF8_BYTE_SIZE as usize {
-                            true
-                        } else if attribute.chars().count() > MIN_COLUMN_LENGTH as usize {
+            for (i, feature) in v.rows.iter().enumerate() {
+                let style = if self.row_selected(self.top_row + i as u64) {
+                    crate::shared::palette::current().marked_style()
+                } else {
+                    crate::shared::palette::current().default_style()
+                };
+
+                let row = self.top_row + i as u64;
+                let search_results = self.search_results();
+
+                // the view is updated asynchronously, so it may still hold the cells of columns
+                // which are no longer visible
+                rows.push(Row::new(feature.iter().zip(&visible_columns).enumerate().map(|(j, (attr, column))| {
+                    // lists and JSON are summarized, the full value is shown in the value pop-up
+                    let summarized = v.summaries.get(&(i, j)).map(String::as_str);
+
+                    let text = if let Some(attribute) = summarized.or(attr.as_deref()) {
+                        let max_length = if self.column_has_fixed_width(*column) {
+                            self.column_width(*column).saturating_sub(2) as usize
+                        } else {
+                            config().min_column_width as usize
+                        };
+
+                        // a UTF-8 character takes at most four bytes
+                        let squish: bool = if attribute.len() > max_length * 4 {
                             true
+                        } else if self.column_has_fixed_width(*column) {
+                            attribute.graphemes(true).count() > max_length + 1
                         } else {
-                            false
+                            attribute.chars().count() > max_length
                         };
 
                         if squish {
                             let graph = attribute.graphemes(true);
-                            let substr: String = graph.into_iter().take(MIN_COLUMN_LENGTH as usize).collect();
-                            return format!("{substr}…");
+                            let substr: String = graph.into_iter().take(max_length).collect();
+                            format!("{substr}…")
                         } else {
                             attribute.to_string()
                         }
                     } else {
-                        return crate::shared::MISSING_VALUE.to_string();
+                        crate::shared::MISSING_VALUE.to_string()
+                    };
+
+                    if search_results.is_some_and(|results| results.contains(row, *column)) {
+                        Cell::from(text).style(crate::shared::palette::current().search_match_style())
+                    } else {
+                        Cell::from(text)
                     }
-                })));
+                })).style(style));
             }
 
             let table = Table::new(rows, widths)
                 .header(header.underlined())
-                .style(crate::shared::palette::DEFAULT.default_style())
-                .column_highlight_style(crate::shared::palette::DEFAULT.highlighted_darker_fg())
-                .row_highlight_style(crate::shared::palette::DEFAULT.highlighted_darker_fg())
-                .cell_highlight_style(crate::shared::palette::DEFAULT.selected_style())
+                .style(crate::shared::palette::current().default_style())
+                .column_highlight_style(crate::shared::palette::current().highlighted_darker_fg())
+                .row_highlight_style(crate::shared::palette::current().highlighted_darker_fg())
+                .cell_highlight_style(crate::shared::palette::current().selected_style())
                 .column_spacing(1);
 
             return table;
@@ -663,6 +1563,18 @@ impl TatTable {
         Table::default()
     }
 
+    /// Returns the width constraints of the given columns
+    fn column_constraints(&self, columns: &[u64]) -> Vec<Constraint> {
+        columns.iter().map(|col| {
+            if self.column_has_fixed_width(*col) {
+                // the width reserved for a column includes the spacing
+                Constraint::Length(self.column_width(*col).saturating_sub(1) as u16)
+            } else {
+                Constraint::Fill(1)
+            }
+        }).collect()
+    }
+
     /// Returns the number of rows currently visible
     fn visible_rows(&self) -> u64 {
         if self.layer_schema().is_none() {
@@ -688,11 +1600,9 @@ impl TatTable {
             return 0;
         }
 
-        if self.layer_schema().unwrap().field_count() * (MIN_COLUMN_LENGTH as u64) < self.table_rect.width as u64 {
-            self.layer_schema().unwrap().field_count() as u64
-        } else {
-            (self.table_rect.width / MIN_COLUMN_LENGTH as u16) as u64
-        }
+        let pinned = self.pinned_count();
+
+        pinned + self.scrolled_columns_from(self.first_column.max(pinned))
     }
 
     /// Returns whether all rows are currently visible
@@ -717,11 +1627,11 @@ impl TatTable {
         let block = Block::new()
             .border_set(border_symbols)
             .borders(borders)
-            .fg(crate::shared::palette::DEFAULT.default_fg);
+            .fg(crate::shared::palette::current().default_fg);
 
         let fid_header = Line::raw(
-            "Feature"
-        ).bold().underlined().fg(crate::shared::palette::DEFAULT.default_fg);
+            if self.show_fids { "FID" } else { "Feature" }
+        ).bold().underlined().fg(crate::shared::palette::current().default_fg);
 
         let header_area = if preview {
             Rect {
@@ -758,13 +1668,24 @@ impl TatTable {
         frame.render_widget(block, block_rect);
         frame.render_widget(fid_header, header_area);
 
-        for (i, fid) in (self.top_row..=self.bottom_row()).enumerate() {
+        let fids = if self.show_fids { self.visible_fids() } else { vec![] };
+
+        for (i, row) in (self.top_row..=self.bottom_row()).enumerate() {
+            let label = if self.show_fids {
+                fids.get(i).map_or(String::new(), |fid| fid.to_string())
+            } else {
+                row.to_string()
+            };
+
             let line = Line::raw(
-                format!(
-                    "{}",
-                    fid,
-                ),
-            ).bold().fg(crate::shared::palette::DEFAULT.default_fg);
+                label,
+            ).style(
+                if self.row_selected(row) {
+                    crate::shared::palette::current().marked_style()
+                } else {
+                    crate::shared::palette::current().default_style()
+                }
+            ).bold();
             let rect = Rect {
                 x: self.feature_col_rect.x,
                 y: self.feature_col_rect.y + i as u16 + if preview { 2 } else { 3 },
@@ -791,7 +1712,8 @@ mod test {
     #[allow(unused)]
     use super::*;
 
-    use crate::fixtures::{basic_table, TatTestStructure, TatTestUtils};
+    use crate::{app::TatEvent, dataset::DatasetResponse, fixtures::{basic_table, TatTestStructure, TatTestUtils}};
+    use ratatui::{backend::TestBackend, Terminal};
 
     use rstest::*;
 
@@ -1094,6 +2016,280 @@ mod test {
         test.terminate();
     }
 
+    #[rstest]
+    fn test_nav_h_specific(basic_table: (TatTestStructure, TatTable)) {
+        let (test, mut t) = basic_table;
+        t.set_layer_index(4);
+
+        t.nav_h(TatNavHorizontal::Specific(8));
+        assert_eq!(t.current_column(), 8);
+        assert_eq!(t.relative_highlighted_column(), 6);
+        assert_eq!(t.first_column, 2);
+
+        t.nav_h(TatNavHorizontal::Specific(1));
+        assert_eq!(t.current_column(), 1);
+        assert_eq!(t.relative_highlighted_column(), 0);
+        assert_eq!(t.first_column, 1);
+
+        t.nav_h(TatNavHorizontal::Specific(3));
+        assert_eq!(t.current_column(), 3);
+        assert_eq!(t.relative_highlighted_column(), 2);
+        assert_eq!(t.first_column, 1);
+
+        // columns which do not exist or are hidden cannot be navigated to
+        t.nav_h(TatNavHorizontal::Specific(100));
+        assert_eq!(t.current_column(), 3);
+
+        test.terminate();
+    }
+
+    #[rstest]
+    fn test_click(basic_table: (TatTestStructure, TatTable)) {
+        let (test, mut t) = basic_table;
+        t.set_layer_index(4);
+
+        assert!(t.click(84, 5, false));
+        assert_eq!(t.current_row(), 3);
+        assert_eq!(t.relative_highlighted_column(), 2);
+
+        // the header only selects the column
+        assert!(t.click(12, 2, false));
+        assert_eq!(t.current_row(), 3);
+        assert_eq!(t.relative_highlighted_column(), 0);
+
+        // the feature column only selects the row
+        assert!(t.click(2, 3, false));
+        assert_eq!(t.current_row(), 1);
+        assert_eq!(t.relative_highlighted_column(), 0);
+
+        // borders and scrollbars are not cells
+        assert!(!t.click(84, 1, false));
+        assert!(!t.click(249, 5, false));
+        assert_eq!(t.current_row(), 1);
+
+        t.nav_v(TatNavVertical::Fraction(1.0));
+        assert_eq!(t.top_row as i64, t.max_top_row().max(1));
+        t.nav_v(TatNavVertical::Fraction(0.0));
+        assert_eq!(t.top_row, 1);
+
+        t.nav_h(TatNavHorizontal::Fraction(1.0));
+        assert_eq!(t.first_column as i64, t.max_first_column());
+        t.nav_h(TatNavHorizontal::Fraction(0.0));
+        assert_eq!(t.first_column, 0);
+
+        test.terminate();
+    }
+
+    #[rstest]
+    fn test_columns(basic_table: (TatTestStructure, TatTable)) {
+        let (test, mut t) = basic_table;
+        t.set_layer_index(4);
+
+        t.hide_current_column();
+        assert_eq!(t.columns(), &[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(t.hidden_column_count(), 1);
+        assert_eq!(t.current_column(), 1);
+
+        t.move_current_column(true);
+        assert_eq!(t.columns(), &[1, 2, 3, 4, 5, 6, 7, 8]);
+
+        t.move_current_column(false);
+        assert_eq!(t.columns(), &[2, 1, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(t.current_column(), 1);
+        assert_eq!(t.relative_highlighted_column(), 1);
+
+        assert!(t.toggle_pin_current_column());
+        assert_eq!(t.columns(), &[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert!(t.column_pinned(1));
+        assert_eq!(t.current_column(), 1);
+        assert_eq!(t.relative_highlighted_column(), 0);
+
+        t.nav_h(TatNavHorizontal::End);
+        assert_eq!(t.first_column, 2);
+        assert_eq!(t.current_column(), 8);
+        assert_eq!(t.current_attribute_view().columns, vec![1, 3, 4, 5, 6, 7, 8]);
+
+        for _ in 0..5 {
+            t.nav_h(TatNavHorizontal::LeftOne);
+        }
+        assert_eq!(t.first_column, 2);
+        assert_eq!(t.current_column(), 3);
+
+        t.nav_h(TatNavHorizontal::LeftOne);
+        assert_eq!(t.first_column, 1);
+        assert_eq!(t.current_column(), 2);
+        assert_eq!(t.current_attribute_view().columns, vec![1, 2, 3, 4, 5, 6, 7]);
+
+        t.nav_h(TatNavHorizontal::LeftOne);
+        assert_eq!(t.current_column(), 1);
+
+        t.show_all_columns();
+        assert_eq!(t.columns(), &[1, 2, 3, 4, 5, 6, 7, 8, 0]);
+        assert_eq!(t.hidden_column_count(), 0);
+
+        t.reset_columns();
+        assert_eq!(t.columns(), &[0, 1, 2, 3, 4, 5, 6, 7, 8]);
+        assert!(!t.column_pinned(1));
+        assert_eq!(t.current_column(), 1);
+
+        // hiding another column keeps the highlighted one
+        assert!(t.hide_column(5));
+        assert!(!t.hide_column(5));
+        assert_eq!(t.columns(), &[0, 1, 2, 3, 4, 6, 7, 8]);
+        assert_eq!(t.current_column(), 1);
+
+        test.terminate();
+    }
+
+    #[rstest]
+    fn test_layer_view(basic_table: (TatTestStructure, TatTable)) {
+        let (test, mut t) = basic_table;
+        t.set_layer_index(4);
+
+        t.nav_v(TatNavVertical::Specific(30));
+        t.nav_h(TatNavHorizontal::Specific(3));
+        t.hide_current_column();
+        t.toggle_mark();
+        t.toggle_visual();
+
+        let (top_row, first_column) = (t.top_row, t.first_column);
+
+        t.set_layer_index(0);
+        assert_eq!(t.top_row, 1);
+        assert_eq!(t.current_row(), 1);
+        assert_eq!(t.hidden_column_count(), 0);
+        assert!(t.selected_rows().is_empty());
+
+        t.set_layer_index(4);
+        assert_eq!(t.top_row, top_row);
+        assert_eq!(t.first_column, first_column);
+        assert_eq!(t.current_row(), 30);
+        assert_eq!(t.current_column(), 4);
+        assert_eq!(t.hidden_column_count(), 1);
+        assert_eq!(t.selected_rows(), vec![30]);
+        assert!(!t.visual_active());
+
+        test.terminate();
+    }
+
+    #[rstest]
+    fn test_jumps(basic_table: (TatTestStructure, TatTable)) {
+        let (test, mut t) = basic_table;
+        t.set_layer_index(4);
+
+        t.nav_v(TatNavVertical::Specific(10));
+        t.nav_h(TatNavHorizontal::Specific(2));
+        t.set_mark('a');
+        assert!(!t.jump_to_mark('b'));
+
+        t.record_jump();
+        t.nav_v(TatNavVertical::Last);
+        assert!(t.jump_to_mark('a'));
+        assert_eq!((t.current_row(), t.current_column()), (10, 2));
+
+        assert!(t.jump_in_list(false));
+        assert_eq!(t.current_row(), 60);
+        assert!(!t.jump_in_list(false));
+        assert!(t.jump_in_list(true));
+        assert_eq!((t.current_row(), t.current_column()), (10, 2));
+        assert!(!t.jump_in_list(true));
+
+        // the marks are kept per layer
+        t.set_layer_index(0);
+        assert!(!t.jump_to_mark('a'));
+        t.set_layer_index(4);
+        assert!(t.jump_to_mark('a'));
+        assert_eq!(t.current_row(), 10);
+
+        test.terminate();
+    }
+
+    #[rstest]
+    fn test_column_widths(basic_table: (TatTestStructure, TatTable)) {
+        let (test, mut t) = basic_table;
+        t.set_layer_index(4);
+
+        assert!(!t.fit_to_content());
+        assert_eq!(t.column_width(1), config().min_column_width);
+
+        t.toggle_fit_to_content();
+        assert!(t.fit_to_content());
+
+        let schema = t.layer_schema().unwrap();
+        let name_width = schema.field_name_by_id(1).unwrap().width() as u64;
+        let expected = name_width.max(schema.content_width(1) as u64).max(4).clamp(config().min_fitted_column_width, config().max_fitted_column_width) + 1;
+        assert_eq!(t.column_width(1), expected);
+        assert!(t.columns().iter().all(|col| t.column_width(*col) <= config().max_fitted_column_width + 1));
+        assert!(t.visible_columns() >= 7);
+
+        t.toggle_fit_to_content();
+        t.nav_h(TatNavHorizontal::RightOne);
+        t.resize_current_column(true);
+        assert_eq!(t.column_width(1), config().min_column_width + config().column_resize_step);
+        assert_eq!(t.current_column(), 1);
+        assert!(t.visible_columns() <= 7);
+
+        for _ in 0..50 {
+            t.resize_current_column(false);
+        }
+        assert_eq!(t.column_width(1), config().min_fitted_column_width + 1);
+        assert!(t.visible_columns() >= 7);
+
+        t.reset_columns();
+        assert_eq!(t.column_width(1), config().min_column_width);
+
+        test.terminate();
+    }
+
+    #[rstest]
+    fn test_visible_fids(basic_table: (TatTestStructure, TatTable)) {
+        let (test, mut t) = basic_table;
+
+        TatTestUtils::set_layer_index_and_update(4, &mut t, &test.tatevent_rx);
+
+        assert!(!t.show_fids());
+        t.toggle_show_fids();
+        assert!(t.show_fids());
+
+        assert_eq!(t.visible_fids(), (1..=t.visible_rows()).collect::<Vec<u64>>());
+
+        // the FIDs of a filtered layer are no longer the row numbers
+        test.ds_request_tx.send(DatasetRequest::SetFilter(4, Some("i32_field > 900".to_string()))).unwrap();
+        match test.tatevent_rx.recv().unwrap() {
+            TatEvent::Dataset(DatasetResponse::LayerUpdated(_, Ok(schema))) => t.update_layer_schema(*schema),
+            _ => panic!(),
+        }
+        TatTestUtils::wait_attribute_view_update(&test.tatevent_rx);
+
+        assert_eq!(t.visible_fids(), vec![5, 6, 34, 43, 47, 48]);
+
+        test.terminate();
+    }
+
+    #[rstest]
+    fn test_render_stale_view(basic_table: (TatTestStructure, TatTable)) {
+        let (test, mut t) = basic_table;
+        let mut terminal = Terminal::new(TestBackend::new(250, 20)).unwrap();
+
+        TatTestUtils::set_layer_index_and_update(4, &mut t, &test.tatevent_rx);
+
+        // a copy which the dataset thread does not update, like a view which has not been
+        // updated yet
+        let view = t.attribute_view.as_ref().unwrap().lock().unwrap();
+        let stale = TatAttributeView { rows: view.rows.clone(), fids: view.fids.clone(), summaries: view.summaries.clone() };
+        drop(view);
+        t.set_attribute_view(Arc::new(Mutex::new(stale)));
+
+        t.hide_current_column();
+        let cells = t.attribute_view.as_ref().unwrap().lock().unwrap().rows[0].len();
+        assert!(cells > t.visible_column_indices().len());
+        terminal.draw(|frame| t.render(frame)).unwrap();
+
+        TatTestUtils::wait_attribute_view_update(&test.tatevent_rx);
+
+        test.terminate();
+    }
+
     #[rstest]
     fn test_selected_value(basic_table: (TatTestStructure, TatTable)) {
         let (test, mut t) = basic_table;
@@ -1145,4 +2341,46 @@ mod test {
 
         test.terminate();
     }
+
+    #[rstest]
+    fn test_selection(basic_table: (TatTestStructure, TatTable)) {
+        let (test, mut t) = basic_table;
+        t.set_layer_index(4);
+
+        assert_eq!(t.selected_rows(), Vec::<u64>::new());
+        assert_eq!(t.selected_rows_or_current(), vec![1]);
+
+        t.toggle_mark();
+        t.nav_v(TatNavVertical::Specific(3));
+        t.toggle_mark();
+        assert_eq!(t.selected_rows(), vec![1, 3]);
+        assert!(t.row_selected(3));
+        assert!(!t.row_selected(2));
+
+        t.toggle_mark();
+        assert_eq!(t.selected_rows(), vec![1]);
+
+        t.nav_v(TatNavVertical::Specific(5));
+        t.toggle_visual();
+        assert!(t.visual_active());
+        t.nav_v(TatNavVertical::Specific(7));
+        assert_eq!(t.selected_rows(), vec![1, 5, 6, 7]);
+        assert_eq!(t.selected_count(), 4);
+
+        t.cancel_visual();
+        assert_eq!(t.selected_rows(), vec![1]);
+
+        t.toggle_visual();
+        t.nav_v(TatNavVertical::Specific(6));
+        t.toggle_visual();
+        assert!(!t.visual_active());
+        assert_eq!(t.selected_rows(), vec![1, 6, 7]);
+        assert_eq!(t.selected_count(), 3);
+
+        t.clear_selection();
+        assert_eq!(t.selected_count(), 0);
+        assert_eq!(t.selected_rows_or_current(), vec![6]);
+
+        test.terminate();
+    }
 }
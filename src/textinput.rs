@@ -0,0 +1,544 @@
+use std::collections::HashMap;
+
+use crossterm::event::KeyCode;
+use ratatui::{layout::{Constraint, Layout, Position, Rect}, style::Stylize, text::Span, Frame};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The number of entries kept in the history of each purpose, the oldest ones are dropped first
+const MAX_HISTORY: usize = 100;
+
+/// A function which checks the text before it is accepted, returning the error to show if the
+/// text is invalid
+pub type TatValidator = fn(&str) -> Result<(), String>;
+
+/// Result of handling a key press in the text input
+#[derive(Debug, PartialEq)]
+pub enum TatTextInputResult {
+    RejectedKey,
+    AcceptedKey,
+    Close,
+    Accept(String),
+}
+
+/// Specifies what a text input is used for, each purpose has a history of its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TatInputPurpose {
+    Command,
+    Search,
+    ExportPath,
+}
+
+/// The earlier accepted texts of each input purpose, newest last
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TatInputHistory {
+    entries: HashMap<TatInputPurpose, Vec<String>>,
+}
+
+impl TatInputHistory {
+    /// Adds the text to the end of the history of the purpose. An earlier entry of the same text
+    /// is removed.
+    pub fn add(&mut self, purpose: TatInputPurpose, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+
+        let entries = self.entries.entry(purpose).or_default();
+        entries.retain(|entry| entry != text);
+        entries.push(text.to_string());
+
+        if entries.len() > MAX_HISTORY {
+            entries.remove(0);
+        }
+    }
+
+    /// Returns the history of the purpose
+    pub fn entries(&self, purpose: TatInputPurpose) -> Vec<String> {
+        self.entries.get(&purpose).cloned().unwrap_or_default()
+    }
+}
+
+/// A single-line widget for getting a free-form text value from the user. Supports
+/// readline-style editing, browsing a history and validating the text before it is accepted.
+#[derive(Debug)]
+pub struct TatTextInput {
+    string: String,
+    /// The position of the cursor in graphemes
+    cursor_pos: usize,
+    validator: Option<TatValidator>,
+    history: Vec<String>,
+    /// The position in the history, equal to its length when not browsing it
+    history_index: usize,
+    /// The text which was typed before browsing the history
+    typed: String,
+    /// Shown after the text until the next accepted key
+    error: Option<String>,
+}
+
+impl TatTextInput {
+    /// Constructs a new widget.
+    pub fn new() -> Self {
+        Self {
+            string: "".to_string(),
+            cursor_pos: 0,
+            validator: None,
+            history: vec![],
+            history_index: 0,
+            typed: "".to_string(),
+            error: None,
+        }
+    }
+
+    /// Constructs a new widget with an initial value. The cursor is placed at the end.
+    pub fn with_text(text: &str) -> Self {
+        let mut input = Self::new();
+        input.set_text(text);
+
+        input
+    }
+
+    /// Sets the earlier texts which can be browsed with Up and Down (or Ctrl+P and Ctrl+N)
+    pub fn with_history(mut self, history: Vec<String>) -> Self {
+        self.history_index = history.len();
+        self.history = history;
+
+        self
+    }
+
+    /// Sets the function which has to accept the text before it can be accepted with Enter
+    pub fn with_validator(mut self, validator: TatValidator) -> Self {
+        self.validator = Some(validator);
+
+        self
+    }
+
+    /// Returns the current text
+    pub fn text(&self) -> &str {
+        &self.string
+    }
+
+    /// Replaces the text and places the cursor at the end
+    pub fn set_text(&mut self, text: &str) {
+        self.string = text.to_string();
+        self.cursor_pos = self.grapheme_count();
+        self.error = None;
+    }
+
+    /// Returns the error which is currently shown (if any)
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Shows an error after the text until the next accepted key
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    /// Renders the current state of the widget. The cursor is only shown if the widget is focused.
+    pub fn render(&self, frame: &mut Frame, area: Rect, focused: bool) {
+        let palette = crate::shared::palette::current();
+
+        let text_area = match self.error.as_ref() {
+            Some(error) => {
+                // the text keeps at least a third of the width
+                let error = format!("  {}", error);
+                let error_width = (Span::raw(&error).width() as u16).min(area.width - area.width / 3);
+                let [text_area, error_area] = Layout::horizontal([
+                    Constraint::Length(area.width - error_width),
+                    Constraint::Length(error_width),
+                ]).areas(area);
+
+                frame.render_widget(Span::styled(error, palette.highlighted_style()).bold(), error_area);
+
+                text_area
+            },
+            None => area,
+        };
+
+        let graphemes: Vec<&str> = self.string.graphemes(true).collect();
+        let widths: Vec<usize> = graphemes.iter().map(|g| Span::raw(*g).width()).collect();
+
+        // keep the cursor visible if the text does not fit
+        let mut skip = 0;
+        let mut cursor_x: usize = widths[..self.cursor_pos].iter().sum();
+        while skip < self.cursor_pos && cursor_x + 1 > text_area.width as usize {
+            cursor_x -= widths[skip];
+            skip += 1;
+        }
+
+        frame.render_widget(Span::styled(graphemes[skip..].concat(), palette.default_style()), text_area);
+
+        if focused {
+            frame.set_cursor_position(Position {
+                x: text_area.x + cursor_x as u16,
+                y: text_area.y,
+            });
+        }
+    }
+
+    /// Handles the incoming key code, rejecting any unaccepted keys. Words are separated by
+    /// whitespace.
+    pub fn key_press(&mut self, key: KeyCode, ctrl_down: bool) -> TatTextInputResult {
+        let error = self.error.take();
+
+        let result = match key {
+            KeyCode::Esc => TatTextInputResult::Close,
+            KeyCode::Enter => return self.accept(),
+            KeyCode::Up => self.browse_history(true),
+            KeyCode::Char('p') if ctrl_down => self.browse_history(true),
+            KeyCode::Down => self.browse_history(false),
+            KeyCode::Char('n') if ctrl_down => self.browse_history(false),
+            KeyCode::Home => self.move_cursor(0),
+            KeyCode::Char('a') if ctrl_down => self.move_cursor(0),
+            KeyCode::End => self.move_cursor(self.grapheme_count()),
+            KeyCode::Char('e') if ctrl_down => self.move_cursor(self.grapheme_count()),
+            KeyCode::Left if ctrl_down => self.move_cursor(self.previous_word_start()),
+            KeyCode::Right if ctrl_down => self.move_cursor(self.next_word_end()),
+            KeyCode::Left => self.move_cursor(self.cursor_pos.saturating_sub(1)),
+            KeyCode::Char('b') if ctrl_down => self.move_cursor(self.cursor_pos.saturating_sub(1)),
+            KeyCode::Right => self.move_cursor(self.cursor_pos + 1),
+            KeyCode::Char('f') if ctrl_down => self.move_cursor(self.cursor_pos + 1),
+            KeyCode::Backspace => self.delete(self.cursor_pos.saturating_sub(1), self.cursor_pos),
+            KeyCode::Delete => self.delete(self.cursor_pos, self.cursor_pos + 1),
+            KeyCode::Char('d') if ctrl_down => self.delete(self.cursor_pos, self.cursor_pos + 1),
+            KeyCode::Char('w') if ctrl_down => self.delete(self.previous_word_start(), self.cursor_pos),
+            KeyCode::Char('u') if ctrl_down => self.delete(0, self.cursor_pos),
+            KeyCode::Char('k') if ctrl_down => self.delete(self.cursor_pos, self.grapheme_count()),
+            KeyCode::Char(_) if ctrl_down => TatTextInputResult::RejectedKey,
+            KeyCode::Char(ch) => self.insert(ch),
+            _ => TatTextInputResult::RejectedKey,
+        };
+
+        if result == TatTextInputResult::RejectedKey {
+            self.error = error;
+        }
+
+        result
+    }
+
+    /// Accepts the text unless the validator rejects it, in which case the error is shown
+    fn accept(&mut self) -> TatTextInputResult {
+        if let Some(validator) = self.validator
+            && let Err(e) = validator(&self.string)
+        {
+            self.error = Some(e);
+
+            return TatTextInputResult::AcceptedKey;
+        }
+
+        self.error = None;
+
+        TatTextInputResult::Accept(self.string.clone())
+    }
+
+    /// Replaces the text with the previous (or next) entry in the history. Moving past the newest
+    /// entry restores the typed text.
+    fn browse_history(&mut self, backwards: bool) -> TatTextInputResult {
+        if self.history.is_empty() {
+            return TatTextInputResult::RejectedKey;
+        }
+
+        if backwards {
+            if self.history_index == 0 {
+                return TatTextInputResult::AcceptedKey;
+            }
+
+            if self.history_index == self.history.len() {
+                self.typed = self.string.clone();
+            }
+
+            self.history_index -= 1;
+        } else {
+            if self.history_index >= self.history.len() {
+                return TatTextInputResult::AcceptedKey;
+            }
+
+            self.history_index += 1;
+        }
+
+        let text = self.history.get(self.history_index).unwrap_or(&self.typed).clone();
+        self.set_text(&text);
+
+        TatTextInputResult::AcceptedKey
+    }
+
+    /// Returns the number of graphemes in the text
+    fn grapheme_count(&self) -> usize {
+        self.string.graphemes(true).count()
+    }
+
+    /// Returns the byte index of the given grapheme position
+    fn byte_index(&self, grapheme_pos: usize) -> usize {
+        self.string
+            .grapheme_indices(true)
+            .nth(grapheme_pos)
+            .map_or(self.string.len(), |(i, _)| i)
+    }
+
+    /// Returns the grapheme position of the start of the word before the cursor
+    fn previous_word_start(&self) -> usize {
+        let graphemes: Vec<&str> = self.string.graphemes(true).collect();
+        let mut pos = self.cursor_pos;
+
+        while pos > 0 && is_whitespace(graphemes[pos - 1]) {
+            pos -= 1;
+        }
+
+        while pos > 0 && !is_whitespace(graphemes[pos - 1]) {
+            pos -= 1;
+        }
+
+        pos
+    }
+
+    /// Returns the grapheme position of the end of the word after the cursor
+    fn next_word_end(&self) -> usize {
+        let graphemes: Vec<&str> = self.string.graphemes(true).collect();
+        let mut pos = self.cursor_pos;
+
+        while pos < graphemes.len() && is_whitespace(graphemes[pos]) {
+            pos += 1;
+        }
+
+        while pos < graphemes.len() && !is_whitespace(graphemes[pos]) {
+            pos += 1;
+        }
+
+        pos
+    }
+
+    /// Moves the cursor to the given grapheme position, limited to the end of the text
+    fn move_cursor(&mut self, pos: usize) -> TatTextInputResult {
+        self.cursor_pos = pos.min(self.grapheme_count());
+
+        TatTextInputResult::AcceptedKey
+    }
+
+    /// Deletes the graphemes between the positions and moves the cursor to the start of the range
+    fn delete(&mut self, start: usize, end: usize) -> TatTextInputResult {
+        let range = self.byte_index(start)..self.byte_index(end);
+        self.string.replace_range(range, "");
+        self.cursor_pos = start.min(self.grapheme_count());
+
+        TatTextInputResult::AcceptedKey
+    }
+
+    /// Inserts the typed character at the cursor. A combining character joins the grapheme
+    /// before the cursor.
+    fn insert(&mut self, ch: char) -> TatTextInputResult {
+        let index = self.byte_index(self.cursor_pos);
+        self.string.insert(index, ch);
+        self.cursor_pos = self.string[..index + ch.len_utf8()].graphemes(true).count();
+
+        TatTextInputResult::AcceptedKey
+    }
+}
+
+impl PartialEq for TatTextInput {
+    // function pointers can't be compared reliably, so the validator is ignored
+    fn eq(&self, other: &Self) -> bool {
+        self.string == other.string
+            && self.cursor_pos == other.cursor_pos
+            && self.history == other.history
+            && self.history_index == other.history_index
+            && self.typed == other.typed
+            && self.error == other.error
+    }
+}
+
+impl Default for TatTextInput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns whether the grapheme separates words
+fn is_whitespace(grapheme: &str) -> bool {
+    grapheme.chars().all(char::is_whitespace)
+}
+
+#[cfg(test)]
+mod test {
+    #[allow(unused)]
+    use super::*;
+
+    use ratatui::{backend::TestBackend, Terminal};
+
+    #[test]
+    fn test_with_text() {
+        let ti = TatTextInput::with_text("päivä.gpkg");
+        assert_eq!(ti.text(), "päivä.gpkg");
+        assert_eq!(ti.cursor_pos, 10);
+    }
+
+    #[test]
+    fn test_editing() {
+        let mut ti = TatTextInput::with_text("äb");
+
+        assert_eq!(ti.key_press(KeyCode::Left, false), TatTextInputResult::AcceptedKey);
+        ti.key_press(KeyCode::Char('ö'), false);
+        assert_eq!(ti.text(), "äöb");
+        assert_eq!(ti.cursor_pos, 2);
+
+        ti.key_press(KeyCode::Backspace, false);
+        assert_eq!(ti.text(), "äb");
+
+        ti.key_press(KeyCode::Home, false);
+        ti.key_press(KeyCode::Delete, false);
+        assert_eq!(ti.text(), "b");
+        assert_eq!(ti.cursor_pos, 0);
+
+        ti.key_press(KeyCode::Backspace, false);
+        assert_eq!(ti.text(), "b");
+
+        ti.key_press(KeyCode::End, false);
+        ti.key_press(KeyCode::Delete, false);
+        assert_eq!(ti.text(), "b");
+        assert_eq!(ti.cursor_pos, 1);
+
+        assert_eq!(ti.key_press(KeyCode::Char('c'), true), TatTextInputResult::RejectedKey);
+        assert_eq!(ti.text(), "b");
+    }
+
+    #[test]
+    fn test_readline_editing() {
+        let mut ti = TatTextInput::with_text("name = 'a b'  AND x");
+
+        ti.key_press(KeyCode::Char('w'), true);
+        assert_eq!(ti.text(), "name = 'a b'  AND ");
+        ti.key_press(KeyCode::Char('w'), true);
+        assert_eq!(ti.text(), "name = 'a b'  ");
+
+        ti.key_press(KeyCode::Left, true);
+        assert_eq!(ti.cursor_pos, 10);
+        ti.key_press(KeyCode::Left, true);
+        ti.key_press(KeyCode::Left, true);
+        assert_eq!(ti.cursor_pos, 5);
+        ti.key_press(KeyCode::Right, true);
+        assert_eq!(ti.cursor_pos, 6);
+        ti.key_press(KeyCode::Right, true);
+        assert_eq!(ti.cursor_pos, 9);
+
+        ti.key_press(KeyCode::Char('k'), true);
+        assert_eq!(ti.text(), "name = 'a");
+        ti.key_press(KeyCode::Char('a'), true);
+        assert_eq!(ti.cursor_pos, 0);
+        ti.key_press(KeyCode::Char('e'), true);
+        assert_eq!(ti.cursor_pos, 9);
+        ti.key_press(KeyCode::Char('b'), true);
+        ti.key_press(KeyCode::Char('u'), true);
+        assert_eq!(ti.text(), "a");
+        assert_eq!(ti.cursor_pos, 0);
+    }
+
+    #[test]
+    fn test_graphemes() {
+        // "e" followed by a combining acute accent and a family emoji are single graphemes
+        let mut ti = TatTextInput::with_text("cafe\u{301} 👨‍👩‍👧");
+        assert_eq!(ti.cursor_pos, 6);
+
+        ti.key_press(KeyCode::Backspace, false);
+        assert_eq!(ti.text(), "cafe\u{301} ");
+
+        ti.key_press(KeyCode::Left, false);
+        ti.key_press(KeyCode::Left, false);
+        ti.key_press(KeyCode::Delete, false);
+        assert_eq!(ti.text(), "caf ");
+
+        ti.key_press(KeyCode::Char('e'), false);
+        ti.key_press(KeyCode::Char('\u{301}'), false);
+        assert_eq!(ti.text(), "cafe\u{301} ");
+        assert_eq!(ti.cursor_pos, 4);
+    }
+
+    #[test]
+    fn test_history() {
+        let mut history = TatInputHistory::default();
+        history.add(TatInputPurpose::Search, "a");
+        history.add(TatInputPurpose::Search, "b");
+        history.add(TatInputPurpose::Search, "a");
+        history.add(TatInputPurpose::Search, "");
+        history.add(TatInputPurpose::Command, "10");
+        assert_eq!(history.entries(TatInputPurpose::Search), vec!["b", "a"]);
+        assert_eq!(history.entries(TatInputPurpose::ExportPath), Vec::<String>::new());
+
+        for i in 0..200 {
+            history.add(TatInputPurpose::Command, &i.to_string());
+        }
+        assert_eq!(history.entries(TatInputPurpose::Command).len(), MAX_HISTORY);
+        assert_eq!(history.entries(TatInputPurpose::Command)[0], "100");
+
+        let mut ti = TatTextInput::new();
+        assert_eq!(ti.key_press(KeyCode::Up, false), TatTextInputResult::RejectedKey);
+
+        let mut ti = TatTextInput::new().with_history(history.entries(TatInputPurpose::Search));
+        ti.key_press(KeyCode::Char('c'), false);
+
+        ti.key_press(KeyCode::Up, false);
+        assert_eq!(ti.text(), "a");
+        ti.key_press(KeyCode::Char('p'), true);
+        ti.key_press(KeyCode::Up, false);
+        assert_eq!(ti.text(), "b");
+
+        ti.key_press(KeyCode::Down, false);
+        assert_eq!(ti.text(), "a");
+        ti.key_press(KeyCode::Char('n'), true);
+        assert_eq!(ti.text(), "c");
+        ti.key_press(KeyCode::Down, false);
+        assert_eq!(ti.text(), "c");
+    }
+
+    #[test]
+    fn test_validator() {
+        let mut ti = TatTextInput::with_text("12a").with_validator(|text| {
+            match text.parse::<u64>() {
+                Ok(_) => Ok(()),
+                Err(_) => Err("Not a number".to_string()),
+            }
+        });
+
+        assert_eq!(ti.key_press(KeyCode::Enter, false), TatTextInputResult::AcceptedKey);
+        assert_eq!(ti.error(), Some("Not a number"));
+
+        // rejected keys keep the error
+        assert_eq!(ti.key_press(KeyCode::F(1), false), TatTextInputResult::RejectedKey);
+        assert_eq!(ti.error(), Some("Not a number"));
+
+        ti.key_press(KeyCode::Backspace, false);
+        assert_eq!(ti.error(), None);
+        assert_eq!(ti.key_press(KeyCode::Enter, false), TatTextInputResult::Accept("12".to_string()));
+    }
+
+    #[test]
+    fn test_key_press_results() {
+        let mut ti = TatTextInput::with_text("q");
+
+        assert_eq!(ti.key_press(KeyCode::Char('q'), false), TatTextInputResult::AcceptedKey);
+        assert_eq!(ti.key_press(KeyCode::Enter, false), TatTextInputResult::Accept("qq".to_string()));
+        assert_eq!(ti.key_press(KeyCode::Esc, false), TatTextInputResult::Close);
+        assert_eq!(ti.key_press(KeyCode::Tab, false), TatTextInputResult::RejectedKey);
+    }
+
+    #[test]
+    fn test_render() {
+        let render = |ti: &TatTextInput, focused: bool| {
+            let mut terminal = Terminal::new(TestBackend::new(10, 1)).unwrap();
+            terminal.draw(|frame| ti.render(frame, frame.area(), focused)).unwrap();
+
+            terminal
+        };
+
+        // the text scrolls to keep the cursor visible, wide characters take two cells
+        let mut ti = TatTextInput::with_text("abcdef日本");
+        let mut terminal = render(&ti, true);
+        terminal.backend().assert_buffer_lines(["bcdef日本 "]);
+        terminal.backend_mut().assert_cursor_position(Position { x: 9, y: 0 });
+
+        ti.key_press(KeyCode::Home, false);
+        render(&ti, true).backend().assert_buffer_lines(["abcdef日本"]);
+
+        ti.set_error("Bad".to_string());
+        let terminal = render(&ti, false);
+        let line: String = terminal.backend().buffer().content.iter().map(|cell| cell.symbol()).collect();
+        assert_eq!(line, "abcde  Bad");
+    }
+}
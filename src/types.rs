@@ -1,4 +1,7 @@
-use gdal::spatial_ref::SpatialRef;
+use std::fmt::{Display, Write};
+
+use gdal::{spatial_ref::{AxisOrientationType, SpatialRef}, vector::OGRFieldType};
+use gdal_sys::{OGRAxisOrientation, OGRFieldSubType};
 
 /// Enum describing different kinds of vertical navigation
 pub enum TatNavVertical {
@@ -13,6 +16,8 @@ pub enum TatNavVertical {
     Specific(i64),
     MouseScrollUp,
     MouseScrollDown,
+    /// A position between the first (0.0) and the last (1.0) row, e.g. from dragging a scrollbar
+    Fraction(f64),
 }
 
 /// Enum describing different kinds of horizontal navigation
@@ -21,59 +26,127 @@ pub enum TatNavHorizontal {
     End,
     RightOne,
     LeftOne,
+    Specific(u64),
+    /// A position between the first (0.0) and the last (1.0) column, e.g. from dragging a
+    /// scrollbar
+    Fraction(f64),
 }
 
-/// A struct which holds information about a coordinate reference system for displaying purposes
-#[derive(Clone, Debug)]
+/// The order in which the features of a layer are shown
+#[derive(Clone, Debug, PartialEq)]
+pub struct TatSortOrder {
+    /// The name of the attribute field which the features are sorted by
+    pub field: String,
+    pub descending: bool,
+}
+
+/// The kind of a coordinate reference system
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TatCrsKind {
+    Geographic,
+    Projected,
+    Compound,
+    Other,
+}
+
+impl Display for TatCrsKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TatCrsKind::Geographic => write!(f, "Geographic"),
+            TatCrsKind::Projected => write!(f, "Projected"),
+            TatCrsKind::Compound => write!(f, "Compound"),
+            TatCrsKind::Other => write!(f, "Other"),
+        }
+    }
+}
+
+/// A struct which holds information about a coordinate reference system for displaying purposes.
+/// CRSs without an authority code are considered custom.
+#[derive(Clone, Debug, Default)]
 pub struct TatCrs {
-    auth_name: String,
-    auth_code: i32,
-    name: String
+    auth_name: Option<String>,
+    auth_code: Option<i32>,
+    name: String,
+    kind: Option<TatCrsKind>,
+    axes: Vec<String>,
+    units: Option<String>,
+    datum: Option<String>,
+    area_of_use: Option<String>,
+    proj: Option<String>,
+    wkt2: Option<String>,
+    projjson: Option<String>,
 }
 
 impl TatCrs {
     /// Constructs a new object
     pub fn new(a_name: String, a_code: i32, crs_name: String) -> Self {
         Self {
-            auth_name: a_name,
-            auth_code: a_code,
+            auth_name: Some(a_name),
+            auth_code: Some(a_code),
             name: crs_name,
+            ..Default::default()
         }
     }
 
-    /// Constructs a new object from a GDAL SpatialRef object
-    pub fn from_spatial_ref(sref: &SpatialRef) -> Option<Self> {
-        let aname = match sref.auth_name() {
-            Some(a_name) => a_name,
-            _ => return None,
-        };
+    /// Constructs a new object for a CRS which has no authority code
+    pub fn custom(crs_name: String) -> Self {
+        Self {
+            name: crs_name,
+            ..Default::default()
+        }
+    }
 
-        let acode = match sref.auth_code() {
-            Ok(a_code) => a_code,
-            _ => return None,
+    /// Constructs a new object from a GDAL SpatialRef object. A CRS without an authority code is
+    /// a custom one.
+    pub fn from_spatial_ref(sref: &SpatialRef) -> Self {
+        let name = sref.name().unwrap_or("unnamed".to_string());
+
+        let mut crs = match (sref.auth_name(), sref.auth_code()) {
+            (Some(a_name), Ok(a_code)) => TatCrs::new(a_name, a_code, name),
+            _ => TatCrs::custom(name),
         };
 
-        let name = match sref.name() {
-            Some(c_name) => c_name,
-            _ => return None,
+        let kind = if sref.is_compound() {
+            TatCrsKind::Compound
+        } else if sref.is_projected() {
+            TatCrsKind::Projected
+        } else if sref.is_geographic() {
+            TatCrsKind::Geographic
+        } else {
+            TatCrsKind::Other
         };
 
-        Some(
-            TatCrs::new(
-                aname,
-                acode,
-                name,
+        crs.kind = Some(kind);
+        crs.axes = TatCrs::axes_from_spatial_ref(sref, kind);
+        crs.units = match kind {
+            TatCrsKind::Geographic => sref.angular_units_name(),
+            _ => sref.linear_units_name(),
+        };
+        crs.datum = sref.get_attr_value("DATUM", 0).unwrap_or(None);
+        crs.area_of_use = sref.area_of_use().map(|area| {
+            format!(
+                "{} (W: {}, S: {}, E: {}, N: {})",
+                area.name,
+                area.west_lon_degree,
+                area.south_lat_degree,
+                area.east_lon_degree,
+                area.north_lat_degree,
             )
-        )
+        });
+        crs.proj = sref.to_proj4().ok();
+        crs.wkt2 = TatCrs::wkt2_from_spatial_ref(sref);
+        crs.projjson = sref.to_projjson().ok();
+
+        crs
     }
 
-    /// Returns the CRS authority name (e.g. EPSG etc.)
-    pub fn auth_name(&self) -> &str {
-        &self.auth_name
+    /// Returns the CRS authority name (e.g. EPSG etc.), if any
+    pub fn auth_name(&self) -> Option<&str> {
+        self.auth_name.as_deref()
     }
 
-    /// Returns the CRS numerical code (e.g. 4326)
-    pub fn auth_code(&self) -> i32 {
+    /// Returns the CRS numerical code (e.g. 4326), if any
+    pub fn auth_code(&self) -> Option<i32> {
         self.auth_code
     }
 
@@ -81,6 +154,106 @@ impl TatCrs {
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    /// Returns whether the CRS has no authority code
+    pub fn is_custom(&self) -> bool {
+        self.auth_name.is_none() || self.auth_code.is_none()
+    }
+
+    /// Returns a short identifier for the CRS, e.g. "EPSG:4326" or "custom"
+    pub fn identifier(&self) -> String {
+        match (self.auth_name(), self.auth_code()) {
+            (Some(a_name), Some(a_code)) => format!("{a_name}:{a_code}"),
+            _ => "custom".to_string(),
+        }
+    }
+
+    /// Returns the kind of the CRS (if known)
+    pub fn kind(&self) -> Option<TatCrsKind> {
+        self.kind
+    }
+
+    /// Returns a detailed, human-readable description of the CRS
+    pub fn details_text(&self) -> String {
+        let unknown = "unknown".to_string();
+
+        let mut text = format!("- Name: {}\n", self.name);
+        writeln!(text, "- Identifier: {}", self.identifier()).unwrap();
+        writeln!(text, "- Type: {}", self.kind.map(|k| k.to_string()).unwrap_or(unknown.clone())).unwrap();
+
+        if self.axes.is_empty() {
+            writeln!(text, "- Axis order: {unknown}").unwrap();
+        } else {
+            writeln!(text, "- Axis order: {}", self.axes.join(", ")).unwrap();
+        }
+
+        writeln!(text, "- Units: {}", self.units.as_ref().unwrap_or(&unknown)).unwrap();
+        writeln!(text, "- Datum: {}", self.datum.as_ref().unwrap_or(&unknown)).unwrap();
+        writeln!(text, "- Area of use: {}", self.area_of_use.as_ref().unwrap_or(&unknown)).unwrap();
+
+        for (title, value) in [
+            ("PROJ string", &self.proj),
+            ("WKT2", &self.wkt2),
+            ("PROJJSON", &self.projjson),
+        ] {
+            writeln!(text, "\n{title}:\n{}", value.as_ref().unwrap_or(&unknown)).unwrap();
+        }
+
+        text
+    }
+
+    /// Returns the names and directions of the CRS's axes in order
+    fn axes_from_spatial_ref(sref: &SpatialRef, kind: TatCrsKind) -> Vec<String> {
+        let target_keys: &[&str] = match kind {
+            TatCrsKind::Geographic => &["GEOGCS"],
+            TatCrsKind::Projected => &["PROJCS"],
+            _ => &["PROJCS", "GEOGCS"],
+        };
+
+        for key in target_keys {
+            let axes: Vec<String> = (0..sref.axes_count())
+                .map_while(|i| {
+                    let name = sref.axis_name(key, i).ok()?;
+                    let orientation = sref.axis_orientation(key, i).ok()?;
+
+                    match TatCrs::axis_orientation_name(orientation) {
+                        Some(o) => Some(format!("{name} ({o})")),
+                        None => Some(name),
+                    }
+                })
+                .collect();
+
+            if !axes.is_empty() {
+                return axes;
+            }
+        }
+
+        vec![]
+    }
+
+    /// Returns the name of an axis orientation in the same form as GDAL, e.g. "NORTH"
+    fn axis_orientation_name(orientation: AxisOrientationType) -> Option<&'static str> {
+        match orientation {
+            OGRAxisOrientation::OAO_North => Some("NORTH"),
+            OGRAxisOrientation::OAO_South => Some("SOUTH"),
+            OGRAxisOrientation::OAO_East => Some("EAST"),
+            OGRAxisOrientation::OAO_West => Some("WEST"),
+            OGRAxisOrientation::OAO_Up => Some("UP"),
+            OGRAxisOrientation::OAO_Down => Some("DOWN"),
+            OGRAxisOrientation::OAO_Other => Some("OTHER"),
+            _ => None,
+        }
+    }
+
+    /// Exports the CRS as (multiline) WKT2. GDAL exports WKT1 unless another format is configured,
+    /// so the format is set for the current thread while exporting.
+    fn wkt2_from_spatial_ref(sref: &SpatialRef) -> Option<String> {
+        gdal::config::set_thread_local_config_option("OSR_WKT_FORMAT", "WKT2").ok()?;
+        let wkt = sref.to_pretty_wkt().ok();
+        let _ = gdal::config::clear_thread_local_config_option("OSR_WKT_FORMAT");
+
+        wkt
+    }
 }
 
 /// A struct describing a field in a GDAL layer for displaying purposes
@@ -88,14 +261,16 @@ impl TatCrs {
 pub struct TatField {
     name: String,
     dtype: u32,
+    subtype: u32,
 }
 
 impl TatField {
     /// Constructs a new object
-    pub fn new(name: String, dtype: u32) -> Self {
+    pub fn new(name: String, dtype: u32, subtype: u32) -> Self {
         Self {
             name,
             dtype,
+            subtype,
         }
     }
 
@@ -109,6 +284,24 @@ impl TatField {
     pub fn dtype(&self) -> u32 {
         self.dtype
     }
+
+    /// Returns the subtype of the field as a u32 (e.g. OFSTJSON)
+    pub fn subtype(&self) -> u32 {
+        self.subtype
+    }
+
+    /// Returns whether the values of the field are lists or JSON, which are summarized in the
+    /// table and shown as a tree in the value pop-up
+    pub fn is_nested(&self) -> bool {
+        match self.dtype {
+            OGRFieldType::OFTStringList
+            | OGRFieldType::OFTIntegerList
+            | OGRFieldType::OFTInteger64List
+            | OGRFieldType::OFTRealList => true,
+            OGRFieldType::OFTString => self.subtype == OGRFieldSubType::OFSTJSON,
+            _ => false,
+        }
+    }
 }
 
 /// A struct describing a geometry field in a GDAL layer for displaying purposes
@@ -145,3 +338,58 @@ impl TatGeomField {
     }
 }
 
+
+#[cfg(test)]
+mod test {
+    #[allow(unused)]
+    use super::*;
+
+    use crate::fixtures::crs_4326;
+
+    use rstest::*;
+
+    #[rstest]
+    fn test_identifier(crs_4326: TatCrs) {
+        assert_eq!(crs_4326.identifier(), "EPSG:4326");
+        assert!(!crs_4326.is_custom());
+
+        let custom = TatCrs::custom("My CRS".to_string());
+        assert_eq!(custom.identifier(), "custom");
+        assert_eq!(custom.auth_name(), None);
+        assert_eq!(custom.auth_code(), None);
+        assert!(custom.is_custom());
+    }
+
+    #[test]
+    fn test_is_nested() {
+        let field = |dtype: u32, subtype: u32| TatField::new("field".to_string(), dtype, subtype);
+
+        assert!(field(OGRFieldType::OFTStringList, OGRFieldSubType::OFSTNone).is_nested());
+        assert!(field(OGRFieldType::OFTRealList, OGRFieldSubType::OFSTNone).is_nested());
+        assert!(field(OGRFieldType::OFTString, OGRFieldSubType::OFSTJSON).is_nested());
+        assert!(!field(OGRFieldType::OFTString, OGRFieldSubType::OFSTNone).is_nested());
+        assert!(!field(OGRFieldType::OFTInteger, OGRFieldSubType::OFSTBoolean).is_nested());
+    }
+
+    #[test]
+    fn test_from_spatial_ref() {
+        let sref = SpatialRef::from_epsg(3067).unwrap();
+        let crs = TatCrs::from_spatial_ref(&sref);
+
+        assert_eq!(crs.identifier(), "EPSG:3067");
+        assert_eq!(crs.kind(), Some(TatCrsKind::Projected));
+        assert!(crs.details_text().contains("- Units: metre"));
+        assert!(crs.details_text().contains("WKT2:\nPROJCRS[\"ETRS89 / TM35FIN(E,N)\""));
+
+        let sref = SpatialRef::from_proj4("+proj=longlat +ellps=GRS80 +no_defs").unwrap();
+        let crs = TatCrs::from_spatial_ref(&sref);
+
+        assert!(crs.is_custom());
+        assert_eq!(crs.kind(), Some(TatCrsKind::Geographic));
+        assert!(crs.details_text().contains("- Identifier: custom\n- Type: Geographic\n"));
+
+        assert_eq!(TatCrs::axis_orientation_name(OGRAxisOrientation::OAO_East), Some("EAST"));
+        assert_eq!(TatCrs::axis_orientation_name(OGRAxisOrientation::OAO_Other), Some("OTHER"));
+        assert_eq!(TatCrs::axis_orientation_name(100), None);
+    }
+}
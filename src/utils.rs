@@ -1,7 +1,7 @@
 #![allow(unused_imports)]
 use cli_log::*;
 
-use std::{env::temp_dir, fs::OpenOptions};
+use std::fs::OpenOptions;
 use std::io::prelude::Write;
 
 use gdal::errors::CplErrType;
@@ -17,8 +17,8 @@ pub fn error_handler(class: CplErrType, number: i32, message: &str) {
         CplErrType::Fatal => "[FATAL]",
     };
 
-    let path = format!("{}/tat_gdal.log", temp_dir().display());
-    match OpenOptions::new().append(true).open(path.as_str()) {
+    let path = &crate::config::config().gdal_log;
+    match OpenOptions::new().append(true).open(path) {
         Ok(mut file) => {
             match writeln!(file, "{class} [{number}] {message}") {
                 Ok(()) => return,
@@ -0,0 +1,348 @@
+use std::collections::HashSet;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Margin, Rect},
+    style::Stylize,
+    text::Line,
+    widgets::{Block, BorderType, Borders, Clear, List, ListState},
+    Frame,
+};
+use serde_json::Value;
+
+use crate::{config::config, keys::TatAction};
+
+/// The actions which can be used in the value tree
+const ACTIONS: [TatAction; 9] = [
+    TatAction::PreviousMenu,
+    TatAction::Up,
+    TatAction::Down,
+    TatAction::ScrollTop,
+    TatAction::ScrollBottom,
+    TatAction::Left,
+    TatAction::Right,
+    TatAction::ShowValue,
+    TatAction::CopyValue,
+];
+
+/// Returns a compact summary of a list or JSON value for showing it in a cell, e.g. "[3 items]"
+/// or "{…}". Other values are returned as they are.
+pub fn summary(value: &str) -> String {
+    match serde_json::from_str::<Value>(value) {
+        Ok(value @ (Value::Array(_) | Value::Object(_))) => container_summary(&value),
+        _ => value.to_string(),
+    }
+}
+
+/// Returns the summary of a JSON array or object
+fn container_summary(value: &Value) -> String {
+    match value {
+        Value::Array(items) if items.is_empty() => "[]".to_string(),
+        Value::Array(items) if items.len() == 1 => "[1 item]".to_string(),
+        Value::Array(items) => format!("[{} items]", items.len()),
+        Value::Object(map) if map.is_empty() => "{}".to_string(),
+        Value::Object(_) => "{…}".to_string(),
+        _ => value.to_string(),
+    }
+}
+
+/// Returns the items of an array (keyed by their indices) or the members of an object
+fn children(value: &Value) -> Vec<(String, &Value)> {
+    match value {
+        Value::Array(items) => items.iter().enumerate().map(|(i, item)| (i.to_string(), item)).collect(),
+        Value::Object(map) => map.iter().map(|(key, member)| (key.clone(), member)).collect(),
+        _ => vec![],
+    }
+}
+
+/// Result of handling a key press in the value tree
+#[derive(Debug, PartialEq)]
+pub enum TatValueTreeResult {
+    RejectedKey,
+    AcceptedKey,
+    Close,
+    /// Copy the (pretty-printed) value of the selected node to the clipboard
+    CopyValue(String),
+}
+
+/// A visible line of the tree, i.e. a node whose ancestors are all expanded
+struct TatTreeLine<'a> {
+    /// The indices of the children leading from the root to the node
+    path: Vec<usize>,
+    /// The key or index of the node in its parent, None for the root
+    key: Option<String>,
+    value: &'a Value,
+}
+
+/// Shows a list or JSON value as a tree whose arrays and objects can be expanded and collapsed
+#[derive(Debug)]
+pub struct TatValueTree {
+    title: String,
+    root: Value,
+    /// The paths of the expanded nodes, see TatTreeLine
+    expanded: HashSet<Vec<usize>>,
+    state: ListState,
+}
+
+impl TatValueTree {
+    /// Constructs a new tree with only the root expanded. Returns None if the value is not a JSON
+    /// array or object.
+    pub fn new(title: String, value: &str) -> Option<Self> {
+        let root: Value = serde_json::from_str(value).ok()?;
+        if !root.is_array() && !root.is_object() {
+            return None;
+        }
+
+        Some(
+            Self {
+                title,
+                root,
+                expanded: HashSet::from([vec![]]),
+                state: ListState::default().with_selected(Some(0)),
+            }
+        )
+    }
+
+    /// Returns the title of the tree
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Handles the incoming key event. Right expands the selected node or moves into it and Left
+    /// collapses it or moves to its parent. The bound keys take precedence over Space, which
+    /// also expands or collapses the node, and e and c, which expand or collapse all nodes.
+    pub fn key_press(&mut self, key: KeyEvent) -> TatValueTreeResult {
+        let lines = self.lines();
+        let selected = self.selected().min(lines.len() - 1);
+        let path = lines[selected].path.clone();
+        let expandable = !children(lines[selected].value).is_empty();
+        let expanded = self.expanded.contains(&path);
+
+        match (config().keys.view_action(&key, &ACTIONS), key.code) {
+            (Some(TatAction::PreviousMenu), _) => return TatValueTreeResult::Close,
+            (Some(TatAction::CopyValue), _) => {
+                let text = match lines[selected].value {
+                    Value::String(text) => text.clone(),
+                    value => serde_json::to_string_pretty(value).unwrap_or_default(),
+                };
+
+                return TatValueTreeResult::CopyValue(text);
+            },
+            (Some(TatAction::Up), _) => self.state.select(Some(selected.saturating_sub(1))),
+            (Some(TatAction::Down), _) => self.state.select(Some((selected + 1).min(lines.len() - 1))),
+            (Some(TatAction::ScrollTop), _) => self.state.select(Some(0)),
+            (Some(TatAction::ScrollBottom), _) => self.state.select(Some(lines.len() - 1)),
+            (Some(TatAction::ShowValue), _) | (None, KeyCode::Char(' ')) if expandable => {
+                if !self.expanded.remove(&path) {
+                    self.expanded.insert(path);
+                }
+            },
+            (Some(TatAction::Right), _) if expandable => {
+                if expanded {
+                    self.state.select(Some(selected + 1));
+                } else {
+                    self.expanded.insert(path);
+                }
+            },
+            (Some(TatAction::Left), _) => {
+                if expanded {
+                    self.expanded.remove(&path);
+                } else if let Some(parent) = path.split_last().map(|(_, parent)| parent) {
+                    let parent_line = lines.iter().position(|line| line.path == parent);
+                    self.state.select(parent_line);
+                }
+            },
+            (None, KeyCode::Char('e')) => self.expanded = TatValueTree::container_paths(&self.root, vec![]),
+            (None, KeyCode::Char('c')) => {
+                self.expanded = HashSet::from([vec![]]);
+                self.state.select(Some(0));
+            },
+            _ => return TatValueTreeResult::RejectedKey,
+        }
+
+        TatValueTreeResult::AcceptedKey
+    }
+
+    /// Renders the tree into the given area
+    pub fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let block = Block::default()
+            .title(Line::raw(self.title.as_str()).bold().underlined().centered())
+            .borders(Borders::ALL)
+            .border_style(crate::shared::palette::current().highlighted_style())
+            .border_type(BorderType::Rounded)
+            .title_bottom(Line::raw(TatValueTree::hint()).centered());
+
+        let items: Vec<String> = self.lines().iter().map(|line| self.line_text(line)).collect();
+
+        let list = List::new(items)
+            .style(crate::shared::palette::current().default_style())
+            .highlight_style(crate::shared::palette::current().selected_style());
+
+        let inner = area.inner(Margin { horizontal: 1, vertical: 1 });
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+        frame.render_stateful_widget(list, inner, &mut self.state);
+    }
+
+    /// Returns the hint listing the keys of the tree
+    fn hint() -> String {
+        format!(
+            " <{}, e/c: expand/collapse all, {}> ",
+            config().keys.hint(&[(&[TatAction::ShowValue], "expand/collapse")]),
+            config().keys.hint(&[(&[TatAction::CopyValue], "copy"), (&[TatAction::PreviousMenu], "close")]),
+        )
+    }
+
+    /// Returns the index of the selected line
+    fn selected(&self) -> usize {
+        self.state.selected().unwrap_or(0)
+    }
+
+    /// Returns the visible lines in order
+    fn lines(&self) -> Vec<TatTreeLine<'_>> {
+        let mut lines = vec![];
+        self.push_lines(&self.root, vec![], None, &mut lines);
+
+        lines
+    }
+
+    /// Adds the line of the node and the lines of its descendants if it is expanded
+    fn push_lines<'a>(&'a self, value: &'a Value, path: Vec<usize>, key: Option<String>, lines: &mut Vec<TatTreeLine<'a>>) {
+        let expanded = self.expanded.contains(&path);
+
+        lines.push(TatTreeLine { path: path.clone(), key, value });
+
+        if !expanded {
+            return;
+        }
+
+        for (i, (key, child)) in children(value).into_iter().enumerate() {
+            let mut child_path = path.clone();
+            child_path.push(i);
+
+            self.push_lines(child, child_path, Some(key), lines);
+        }
+    }
+
+    /// Returns the text of a line, indented by its depth and prefixed with a marker showing
+    /// whether it is expanded
+    fn line_text(&self, line: &TatTreeLine) -> String {
+        let marker = if children(line.value).is_empty() {
+            "  "
+        } else if self.expanded.contains(&line.path) {
+            "▾ "
+        } else {
+            "▸ "
+        };
+
+        let value = match line.value {
+            Value::Array(_) | Value::Object(_) => container_summary(line.value),
+            value => value.to_string(),
+        };
+
+        match line.key.as_ref() {
+            Some(key) => format!("{}{}{}: {}", "  ".repeat(line.path.len()), marker, key, value),
+            None => format!("{}{}", marker, value),
+        }
+    }
+
+    /// Returns the paths of all non-empty arrays and objects in the value
+    fn container_paths(value: &Value, path: Vec<usize>) -> HashSet<Vec<usize>> {
+        let mut paths = HashSet::new();
+
+        if children(value).is_empty() {
+            return paths;
+        }
+
+        for (i, (_, child)) in children(value).into_iter().enumerate() {
+            let mut child_path = path.clone();
+            child_path.push(i);
+
+            paths.extend(TatValueTree::container_paths(child, child_path));
+        }
+
+        paths.insert(path);
+
+        paths
+    }
+}
+
+impl PartialEq for TatValueTree {
+    // the list state is not compared
+    fn eq(&self, other: &Self) -> bool {
+        self.title == other.title && self.root == other.root && self.expanded == other.expanded
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[allow(unused)]
+    use super::*;
+
+    fn texts(tree: &TatValueTree) -> Vec<String> {
+        tree.lines().iter().map(|line| tree.line_text(line)).collect()
+    }
+
+    #[test]
+    fn test_summary() {
+        assert_eq!(summary("[1, 2, 3]"), "[3 items]");
+        assert_eq!(summary("[\"a\"]"), "[1 item]");
+        assert_eq!(summary("[]"), "[]");
+        assert_eq!(summary("{\"key\": \"value\"}"), "{…}");
+        assert_eq!(summary("{}"), "{}");
+        assert_eq!(summary("\"text\""), "\"text\"");
+        assert_eq!(summary("{not json"), "{not json");
+    }
+
+    #[test]
+    fn test_new() {
+        assert!(TatValueTree::new("title".to_string(), "[1]").is_some());
+        assert!(TatValueTree::new("title".to_string(), "12").is_none());
+        assert!(TatValueTree::new("title".to_string(), "text").is_none());
+    }
+
+    #[test]
+    fn test_key_press() {
+        let value = "{\"name\": \"a\", \"tags\": [\"x\", \"y\"], \"nested\": {\"list\": [1.5], \"empty\": []}}";
+        let mut tree = TatValueTree::new(" Value ".to_string(), value).unwrap();
+
+        assert_eq!(texts(&tree), vec![
+            "▾ {…}",
+            "    name: \"a\"",
+            "  ▸ tags: [2 items]",
+            "  ▸ nested: {…}",
+        ]);
+
+        // expanding with Right and moving into the node with the next Right
+        tree.key_press(KeyCode::Char('j').into());
+        tree.key_press(KeyCode::Char('j').into());
+        tree.key_press(KeyCode::Right.into());
+        assert_eq!(texts(&tree)[3], "      0: \"x\"");
+        tree.key_press(KeyCode::Right.into());
+        assert_eq!(tree.selected(), 3);
+        assert_eq!(tree.key_press(KeyCode::Char('y').into()), TatValueTreeResult::CopyValue("x".to_string()));
+
+        // Left moves to the parent and then collapses it
+        tree.key_press(KeyCode::Left.into());
+        assert_eq!(tree.selected(), 2);
+        tree.key_press(KeyCode::Left.into());
+        assert_eq!(texts(&tree).len(), 4);
+
+        tree.key_press(KeyCode::Char('G').into());
+        tree.key_press(KeyCode::Enter.into());
+        assert_eq!(texts(&tree)[4..], ["    ▸ list: [1 item]", "      empty: []"]);
+        assert_eq!(tree.key_press(KeyCode::Char('y').into()), TatValueTreeResult::CopyValue("{\n  \"list\": [\n    1.5\n  ],\n  \"empty\": []\n}".to_string()));
+
+        tree.key_press(KeyCode::Char('e').into());
+        assert_eq!(texts(&tree).len(), 9);
+        assert_eq!(texts(&tree)[7], "        0: 1.5");
+
+        tree.key_press(KeyCode::Char('c').into());
+        assert_eq!(texts(&tree).len(), 4);
+        assert_eq!(tree.selected(), 0);
+
+        assert_eq!(tree.key_press(KeyCode::Char('x').into()), TatValueTreeResult::RejectedKey);
+        assert_eq!(tree.key_press(KeyCode::Esc.into()), TatValueTreeResult::Close);
+    }
+}